@@ -0,0 +1,525 @@
+//! Display the members of tar, tar.gz, and zip archives in a tree.
+
+use crate::{
+    cli::global::GlobalArgs,
+    errors::NomadError,
+    style::models::NomadStyle,
+    traverse::{
+        models::ItemMetadata,
+        modes::NomadMode,
+        traits::{ToTree, TransformFound},
+        virtual_items::{find_virtual_items, VirtualItem},
+    },
+    utils::export::{get_export, ExportMode},
+};
+
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use flate2::read::{DeflateDecoder, MultiGzDecoder};
+use tar::{Archive, EntryType};
+use zip::{result::ZipError, CompressionMethod, ZipArchive};
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+/// The file type bits of a directory's mode.
+const MODE_DIRECTORY: u32 = 0o040000;
+/// The file type bits of a regular file's mode.
+const MODE_FILE: u32 = 0o100000;
+/// The file type bits of a symlink's mode.
+const MODE_SYMLINK: u32 = 0o120000;
+
+/// Variants for the supported archive formats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArchiveFormat {
+    /// An uncompressed tar archive.
+    Tar,
+    /// A gzip-compressed tar archive.
+    TarGz,
+    /// A zip archive.
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Get the format of an archive from its file extension.
+    pub fn of(archive: &Path) -> Option<Self> {
+        let name = archive.file_name()?.to_str()?.to_lowercase();
+
+        if name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Contains the location of a member's contents within an archive.
+#[derive(Debug)]
+enum MemberLocation {
+    /// The contents start at `offset` within the uncompressed tar stream.
+    Tar { offset: u64, size: u64 },
+    /// The contents start at `offset` within the zip archive and are compressed
+    /// with `method`.
+    Zip {
+        compressed_size: u64,
+        method: CompressionMethod,
+        offset: u64,
+    },
+}
+
+/// Contains a member of an archive.
+#[derive(Debug)]
+struct ArchiveMember {
+    /// The member's path and metadata.
+    item: VirtualItem,
+    /// The location of the member's contents.
+    location: MemberLocation,
+}
+
+/// Check whether a path points to an archive that may be displayed as a tree.
+pub fn is_archive(item: &Path) -> bool {
+    ArchiveFormat::of(item).is_some() && item.is_file()
+}
+
+/// Split the path of an archive member that was displayed in a tree into the
+/// path to the archive and the member's path within it. Returns `None` if the
+/// path is not within an archive.
+pub fn find_archive_member(item: &str) -> Option<(PathBuf, String)> {
+    let path = Path::new(item);
+    if path.exists() {
+        return None;
+    }
+
+    path.ancestors()
+        .skip(1)
+        .find(|ancestor| is_archive(ancestor))
+        .and_then(|archive| {
+            path.strip_prefix(archive).ok().and_then(|member| {
+                member
+                    .to_str()
+                    .map(|member| (archive.to_path_buf(), member.to_string()))
+            })
+        })
+}
+
+/// Read the members of an archive.
+fn read_members(archive: &Path) -> Result<Vec<ArchiveMember>, NomadError> {
+    match ArchiveFormat::of(archive) {
+        Some(ArchiveFormat::Zip) => read_zip_members(archive),
+        Some(format) => read_tar_members(open_tar_stream(archive, format)?),
+        None => Err(NomadError::ArchiveError(format!(
+            "{} is not a tar, tar.gz, or zip archive",
+            archive.display()
+        ))),
+    }
+}
+
+/// Open the uncompressed stream of a tar archive.
+fn open_tar_stream(archive: &Path, format: ArchiveFormat) -> Result<Box<dyn Read>, NomadError> {
+    let file = BufReader::new(File::open(archive)?);
+
+    Ok(match format {
+        ArchiveFormat::TarGz => Box::new(MultiGzDecoder::new(file)),
+        _ => Box::new(file),
+    })
+}
+
+/// Open a member of an archive to stream its contents without extracting it.
+pub fn open_member(archive: &Path, member: &str) -> Result<Box<dyn Read>, NomadError> {
+    let location = read_members(archive)?
+        .into_iter()
+        .rev()
+        .find(|archive_member| {
+            !archive_member.item.is_dir
+                && archive_member
+                    .item
+                    .path
+                    .trim_start_matches("./")
+                    .trim_matches('/')
+                    == member
+        })
+        .map(|archive_member| archive_member.location)
+        .ok_or_else(|| {
+            NomadError::ArchiveError(format!("{member} is not a file in {}", archive.display()))
+        })?;
+
+    match location {
+        MemberLocation::Tar { offset, size } => {
+            let format = ArchiveFormat::of(archive).unwrap_or(ArchiveFormat::Tar);
+            let mut stream = open_tar_stream(archive, format)?;
+            skip_bytes(&mut stream, offset)?;
+
+            Ok(Box::new(stream.take(size)))
+        }
+        MemberLocation::Zip {
+            compressed_size,
+            method,
+            offset,
+        } => {
+            let mut file = BufReader::new(File::open(archive)?);
+            file.seek(SeekFrom::Start(offset))?;
+
+            let contents = file.take(compressed_size);
+            match method {
+                CompressionMethod::Stored => Ok(Box::new(contents)),
+                CompressionMethod::Deflated => Ok(Box::new(DeflateDecoder::new(contents))),
+                _ => Err(NomadError::ArchiveError(format!(
+                    "{member} is compressed with an unsupported method ({method})"
+                ))),
+            }
+        }
+    }
+}
+
+/// Display the members of an archive in a tree.
+pub fn walk_archive(
+    args: &GlobalArgs,
+    nomad_style: &NomadStyle,
+    archive: &str,
+) -> Result<(), NomadError> {
+    let members = read_members(Path::new(archive))?;
+    let export = get_export(args, ExportMode::Archive(archive))?;
+
+    find_virtual_items(
+        args,
+        members.into_iter().map(|member| member.item).collect(),
+        archive,
+    )?
+    .into_iter()
+    .transform(archive)?
+    .to_tree(
+        args,
+        None,
+        export,
+        None,
+        NomadMode::Normal,
+        nomad_style,
+        archive,
+    )?;
+
+    Ok(())
+}
+
+/// Skip a number of bytes in a stream. Raises an error if the stream ends early.
+fn skip_bytes(stream: &mut impl Read, count: u64) -> Result<(), NomadError> {
+    if io::copy(&mut stream.take(count), &mut io::sink())? < count {
+        return Err(NomadError::ArchiveError(
+            "The archive ended unexpectedly".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Convert an error raised while reading a malformed tar archive.
+fn tar_error(error: io::Error) -> NomadError {
+    NomadError::ArchiveError(error.to_string())
+}
+
+/// Read the members of a tar archive from its uncompressed stream. GNU long
+/// names and PAX extended headers are applied to the members they describe.
+fn read_tar_members(stream: impl Read) -> Result<Vec<ArchiveMember>, NomadError> {
+    let mut members = Vec::new();
+
+    let mut archive = Archive::new(stream);
+    for entry in archive.entries().map_err(tar_error)? {
+        let mut entry = entry.map_err(tar_error)?;
+
+        let entry_type = entry.header().entry_type();
+        if matches!(
+            entry_type,
+            EntryType::GNULongLink
+                | EntryType::GNULongName
+                | EntryType::XGlobalHeader
+                | EntryType::XHeader
+        ) {
+            continue;
+        }
+
+        let path = String::from_utf8_lossy(&entry.path_bytes()).to_string();
+        let size = entry.size();
+        let pax_modified = entry
+            .pax_extensions()
+            .map_err(tar_error)?
+            .and_then(|extensions| {
+                extensions
+                    .filter_map(|extension| extension.ok())
+                    .find(|extension| extension.key() == Ok("mtime"))
+                    .and_then(|extension| {
+                        extension
+                            .value()
+                            .ok()
+                            .and_then(|mtime| mtime.split('.').next())
+                            .and_then(|mtime| mtime.parse::<i64>().ok())
+                    })
+            });
+        let modified = pax_modified.or_else(|| {
+            entry
+                .header()
+                .mtime()
+                .ok()
+                .and_then(|mtime| i64::try_from(mtime).ok())
+        });
+
+        let is_dir = entry_type.is_dir() || path.ends_with('/');
+        let file_type = if is_dir {
+            MODE_DIRECTORY
+        } else if entry_type.is_symlink() {
+            MODE_SYMLINK
+        } else {
+            MODE_FILE
+        };
+
+        members.push(ArchiveMember {
+            item: VirtualItem {
+                is_dir,
+                metadata: ItemMetadata {
+                    mode: Some(file_type | (entry.header().mode().unwrap_or(0) & 0o7777)),
+                    modified,
+                    size: Some(size),
+                },
+                path,
+            },
+            location: MemberLocation::Tar {
+                offset: entry.raw_file_position(),
+                size,
+            },
+        });
+    }
+
+    Ok(members)
+}
+
+/// Convert an MS-DOS date and time to a UNIX timestamp in the local timezone.
+fn convert_dos_time(date: u16, time: u16) -> Option<i64> {
+    NaiveDate::from_ymd_opt(
+        ((date >> 9) & 0x7f) as i32 + 1980,
+        ((date >> 5) & 0x0f) as u32,
+        (date & 0x1f) as u32,
+    )?
+    .and_hms_opt(
+        (time >> 11) as u32,
+        ((time >> 5) & 0x3f) as u32,
+        (time & 0x1f) as u32 * 2,
+    )?
+    .and_local_timezone(Local)
+    .single()
+    .map(|date_time| date_time.timestamp())
+}
+
+/// Get the UTC modified time from the extended timestamp field within a zip
+/// member's extra fields, if it has one.
+fn read_extended_timestamp(extra: &[u8]) -> Option<i64> {
+    let mut remaining = extra;
+    while remaining.len() >= 4 {
+        let id = u16::from_le_bytes([remaining[0], remaining[1]]);
+        let length = u16::from_le_bytes([remaining[2], remaining[3]]) as usize;
+        let data = remaining.get(4..4 + length)?;
+
+        if id == 0x5455 && data.len() >= 5 && data[0] & 1 != 0 {
+            return Some(i32::from_le_bytes([data[1], data[2], data[3], data[4]]) as i64);
+        }
+
+        remaining = &remaining[4 + length..];
+    }
+
+    None
+}
+
+/// Convert an error raised while reading a malformed zip archive.
+fn zip_error(error: ZipError) -> NomadError {
+    match error {
+        ZipError::Io(error) => NomadError::IOError(error),
+        error => NomadError::ArchiveError(error.to_string()),
+    }
+}
+
+/// Read the members of a zip archive from its central directory.
+fn read_zip_members(archive: &Path) -> Result<Vec<ArchiveMember>, NomadError> {
+    let mut zip_archive =
+        ZipArchive::new(BufReader::new(File::open(archive)?)).map_err(zip_error)?;
+
+    let mut members = Vec::new();
+    for index in 0..zip_archive.len() {
+        let member = zip_archive.by_index_raw(index).map_err(zip_error)?;
+
+        let path = String::from_utf8_lossy(member.name_raw()).replace('\\', "/");
+        let is_dir = path.ends_with('/');
+        let mode = member
+            .unix_mode()
+            .filter(|mode| *mode != 0)
+            .unwrap_or(if is_dir {
+                MODE_DIRECTORY | 0o755
+            } else {
+                MODE_FILE | 0o644
+            });
+        let modified = read_extended_timestamp(member.extra_data()).or_else(|| {
+            convert_dos_time(
+                member.last_modified().datepart(),
+                member.last_modified().timepart(),
+            )
+        });
+
+        members.push(ArchiveMember {
+            item: VirtualItem {
+                is_dir,
+                metadata: ItemMetadata {
+                    mode: Some(mode),
+                    modified,
+                    size: Some(member.size()),
+                },
+                path,
+            },
+            location: MemberLocation::Zip {
+                compressed_size: member.compressed_size(),
+                method: member.compression(),
+                offset: member.data_start(),
+            },
+        });
+    }
+
+    Ok(members)
+}
+
+#[cfg(test)]
+mod test_archive {
+    use super::*;
+
+    use crate::utils::test_utils::create_fixture;
+
+    use flate2::{write::GzEncoder, Compression};
+    use tar::{Builder, Header};
+    use zip::{write::FileOptions, ZipWriter};
+
+    use std::{
+        fs,
+        io::{Cursor, Write},
+    };
+
+    /// Build a tar archive containing the members.
+    fn build_tar(members: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = Builder::new(Vec::new());
+        for (path, contents) in members {
+            let mut header = Header::new_gnu();
+            header.set_entry_type(if path.ends_with('/') {
+                EntryType::Directory
+            } else {
+                EntryType::Regular
+            });
+            header.set_mode(0o644);
+            header.set_mtime(1610612736);
+            header.set_size(contents.len() as u64);
+            builder
+                .append_data(&mut header, path, contents.as_bytes())
+                .unwrap();
+        }
+
+        builder.into_inner().unwrap()
+    }
+
+    /// Build a zip archive containing deflated members.
+    fn build_zip(members: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, contents) in members {
+            writer
+                .start_file(
+                    *path,
+                    FileOptions::default()
+                        .compression_method(CompressionMethod::Deflated)
+                        .unix_permissions(0o755),
+                )
+                .unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_read_archives() {
+        let fixture = create_fixture(&[]);
+        let target_directory = fixture.path();
+
+        let members = [
+            ("release/", ""),
+            ("release/README.md", "# nomad\n"),
+            ("release/bin/nd", "binary contents"),
+        ];
+
+        let tar = target_directory.join("release.tar");
+        fs::write(&tar, build_tar(&members)).unwrap();
+
+        let tar_gz = target_directory.join("release.tgz");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&build_tar(&members)).unwrap();
+        fs::write(&tar_gz, encoder.finish().unwrap()).unwrap();
+
+        let zip = target_directory.join("release.zip");
+        fs::write(&zip, build_zip(&members[1..])).unwrap();
+
+        for archive in [&tar, &tar_gz, &zip] {
+            let paths = read_members(archive)
+                .unwrap()
+                .into_iter()
+                .filter(|member| !member.item.is_dir)
+                .map(|member| (member.item.path, member.item.metadata.size))
+                .collect::<Vec<(String, Option<u64>)>>();
+            assert_eq!(
+                paths,
+                vec![
+                    ("release/README.md".to_string(), Some(8)),
+                    ("release/bin/nd".to_string(), Some(15)),
+                ]
+            );
+
+            let mut contents = String::new();
+            open_member(archive, "release/bin/nd")
+                .unwrap()
+                .read_to_string(&mut contents)
+                .unwrap();
+            assert_eq!(contents, "binary contents");
+
+            assert!(open_member(archive, "release/missing").is_err());
+        }
+
+        let member = tar.join("release/README.md");
+        assert_eq!(
+            find_archive_member(member.to_str().unwrap()),
+            Some((tar.clone(), "release/README.md".to_string()))
+        );
+        assert_eq!(find_archive_member(tar.to_str().unwrap()), None);
+    }
+
+    #[test]
+    fn test_read_malformed_archives() {
+        let fixture = create_fixture(&[]);
+        let target_directory = fixture.path();
+
+        // A PAX extended header whose base-256 size field holds the largest
+        // possible value.
+        let mut header = Header::new_gnu();
+        header.set_path("pax_header").unwrap();
+        header.set_entry_type(EntryType::XHeader);
+        header.as_mut_bytes()[124..136].copy_from_slice(&[0xff; 12]);
+        header.set_cksum();
+
+        let mut tar = header.as_bytes().to_vec();
+        tar.extend([0; 1024]);
+
+        let truncated_zip = build_zip(&[("release/README.md", "# nomad\n")])[..40].to_vec();
+
+        for (name, contents) in [("huge.tar", tar), ("truncated.zip", truncated_zip)] {
+            let archive = target_directory.join(name);
+            fs::write(&archive, contents).unwrap();
+
+            assert!(read_members(&archive).is_err());
+        }
+    }
+}
@@ -0,0 +1,556 @@
+//! Write checksum manifests and verify directories against them.
+
+use crate::{
+    cli::{
+        checksum::{ChecksumAlgorithm, ChecksumOptions},
+        global::GlobalArgs,
+    },
+    compare::{collect_side, export_node},
+    errors::NomadError,
+    style::models::NomadStyle,
+    traverse::{
+        format::{format_content, format_directory},
+        modes::NomadMode,
+        state::TreeState,
+        utils::{check_nesting, export_root, format_root, get_file_icon},
+        writer::{find_last_items, TreeOutput, TreeWriter},
+    },
+    utils::export::{get_export, ExportMode, ExportNode, NodeType},
+};
+
+use ansi_term::Colour;
+use anyhow::Result;
+use chrono::Local;
+use sha2::{Digest, Sha256};
+
+use std::{
+    collections::BTreeMap,
+    env,
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::{Component, Path, PathBuf},
+    time::Instant,
+};
+
+/// The marker displayed in front of a file whose hash matches the manifest.
+pub const OK_MARKER: &str = "✓";
+
+/// Variants for the result of verifying a file against a manifest.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Verification {
+    /// The file's contents do not match the hash in the manifest.
+    Changed,
+    /// The file is not in the manifest.
+    Extra,
+    /// The file is in the manifest, but does not exist.
+    Missing,
+    /// The file's contents match the hash in the manifest.
+    Ok,
+}
+
+impl Verification {
+    /// Get the marker displayed in front of the file. The Git status markers
+    /// and their colors are reused for files that did not pass.
+    pub fn marker(&self, args: &GlobalArgs, nomad_style: &NomadStyle) -> String {
+        let git_style = &nomad_style.git;
+        let (color, marker) = match self {
+            Verification::Changed => (git_style.modified_color, git_style.modified_marker.as_str()),
+            Verification::Extra => (
+                git_style.untracked_color,
+                git_style.untracked_marker.as_str(),
+            ),
+            Verification::Missing => (git_style.deleted_color, git_style.deleted_marker.as_str()),
+            Verification::Ok => (git_style.staged_added_color, OK_MARKER),
+        };
+
+        if args.style.no_colors {
+            marker.to_string()
+        } else {
+            color.paint(marker).to_string()
+        }
+    }
+}
+
+/// Hash the contents of a file and format the hash as lowercase hexadecimal.
+pub fn hash_file(algorithm: ChecksumAlgorithm, item: &Path) -> Result<String, NomadError> {
+    let mut file = File::open(item)?;
+    let mut buffer = vec![0; 64 * 1024];
+
+    let hash = match algorithm {
+        ChecksumAlgorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            io::copy(&mut file, &mut hasher)?;
+
+            hasher.finalize().as_bytes().to_vec()
+        }
+        ChecksumAlgorithm::Sha256 => {
+            let mut hasher = Sha256::new();
+            loop {
+                let read = file.read(&mut buffer)?;
+                if read == 0 {
+                    break;
+                }
+
+                hasher.update(&buffer[..read]);
+            }
+
+            hasher.finalize().to_vec()
+        }
+    };
+
+    Ok(hash.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Format a line of the manifest in `sha256sum` format. Like `sha256sum`, the
+/// line starts with a `\` if backslashes and newlines in the path are escaped.
+fn format_manifest_line(hash: &str, path: &str) -> String {
+    if path.contains(['\\', '\n']) {
+        format!(
+            "\\{hash}  {}",
+            path.replace('\\', "\\\\").replace('\n', "\\n")
+        )
+    } else {
+        format!("{hash}  {path}")
+    }
+}
+
+/// Parse a manifest in `sha256sum` format into the hash of each path. Lines
+/// written in binary mode (`hash *path`) are accepted as well.
+pub fn parse_manifest(manifest: &str) -> Result<BTreeMap<PathBuf, String>, NomadError> {
+    let mut hashes = BTreeMap::new();
+
+    for (index, line) in manifest.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (escaped, line) = match line.strip_prefix('\\') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+
+        let (hash, path) = line
+            .split_once(' ')
+            .filter(|(hash, path)| {
+                !hash.is_empty()
+                    && hash.chars().all(|character| character.is_ascii_hexdigit())
+                    && (path.starts_with(' ') || path.starts_with('*'))
+            })
+            .ok_or_else(|| {
+                NomadError::ManifestError(format!("line {} is not a checksum line", index + 1))
+            })?;
+
+        let path = if escaped {
+            unescape(&path[1..])
+        } else {
+            path[1..].to_string()
+        };
+
+        hashes.insert(PathBuf::from(path), hash.to_lowercase());
+    }
+
+    Ok(hashes)
+}
+
+/// Undo the escaping of backslashes and newlines in a path.
+fn unescape(path: &str) -> String {
+    let mut unescaped = String::new();
+    let mut characters = path.chars();
+
+    while let Some(character) = characters.next() {
+        match (character, characters.clone().next()) {
+            ('\\', Some('n')) => {
+                unescaped.push('\n');
+                characters.next();
+            }
+            ('\\', Some('\\')) => {
+                unescaped.push('\\');
+                characters.next();
+            }
+            _ => unescaped.push(character),
+        }
+    }
+
+    unescaped
+}
+
+/// Get the path of a file relative to the root of the manifest, separated by `/`.
+fn get_manifest_path(relative_path: &Path) -> String {
+    relative_path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(section) => Some(section.to_str().unwrap_or("?")),
+            _ => None,
+        })
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+/// Write a manifest containing the hash of every file in the directory. The
+/// manifest is written to the `--export` file if provided, otherwise it is printed.
+pub fn write_manifest(
+    checksum_options: &ChecksumOptions,
+    nomad_style: &NomadStyle,
+    target_directory: &str,
+) -> Result<(), NomadError> {
+    let args = &checksum_options.general;
+    let side = collect_side(args, nomad_style, target_directory)?;
+
+    let mut manifest = String::new();
+    for (relative_path, item) in side.items.iter().filter(|(_, item)| item.path.is_file()) {
+        manifest.push_str(&format_manifest_line(
+            &hash_file(checksum_options.algo, &item.path)?,
+            &get_manifest_path(relative_path),
+        ));
+        manifest.push('\n');
+    }
+
+    match args.export {
+        Some(ref filename) => {
+            let filename = filename.clone().unwrap_or_else(|| {
+                format!(
+                    "{}sums_{}.txt",
+                    checksum_options.algo,
+                    Local::now().format("%F_%H-%M-%S")
+                )
+            });
+            let file_path = env::current_dir()?.join(filename);
+
+            File::create(&file_path)?.write_all(manifest.as_bytes())?;
+
+            println!(
+                "{}",
+                Colour::Green.bold().paint(format!(
+                    "Manifest was exported to {}\n",
+                    file_path.display()
+                ))
+            );
+        }
+        None => print!("{manifest}"),
+    }
+
+    Ok(())
+}
+
+/// Contains an item in the tree of a verified directory.
+#[derive(Debug)]
+struct VerifiedItem {
+    /// Indicates whether this is a directory.
+    is_dir: bool,
+    /// Indicates whether this is an empty directory.
+    is_empty: bool,
+    /// The absolute path to the item.
+    path: PathBuf,
+    /// The result of verifying the file. Directories are not verified.
+    verification: Option<Verification>,
+}
+
+/// Counts the files with each verification result.
+#[derive(Debug, Default)]
+pub struct VerificationCounts {
+    /// The number of changed files.
+    changed: usize,
+    /// The number of files that are not in the manifest.
+    extra: usize,
+    /// The number of missing files.
+    missing: usize,
+    /// The number of files that passed.
+    ok: usize,
+}
+
+impl VerificationCounts {
+    /// Count a verified file.
+    pub fn add(&mut self, verification: Verification) {
+        match verification {
+            Verification::Changed => self.changed += 1,
+            Verification::Extra => self.extra += 1,
+            Verification::Missing => self.missing += 1,
+            Verification::Ok => self.ok += 1,
+        }
+    }
+
+    /// Check whether every file passed.
+    pub fn passed(&self) -> bool {
+        self.changed == 0 && self.extra == 0 && self.missing == 0
+    }
+
+    /// Display the number of files with each verification result.
+    pub fn display(&self) {
+        println!(
+            "| {} OK | {} changed | {} missing | {} extra |\n",
+            self.ok, self.changed, self.missing, self.extra
+        );
+    }
+}
+
+/// Verify the files in the directory against a manifest and display the result
+/// of each file in a tree. Returns whether every file passed.
+///
+/// Files in the manifest that were not found by the traversal, ie. because they
+/// are hidden or ignored, are still verified if they exist.
+pub fn verify_manifest(
+    checksum_options: &ChecksumOptions,
+    manifest: &str,
+    nomad_style: &NomadStyle,
+    target_directory: &str,
+) -> Result<bool, NomadError> {
+    let args = &checksum_options.general;
+    let start = Instant::now();
+
+    let mut hashes = parse_manifest(&fs::read_to_string(manifest)?)?;
+    let side = collect_side(args, nomad_style, target_directory)?;
+
+    // Items that are neither directories nor files, such as dangling symlinks,
+    // cannot be hashed and are not written to manifests.
+    let mut items = BTreeMap::new();
+    for (relative_path, item) in side
+        .items
+        .into_iter()
+        .filter(|(_, item)| item.is_dir || item.path.is_file())
+    {
+        let verification = if item.is_dir {
+            None
+        } else {
+            Some(
+                match hashes.remove(Path::new(&get_manifest_path(&relative_path))) {
+                    Some(hash) if hash_file(checksum_options.algo, &item.path)? == hash => {
+                        Verification::Ok
+                    }
+                    Some(_) => Verification::Changed,
+                    None => Verification::Extra,
+                },
+            )
+        };
+
+        items.insert(
+            relative_path,
+            VerifiedItem {
+                is_dir: item.is_dir,
+                is_empty: item.is_empty,
+                path: item.path,
+                verification,
+            },
+        );
+    }
+
+    for (relative_path, hash) in hashes {
+        let path = Path::new(target_directory).join(&relative_path);
+        let verification = if path.is_file() {
+            if hash_file(checksum_options.algo, &path)? == hash {
+                Verification::Ok
+            } else {
+                Verification::Changed
+            }
+        } else {
+            Verification::Missing
+        };
+
+        // The directories containing missing files may not exist anymore.
+        for ancestor in relative_path.ancestors().skip(1) {
+            if !ancestor.as_os_str().is_empty() && !items.contains_key(ancestor) {
+                items.insert(
+                    ancestor.to_path_buf(),
+                    VerifiedItem {
+                        is_dir: true,
+                        is_empty: false,
+                        path: Path::new(target_directory).join(ancestor),
+                        verification: None,
+                    },
+                );
+            }
+        }
+
+        items.insert(
+            relative_path,
+            VerifiedItem {
+                is_dir: false,
+                is_empty: false,
+                path,
+                verification: Some(verification),
+            },
+        );
+    }
+
+    if items.is_empty() {
+        return Err(NomadError::NothingFound);
+    }
+
+    let export = get_export(args, ExportMode::Checksum(manifest))?;
+
+    println!();
+    let root_name = Path::new(target_directory)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(target_directory);
+    let mut tree = TreeWriter::new(
+        nomad_style,
+        TreeOutput::Print(export),
+        format_root(
+            args,
+            &NomadMode::Normal,
+            root_name,
+            None,
+            Path::new(target_directory),
+        ),
+        Some(export_root(
+            args,
+            root_name,
+            "",
+            Path::new(target_directory),
+        )),
+    )?;
+
+    let mut counts = VerificationCounts::default();
+    let mut state = TreeState::default();
+
+    let mut current_depth = 0;
+    let mut previous_item = (PathBuf::from(target_directory), true);
+
+    let last_items = find_last_items(
+        items
+            .keys()
+            .map(|relative_path| Some(relative_path.components().count())),
+    );
+
+    for ((relative_path, item), is_last) in items.into_iter().zip(last_items) {
+        let depth = relative_path.components().count();
+        let marker = item.verification.map(|verification| {
+            counts.add(verification);
+            verification.marker(args, nomad_style)
+        });
+
+        check_nesting(
+            current_depth,
+            &item.path,
+            depth,
+            &previous_item.0,
+            previous_item.1,
+            &mut tree,
+        )?;
+
+        if args.statistics {
+            state.add_statistics(&item.path, item.is_dir, None, target_directory);
+        }
+
+        if item.is_dir {
+            let directory_label = state.label_directory(item.path.to_str().unwrap_or("?"));
+            let node = tree.is_documented().then(|| ExportNode {
+                label: Some(directory_label.to_string()),
+                ..export_node(&relative_path, NodeType::Directory)
+            });
+
+            tree.begin_node(
+                format_directory(
+                    args,
+                    None,
+                    item.is_empty,
+                    &item.path,
+                    (args.labels.label_directories || args.labels.all_labels)
+                        .then_some(directory_label),
+                    None,
+                    &[],
+                    None,
+                    nomad_style,
+                    target_directory,
+                ),
+                node,
+                is_last,
+            )?;
+        } else {
+            // Missing files cannot be opened, so they are not numbered.
+            let file_number = (item.verification != Some(Verification::Missing))
+                .then(|| state.number_file(item.path.to_str().unwrap_or("?")));
+            let number = file_number
+                .filter(|_| args.labels.numbers || args.labels.all_labels)
+                .map(|number| number.to_string());
+            let node = tree.is_documented().then(|| ExportNode {
+                label: file_number.map(|number| number.to_string()),
+                ..export_node(&relative_path, NodeType::File)
+            });
+
+            tree.add_node(
+                format_content(
+                    args,
+                    marker,
+                    get_file_icon(&item.path),
+                    &item.path,
+                    &[],
+                    None,
+                    nomad_style,
+                    number,
+                    target_directory,
+                ),
+                node,
+                is_last,
+            )?;
+        }
+
+        previous_item = (item.path, item.is_dir);
+        current_depth = depth;
+    }
+
+    state.store_labels()?;
+
+    let (_, export) = tree.finish()?;
+
+    println!();
+    counts.display();
+
+    if args.statistics {
+        state.count_skipped(args, target_directory)?;
+        state.display_statistics(
+            args,
+            start.elapsed().as_millis(),
+            nomad_style,
+            Path::new(target_directory),
+        );
+    }
+
+    if let Some(export) = export {
+        export.display_success();
+    }
+
+    Ok(counts.passed())
+}
+
+#[cfg(test)]
+mod test_checksum {
+    use super::*;
+
+    use crate::utils::test_utils::create_fixture;
+
+    #[test]
+    fn test_hash_file() {
+        let fixture = create_fixture(&[("checksum.txt", "abc")]);
+        let item = fixture.path().join("checksum.txt");
+
+        assert_eq!(
+            hash_file(ChecksumAlgorithm::Sha256, &item).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hash_file(ChecksumAlgorithm::Blake3, &item).unwrap(),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+    }
+
+    #[test]
+    fn test_manifest_lines() {
+        let manifest = [
+            format_manifest_line("ab12", "src/main.rs"),
+            format_manifest_line("CD34", "odd\\name\nwith newline"),
+            "ef56 *binary.bin".to_string(),
+        ]
+        .join("\n");
+
+        assert_eq!(
+            parse_manifest(&manifest).unwrap(),
+            BTreeMap::from([
+                (PathBuf::from("binary.bin"), "ef56".to_string()),
+                (PathBuf::from("odd\\name\nwith newline"), "cd34".to_string()),
+                (PathBuf::from("src/main.rs"), "ab12".to_string()),
+            ])
+        );
+        assert!(parse_manifest("not a manifest").is_err());
+    }
+}
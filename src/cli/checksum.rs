@@ -0,0 +1,59 @@
+//! Providing checksum CLI options.
+
+use structopt::StructOpt;
+
+use super::global::GlobalArgs;
+
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, PartialEq, StructOpt)]
+pub struct ChecksumOptions {
+    #[structopt(
+        long = "algo",
+        default_value = "sha256",
+        possible_values = &["sha256", "blake3"],
+        help = "The hash algorithm used for the manifest"
+    )]
+    pub algo: ChecksumAlgorithm,
+
+    #[structopt(flatten)]
+    pub general: GlobalArgs,
+
+    #[structopt(
+        long = "verify",
+        help = "Verify the directory against a manifest and display the result in a tree"
+    )]
+    pub verify: Option<String>,
+}
+
+/// Variants for the hash algorithms used in checksum manifests.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChecksumAlgorithm {
+    /// Hash files with BLAKE3.
+    Blake3,
+    /// Hash files with SHA-256.
+    Sha256,
+}
+
+impl FromStr for ChecksumAlgorithm {
+    type Err = String;
+
+    fn from_str(algorithm: &str) -> Result<Self, Self::Err> {
+        match algorithm {
+            "blake3" => Ok(ChecksumAlgorithm::Blake3),
+            "sha256" => Ok(ChecksumAlgorithm::Sha256),
+            _ => Err(format!("Invalid hash algorithm: {algorithm}")),
+        }
+    }
+}
+
+impl fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let algorithm = match self {
+            ChecksumAlgorithm::Blake3 => "blake3",
+            ChecksumAlgorithm::Sha256 => "sha256",
+        };
+
+        write!(f, "{algorithm}")
+    }
+}
@@ -0,0 +1,23 @@
+//! Providing directory comparison CLI options.
+
+use structopt::StructOpt;
+
+use super::global::GlobalArgs;
+
+#[derive(Debug, PartialEq, StructOpt)]
+pub struct CompareOptions {
+    #[structopt(
+        long = "contents",
+        help = "Compare the contents of files that have the same size instead of their modified times"
+    )]
+    pub contents: bool,
+
+    #[structopt(flatten)]
+    pub general: GlobalArgs,
+
+    #[structopt(help = "The original directory")]
+    pub left: String,
+
+    #[structopt(help = "The directory that is compared against the original directory")]
+    pub right: String,
+}
@@ -2,7 +2,7 @@
 
 use structopt::StructOpt;
 
-use super::global::{LabelArgs, MetaArgs, RegexArgs, StyleArgs};
+use super::global::{ExportFormat, LabelArgs, MetaArgs, RegexArgs, SortArgs, StyleArgs};
 
 #[derive(Debug, PartialEq, StructOpt)]
 pub enum GitOptions {
@@ -69,6 +69,14 @@ pub struct BranchOptions {
     )]
     pub export: Option<Option<String>>,
 
+    #[structopt(
+        long = "export-format",
+        default_value = "text",
+        possible_values = &["text", "json", "html"],
+        help = "The format of the file written by `--export`: the tree as text, a nested JSON document, or an HTML page"
+    )]
+    pub export_format: ExportFormat,
+
     #[structopt(short, long, help = "Display branches in a normal list")]
     pub flat: bool,
 
@@ -105,6 +113,14 @@ pub struct StatusOptions {
     )]
     pub export: Option<Option<String>>,
 
+    #[structopt(
+        long = "export-format",
+        default_value = "text",
+        possible_values = &["text", "json", "html"],
+        help = "The format of the file written by `--export`: the tree as text, a nested JSON document, or an HTML page"
+    )]
+    pub export_format: ExportFormat,
+
     #[structopt(flatten)]
     pub labels: LabelArgs,
 
@@ -114,6 +130,9 @@ pub struct StatusOptions {
     #[structopt(flatten)]
     pub regex: RegexArgs,
 
+    #[structopt(flatten)]
+    pub sort: SortArgs,
+
     #[structopt(
         short = "s",
         long = "stats",
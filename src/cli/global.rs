@@ -1,8 +1,17 @@
 //! Providing arguments that are used throughout `nomad`.
 
+use crate::utils::meta::convert_bytes;
+
+use serde::{Deserialize, Serialize};
 use structopt::StructOpt;
 
-#[derive(Debug, PartialEq, StructOpt)]
+use std::{
+    fmt,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+#[derive(Clone, Debug, PartialEq, StructOpt)]
 pub struct GlobalArgs {
     #[structopt(
         long = "export",
@@ -10,6 +19,14 @@ pub struct GlobalArgs {
     )]
     pub export: Option<Option<String>>,
 
+    #[structopt(
+        long = "export-format",
+        default_value = "text",
+        possible_values = &["text", "json", "html"],
+        help = "The format of the file written by `--export`: the tree as text, a nested JSON document, or an HTML page"
+    )]
+    pub export_format: ExportFormat,
+
     #[structopt(flatten)]
     pub labels: LabelArgs,
 
@@ -22,6 +39,9 @@ pub struct GlobalArgs {
     #[structopt(flatten)]
     pub regex: RegexArgs,
 
+    #[structopt(flatten)]
+    pub sort: SortArgs,
+
     #[structopt(flatten)]
     pub style: StyleArgs,
 
@@ -31,9 +51,17 @@ pub struct GlobalArgs {
         help = "Display traversal statistics after the tree is displayed"
     )]
     pub statistics: bool,
+
+    #[structopt(
+        long = "stats-format",
+        default_value = "table",
+        possible_values = &["table", "json"],
+        help = "The format of the statistics displayed by `--stats`: tables or JSON"
+    )]
+    pub stats_format: StatsFormat,
 }
 
-#[derive(Debug, PartialEq, StructOpt)]
+#[derive(Clone, Debug, PartialEq, StructOpt)]
 pub struct LabelArgs {
     #[structopt(
         short = "L",
@@ -57,8 +85,26 @@ pub struct LabelArgs {
     pub numbers: bool,
 }
 
-#[derive(Debug, PartialEq, StructOpt)]
+#[derive(Clone, Debug, PartialEq, StructOpt)]
 pub struct MetaArgs {
+    #[structopt(
+        long = "apparent-size",
+        help = "Use the apparent size of files instead of their size on disk when adding up directory sizes"
+    )]
+    pub apparent_size: bool,
+
+    #[structopt(
+        long = "du",
+        help = "Display the total size of each directory's contents and the size of each file"
+    )]
+    pub du: bool,
+
+    #[structopt(
+        long = "dupes",
+        help = "Mark files whose contents are identical to another file and summarize the space they waste"
+    )]
+    pub dupes: bool,
+
     #[structopt(
         short = "m",
         long = "metadata",
@@ -71,40 +117,535 @@ pub struct MetaArgs {
         help = "Display code statistics (lines of code, blanks, and comments) for each item"
     )]
     pub tokei: bool,
+
+    #[structopt(
+        long = "top",
+        help = "Only display the largest N items in each directory. Implies `--du`"
+    )]
+    pub top: Option<usize>,
 }
 
-#[derive(Debug, PartialEq, StructOpt)]
+#[derive(Clone, Debug, PartialEq, StructOpt)]
 pub struct ModifierArgs {
+    #[structopt(
+        long = "changed-within",
+        help = "Only display items that were modified within this long ago, ie. `30m`, `2d`, or `6mo`"
+    )]
+    pub changed_within: Option<Age>,
+
+    #[structopt(
+        long = "compact",
+        help = "Collapse chains of directories that only contain a single directory into one item"
+    )]
+    pub compact: bool,
+
     #[structopt(long = "dirs", help = "Only display directories")]
     pub dirs: bool,
 
     #[structopt(long = "disrespect", help = "Disrespect all ignore rules")]
     pub disrespect: bool,
 
+    #[structopt(
+        long = "group",
+        help = "Only display items owned by this group. Accepts a group name or ID"
+    )]
+    pub group: Option<String>,
+
     #[structopt(long = "hidden", help = "Display hidden files")]
     pub hidden: bool,
 
+    #[structopt(
+        long = "ignore-file",
+        number_of_values = 1,
+        help = "Ignore the items matching the rules in this file, which uses the `.gitignore` syntax. May be used multiple times"
+    )]
+    pub ignore_file: Vec<String>,
+
+    #[structopt(
+        long = "ignore-glob",
+        number_of_values = 1,
+        help = "Ignore the items matching this glob, ie. `*.min.js` or `target/`. May be used multiple times"
+    )]
+    pub ignore_glob: Vec<String>,
+
     #[structopt(long = "max-depth", help = "Set the maximum depth to recurse")]
     pub max_depth: Option<usize>,
 
+    #[structopt(
+        long = "max-entries",
+        help = "Only display the first N items in each directory and summarize the rest"
+    )]
+    pub max_entries: Option<usize>,
+
     #[structopt(
         long = "max-filesize",
-        help = "Set the maximum filesize (in bytes) to include in the tree"
+        help = "Set the maximum filesize to include in the tree, ie. `500`, `10K`, `5MiB`, or `1G`"
     )]
-    pub max_filesize: Option<u64>,
+    pub max_filesize: Option<FileSize>,
+
+    #[structopt(
+        long = "min-filesize",
+        help = "Set the minimum filesize to include in the tree, ie. `500`, `10K`, `5MiB`, or `1G`"
+    )]
+    pub min_filesize: Option<FileSize>,
+
+    #[structopt(
+        long = "newer",
+        help = "Only display items that were modified more recently than this item"
+    )]
+    pub newer: Option<String>,
+
+    #[structopt(long = "no-follow", help = "Do not follow symbolic links")]
+    pub no_follow: bool,
+
+    #[structopt(
+        long = "older-than",
+        help = "Only display items that were modified longer than this long ago, ie. `30m`, `2d`, or `6mo`"
+    )]
+    pub older_than: Option<Age>,
+
+    #[structopt(
+        long = "parallel",
+        help = "Traverse the directory with multiple threads"
+    )]
+    pub parallel: bool,
+
+    #[structopt(
+        long = "perm",
+        number_of_values = 1,
+        allow_hyphen_values = true,
+        help = "Only display items with these permissions, ie. `-x` for executables, `o+w` for world-writable items, or `g-r`. May be used multiple times"
+    )]
+    pub perm: Vec<Permission>,
+
+    #[structopt(long = "setuid", help = "Only display items with the setuid bit set")]
+    pub setuid: bool,
+
+    #[structopt(
+        long = "threads",
+        requires = "parallel",
+        validator = is_positive_integer,
+        help = "The number of threads used by `--parallel` (chosen automatically by default)"
+    )]
+    pub threads: Option<usize>,
+
+    #[structopt(
+        long = "time-field",
+        default_value = "mtime",
+        possible_values = &["mtime", "ctime", "atime"],
+        help = "The timestamp used by `--changed-within`, `--older-than`, and `--newer`: the modified, changed, or accessed time"
+    )]
+    pub time_field: TimeField,
+
+    #[structopt(
+        long = "user",
+        help = "Only display items owned by this user. Accepts a username or ID"
+    )]
+    pub user: Option<String>,
+}
+
+/// Check whether an argument is an integer greater than zero.
+fn is_positive_integer(value: String) -> Result<(), String> {
+    match value.parse::<usize>() {
+        Ok(number) if number > 0 => Ok(()),
+        _ => Err(format!("{value} is not a positive integer")),
+    }
+}
+
+/// The length of time items are filtered by, ie. `2d` or `6mo`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Age(pub Duration);
+
+impl FromStr for Age {
+    type Err = String;
+
+    fn from_str(age: &str) -> Result<Self, Self::Err> {
+        let split = age
+            .find(|character: char| !character.is_ascii_digit())
+            .unwrap_or(age.len());
+        let (amount, unit) = age.split_at(split);
+
+        let amount = amount
+            .parse::<u64>()
+            .map_err(|_| format!("Invalid age: {age}"))?;
+        let seconds = match unit {
+            "s" => 1,
+            "m" | "min" => 60,
+            "h" => 60 * 60,
+            "d" => 60 * 60 * 24,
+            "w" => 60 * 60 * 24 * 7,
+            "mo" => 60 * 60 * 24 * 30,
+            "y" => 60 * 60 * 24 * 365,
+            _ => {
+                return Err(format!(
+                    "Invalid age unit in {age}. Use s, m, h, d, w, mo, or y"
+                ))
+            }
+        };
+
+        // The age is subtracted from the current time when filtering, so it must
+        // not reach past the earliest representable time.
+        amount
+            .checked_mul(seconds)
+            .map(Duration::from_secs)
+            .filter(|duration| SystemTime::now().checked_sub(*duration).is_some())
+            .map(Age)
+            .ok_or_else(|| format!("Invalid age: {age} is too large"))
+    }
+}
+
+impl fmt::Display for Age {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.0.as_secs();
+        let (amount, unit) = [
+            (60 * 60 * 24 * 365, "y"),
+            (60 * 60 * 24 * 30, "mo"),
+            (60 * 60 * 24 * 7, "w"),
+            (60 * 60 * 24, "d"),
+            (60 * 60, "h"),
+            (60, "m"),
+        ]
+        .into_iter()
+        .find(|(length, _)| seconds > 0 && seconds.is_multiple_of(*length))
+        .map_or((seconds, "s"), |(length, unit)| (seconds / length, unit));
+
+        write!(f, "{amount}{unit}")
+    }
+}
+
+/// A filesize in bytes, which may be entered with a unit, ie. `10K`, `5MiB`, or `1G`.
+///
+/// `K`, `M`, `G`, and `T` are powers of 1000 like the sizes displayed in the
+/// tree. `Ki`, `Mi`, `Gi`, and `Ti` are powers of 1024. A trailing `B` is optional.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FileSize(pub u64);
+
+impl FromStr for FileSize {
+    type Err = String;
+
+    fn from_str(size: &str) -> Result<Self, Self::Err> {
+        let split = size
+            .find(|character: char| !character.is_ascii_digit() && character != '.')
+            .unwrap_or(size.len());
+        let (amount, unit) = size.split_at(split);
+
+        let amount = amount
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid filesize: {size}"))?;
+        let unit = unit.to_lowercase();
+        let multiplier: u64 = match unit.strip_suffix('b').unwrap_or(&unit) {
+            "" => 1,
+            "k" => 1000,
+            "ki" => 1 << 10,
+            "m" => 1000 * 1000,
+            "mi" => 1 << 20,
+            "g" => 1000 * 1000 * 1000,
+            "gi" => 1 << 30,
+            "t" => 1000 * 1000 * 1000 * 1000,
+            "ti" => 1 << 40,
+            _ => {
+                return Err(format!(
+                    "Invalid filesize unit in {size}. Use K, M, G, T, Ki, Mi, Gi, or Ti"
+                ))
+            }
+        };
+
+        Ok(FileSize((amount * multiplier as f64).round() as u64))
+    }
 }
 
-#[derive(Debug, PartialEq, StructOpt)]
+impl fmt::Display for FileSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = i64::try_from(self.0).map_or(format!("{} B", self.0), convert_bytes);
+
+        write!(f, "{}", size.trim())
+    }
+}
+
+/// A permission check, ie. `-x`, `o+w`, or `ug-r`.
+///
+/// The permissions must be granted to (`+`) or withheld from (`-`) every class
+/// of users that is specified. If no class is specified, such as `-x`, the
+/// permissions only have to be granted to any class of users.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Permission {
+    /// The classes of users the permissions apply to: `u`, `g`, and/or `o`.
+    pub classes: Vec<char>,
+    /// Indicates whether the permissions must be granted or withheld.
+    pub granted: bool,
+    /// The permissions: `r`, `w`, and/or `x`.
+    pub permissions: Vec<char>,
+}
+
+impl FromStr for Permission {
+    type Err = String;
+
+    fn from_str(permission: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!("Invalid permission: {permission}. Use a format such as `-x`, `o+w`, or `ug-r`")
+        };
+
+        let split = permission.find(['+', '-']).ok_or_else(invalid)?;
+        let (classes, permissions) = permission.split_at(split);
+
+        let mut classes = classes.chars().collect::<Vec<char>>();
+        if classes.contains(&'a') {
+            classes = vec!['u', 'g', 'o'];
+        }
+        let permissions = permissions.chars().skip(1).collect::<Vec<char>>();
+
+        if permissions.is_empty()
+            || !classes.iter().all(|class| "ugo".contains(*class))
+            || !permissions.iter().all(|bit| "rwx".contains(*bit))
+        {
+            return Err(invalid());
+        }
+
+        Ok(Permission {
+            granted: classes.is_empty() || permission[split..].starts_with('+'),
+            classes,
+            permissions,
+        })
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.classes.iter().collect::<String>(),
+            if self.granted && !self.classes.is_empty() {
+                '+'
+            } else {
+                '-'
+            },
+            self.permissions.iter().collect::<String>()
+        )
+    }
+}
+
+/// Contains the timestamps items may be filtered by.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum TimeField {
+    /// The last time the item was accessed.
+    Accessed,
+    /// The last time the item's contents or metadata changed.
+    Changed,
+    /// The last time the item's contents were modified.
+    Modified,
+}
+
+impl FromStr for TimeField {
+    type Err = String;
+
+    fn from_str(time_field: &str) -> Result<Self, Self::Err> {
+        match time_field {
+            "atime" => Ok(TimeField::Accessed),
+            "ctime" => Ok(TimeField::Changed),
+            "mtime" => Ok(TimeField::Modified),
+            _ => Err(format!("Invalid time field: {time_field}")),
+        }
+    }
+}
+
+impl fmt::Display for TimeField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time_field = match self {
+            TimeField::Accessed => "atime",
+            TimeField::Changed => "ctime",
+            TimeField::Modified => "mtime",
+        };
+
+        write!(f, "{time_field}")
+    }
+}
+
+/// Contains the formats a tree may be exported in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    /// Export the tree as a self-contained HTML page.
+    Html,
+    /// Export the tree as a nested JSON document.
+    Json,
+    /// Export the tree as it is displayed.
+    Text,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(export_format: &str) -> Result<Self, Self::Err> {
+        match export_format {
+            "html" => Ok(ExportFormat::Html),
+            "json" => Ok(ExportFormat::Json),
+            "text" => Ok(ExportFormat::Text),
+            _ => Err(format!("Invalid export format: {export_format}")),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let export_format = match self {
+            ExportFormat::Html => "html",
+            ExportFormat::Json => "json",
+            ExportFormat::Text => "text",
+        };
+
+        write!(f, "{export_format}")
+    }
+}
+
+/// Contains the formats the traversal statistics may be displayed in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatsFormat {
+    /// Display the statistics as JSON.
+    Json,
+    /// Display the statistics in tables.
+    Table,
+}
+
+impl FromStr for StatsFormat {
+    type Err = String;
+
+    fn from_str(stats_format: &str) -> Result<Self, Self::Err> {
+        match stats_format {
+            "json" => Ok(StatsFormat::Json),
+            "table" => Ok(StatsFormat::Table),
+            _ => Err(format!("Invalid statistics format: {stats_format}")),
+        }
+    }
+}
+
+impl fmt::Display for StatsFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stats_format = match self {
+            StatsFormat::Json => "json",
+            StatsFormat::Table => "table",
+        };
+
+        write!(f, "{stats_format}")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, StructOpt)]
 pub struct RegexArgs {
+    #[structopt(
+        long = "contains",
+        help = "Only display files whose contents match this pattern and display the number of matching lines. Supports regex expressions"
+    )]
+    pub contains: Option<String>,
+
+    #[structopt(
+        long = "exclude",
+        number_of_values = 1,
+        help = "Do not display items matching this pattern. May be used multiple times. Supports regex expressions"
+    )]
+    pub exclude: Vec<String>,
+
+    #[structopt(
+        short = "i",
+        long = "ignore-case",
+        help = "Match patterns case-insensitively"
+    )]
+    pub ignore_case: bool,
+
+    #[structopt(
+        long = "match-all",
+        help = "Only display items matching every pattern instead of any pattern"
+    )]
+    pub match_all: bool,
+
+    #[structopt(
+        long = "match-dirs",
+        help = "Also match the pattern against directory names and display the contents of matching directories"
+    )]
+    pub match_dirs: bool,
+
+    #[structopt(
+        long = "name-only",
+        help = "Match patterns against item names instead of their paths"
+    )]
+    pub name_only: bool,
+
     #[structopt(
         short = "p",
         long = "pattern",
-        help = "Only display items matching this pattern. Supports regex expressions"
+        number_of_values = 1,
+        help = "Only display items matching this pattern. May be used multiple times to display items matching any pattern. Supports regex expressions"
+    )]
+    pub patterns: Vec<String>,
+
+    #[structopt(
+        long = "show-lines",
+        help = "Display the lines matching the `--contains` pattern below each file"
     )]
-    pub pattern: Option<String>,
+    pub show_lines: bool,
 }
 
-#[derive(Debug, PartialEq, StructOpt)]
+#[derive(Clone, Debug, PartialEq, StructOpt)]
+pub struct SortArgs {
+    #[structopt(long = "dirs-first", help = "Display directories before files")]
+    pub dirs_first: bool,
+
+    #[structopt(long = "reverse", help = "Reverse the sort order")]
+    pub reverse: bool,
+
+    #[structopt(
+        long = "sort",
+        default_value = "name",
+        possible_values = &["name", "size", "mtime", "ext", "status"],
+        help = "Sort the items in each directory by name, size (largest first), modified time (newest first), extension, or Git status"
+    )]
+    pub sort_by: SortBy,
+}
+
+/// Contains the keys the items in each directory may be sorted by.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortBy {
+    /// Sort by extension, then by name.
+    Extension,
+    /// Sort by the last modified time. The newest items are displayed first.
+    Modified,
+    /// Sort by name.
+    Name,
+    /// Sort by file size. The largest items are displayed first.
+    Size,
+    /// Sort by Git status. Changed items are displayed first.
+    Status,
+}
+
+impl FromStr for SortBy {
+    type Err = String;
+
+    fn from_str(sort_by: &str) -> Result<Self, Self::Err> {
+        match sort_by {
+            "ext" => Ok(SortBy::Extension),
+            "mtime" => Ok(SortBy::Modified),
+            "name" => Ok(SortBy::Name),
+            "size" => Ok(SortBy::Size),
+            "status" => Ok(SortBy::Status),
+            _ => Err(format!("Invalid sort key: {sort_by}")),
+        }
+    }
+}
+
+impl fmt::Display for SortBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sort_by = match self {
+            SortBy::Extension => "ext",
+            SortBy::Modified => "mtime",
+            SortBy::Name => "name",
+            SortBy::Size => "size",
+            SortBy::Status => "status",
+        };
+
+        write!(f, "{sort_by}")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, StructOpt)]
 pub struct StyleArgs {
     #[structopt(long = "no-colors", help = "Do not display any colors")]
     pub no_colors: bool,
@@ -121,3 +662,87 @@ pub struct StyleArgs {
     )]
     pub plain: bool,
 }
+
+#[cfg(test)]
+mod test_global {
+    use super::*;
+
+    #[test]
+    fn test_parse_threads() {
+        let args = GlobalArgs::from_iter(["nd", "--parallel", "--threads", "4"]);
+        assert!(args.modifiers.parallel);
+        assert_eq!(args.modifiers.threads, Some(4));
+
+        assert!(GlobalArgs::from_iter_safe(["nd", "--parallel", "--threads", "0"]).is_err());
+        assert!(GlobalArgs::from_iter_safe(["nd", "--threads", "4"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!("30m".parse::<Age>(), Ok(Age(Duration::from_secs(1800))));
+        assert_eq!("2d".parse::<Age>(), Ok(Age(Duration::from_secs(172800))));
+        assert_eq!(
+            "6mo".parse::<Age>(),
+            Ok(Age(Duration::from_secs(6 * 30 * 86400)))
+        );
+        assert!("6".parse::<Age>().is_err());
+        assert!("d".parse::<Age>().is_err());
+        assert_eq!(
+            "99999999999999y".parse::<Age>(),
+            Err("Invalid age: 99999999999999y is too large".to_string())
+        );
+        assert_eq!(
+            "18000000000000000000s".parse::<Age>(),
+            Err("Invalid age: 18000000000000000000s is too large".to_string())
+        );
+
+        assert_eq!("6mo".parse::<Age>().unwrap().to_string(), "6mo");
+        assert_eq!("90s".parse::<Age>().unwrap().to_string(), "90s");
+    }
+
+    #[test]
+    fn test_parse_filesize() {
+        assert_eq!("500".parse::<FileSize>(), Ok(FileSize(500)));
+        assert_eq!("10K".parse::<FileSize>(), Ok(FileSize(10_000)));
+        assert_eq!("5MiB".parse::<FileSize>(), Ok(FileSize(5 * 1024 * 1024)));
+        assert_eq!("1.5gb".parse::<FileSize>(), Ok(FileSize(1_500_000_000)));
+        assert!("10X".parse::<FileSize>().is_err());
+        assert!("MiB".parse::<FileSize>().is_err());
+
+        assert_eq!(FileSize(50_000_000).to_string(), "50 MB");
+    }
+
+    #[test]
+    fn test_parse_permission() {
+        assert_eq!(
+            "-x".parse::<Permission>(),
+            Ok(Permission {
+                classes: vec![],
+                granted: true,
+                permissions: vec!['x'],
+            })
+        );
+        assert_eq!(
+            "o+w".parse::<Permission>(),
+            Ok(Permission {
+                classes: vec!['o'],
+                granted: true,
+                permissions: vec!['w'],
+            })
+        );
+        assert_eq!(
+            "a-rw".parse::<Permission>(),
+            Ok(Permission {
+                classes: vec!['u', 'g', 'o'],
+                granted: false,
+                permissions: vec!['r', 'w'],
+            })
+        );
+        assert!("x".parse::<Permission>().is_err());
+        assert!("o+".parse::<Permission>().is_err());
+        assert!("z+w".parse::<Permission>().is_err());
+
+        assert_eq!("-x".parse::<Permission>().unwrap().to_string(), "-x");
+        assert_eq!("go-w".parse::<Permission>().unwrap().to_string(), "go-w");
+    }
+}
@@ -1,19 +1,32 @@
 //! Defining command-line interface flags.
 
+pub mod checksum;
+pub mod compare;
 pub mod config;
 pub mod filetype;
 pub mod git;
 pub mod global;
 pub mod releases;
+pub mod snapshot;
 
-use structopt::StructOpt;
+use crate::archive::is_archive;
+
+use structopt::{
+    clap::{Error, ErrorKind},
+    StructOpt,
+};
+
+use std::path::Path;
 
 use self::{
+    checksum::ChecksumOptions,
+    compare::CompareOptions,
     config::ConfigOptions,
     filetype::FileTypeOptions,
     git::GitOptions,
     global::GlobalArgs,
     releases::{ReleaseOptions, UpgradeOptions},
+    snapshot::SnapshotOptions,
 };
 
 /// This struct contains all flags that are used in this program.
@@ -27,14 +40,28 @@ pub struct Args {
     #[structopt(long, help = "Display the banner")]
     pub banner: bool,
 
-    #[structopt(help = "Display a tree for this directory")]
-    pub directory: Option<String>,
+    #[structopt(
+        help = "Display a tree for this directory. Display a tree for each directory under a shared root if several directories are provided"
+    )]
+    pub directories: Vec<String>,
 
     #[structopt(flatten)]
     pub global: GlobalArgs,
 
+    #[structopt(
+        long,
+        help = "Display the tree of a Git revision (a commit, branch, or tag) instead of the working directory"
+    )]
+    pub rev: Option<String>,
+
     #[structopt(subcommand)]
     pub sub_commands: Option<SubCommands>,
+
+    #[structopt(
+        long,
+        help = "Keep running and redraw the tree whenever the items within the directory change. Created or modified items are highlighted"
+    )]
+    pub watch: bool,
 }
 
 #[derive(Debug, PartialEq, StructOpt)]
@@ -42,6 +69,14 @@ pub enum SubCommands {
     ///`bat` (the Rust alternative to the `cat` command) a file.
     /// This may be used after running nomad in a labeled mode.
     Bat { item_labels: Vec<String> },
+    /// Write a manifest containing the hash of every file in `sha256sum` format,
+    /// or verify a directory against a manifest.
+    ///
+    /// The manifest is printed unless `--export` is used.
+    Checksum(Box<ChecksumOptions>),
+    /// Compare two directories and display their differences in a single tree.
+    /// Items are marked as added, removed, type-changed, or content-changed.
+    Compare(Box<CompareOptions>),
     /// Customize/configure nomad or view your current configuration.
     ///
     /// Edit or view your settings defined in the self-instantiated configuration
@@ -56,7 +91,7 @@ pub enum SubCommands {
     /// This may be used after running nomad in a labeled mode.
     Edit { item_labels: Vec<String> },
     /// Filter directory items by filetype.
-    Ft(FileTypeOptions),
+    Ft(Box<FileTypeOptions>),
     /// Run commonly used Git commands.
     /// Some commands may be used after running nomad in a labeled mode.
     ///
@@ -66,21 +101,58 @@ pub enum SubCommands {
     Releases(ReleaseOptions),
     /// Enter rootless (interactive) mode.
     Rootless,
+    /// Save snapshots of a directory and display what changed since then without
+    /// using Git.
+    ///
+    /// Use the `-h`/`--help` flags to see the available options for each command.
+    Snapshot(Box<SnapshotOptions>),
     /// Run `tokei` (lines of code counter).
     Tokei,
     /// Upgrade nomad or just check if there is an upgrade available.
     Upgrade(UpgradeOptions),
 }
 
-/// Return the `Args` struct.
+/// Return the `Args` struct. Exits with an error if several directories are
+/// provided for a mode that only displays a single directory.
 pub fn get_args() -> Args {
-    Args::from_args()
+    let args = Args::from_args();
+
+    // Only the normal tree may be displayed for several directories.
+    if args.directories.len() > 1 {
+        let single_target = if args.sub_commands.is_some() {
+            Some("a subcommand")
+        } else if args.watch {
+            Some("`--watch`")
+        } else if args.rev.is_some() {
+            Some("`--rev`")
+        } else if args
+            .directories
+            .iter()
+            .any(|directory| is_archive(Path::new(directory)))
+        {
+            Some("an archive")
+        } else {
+            None
+        };
+
+        if let Some(single_target) = single_target {
+            Error::with_description(
+                &format!("Only one directory may be provided with {single_target}"),
+                ErrorKind::ArgumentConflict,
+            )
+            .exit();
+        }
+    }
+
+    args
 }
 
 #[cfg(test)]
 mod test_cli {
     use assert_cmd::Command;
 
+    use std::time::Duration;
+
     #[test]
     fn test_invalid_arg() {
         Command::cargo_bin("nd")
@@ -89,4 +161,20 @@ mod test_cli {
             .assert()
             .failure();
     }
+
+    #[test]
+    fn test_single_target() {
+        for flags in [&["--watch"][..], &["--rev", "HEAD"], &["tokei"]] {
+            let assert = Command::cargo_bin("nd")
+                .unwrap()
+                .args(["src", "manual"])
+                .args(flags)
+                .timeout(Duration::from_secs(10))
+                .assert()
+                .failure();
+
+            assert!(String::from_utf8_lossy(&assert.get_output().stderr)
+                .contains("Only one directory may be provided"));
+        }
+    }
 }
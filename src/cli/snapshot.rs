@@ -0,0 +1,26 @@
+//! Providing snapshot CLI options.
+
+use structopt::StructOpt;
+
+use super::global::GlobalArgs;
+
+#[derive(Debug, PartialEq, StructOpt)]
+pub enum SnapshotOptions {
+    /// Display the items that appeared, disappeared, or changed since a snapshot
+    /// was saved.
+    Diff(SnapshotNameOptions),
+    /// List all saved snapshots.
+    List,
+    /// Save the items and their metadata under a name. Overwrites an existing
+    /// snapshot with the same name.
+    Save(SnapshotNameOptions),
+}
+
+#[derive(Debug, PartialEq, StructOpt)]
+pub struct SnapshotNameOptions {
+    #[structopt(flatten)]
+    pub general: GlobalArgs,
+
+    #[structopt(help = "The name of the snapshot")]
+    pub name: String,
+}
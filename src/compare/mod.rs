@@ -0,0 +1,794 @@
+//! Compare two directories and display their differences in a single tree.
+
+use crate::{
+    cli::{compare::CompareOptions, global::GlobalArgs},
+    errors::NomadError,
+    style::models::NomadStyle,
+    traverse::{
+        dupes::format_duplicate,
+        find_items,
+        format::{format_content, format_directory, format_hidden_items},
+        limits::LimitedItems,
+        models::{ItemMetadata, TransformedItem},
+        modes::NomadMode,
+        sizes::{format_size, DirectorySizes},
+        sort::ItemSorter,
+        state::TreeState,
+        traits::TransformFound,
+        utils::{
+            build_walker, check_nesting, compact_directories, export_root, format_root,
+            get_file_icon,
+        },
+        writer::{find_last_items, TreeOutput, TreeWriter},
+    },
+    utils::{
+        export::{get_export, ExportMode, ExportNode, NodeType},
+        paths::canonicalize_path,
+    },
+};
+
+use anyhow::Result;
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs::{self, File},
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, UNIX_EPOCH},
+};
+
+/// Variants for the differences between the two sides of a comparison.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Difference {
+    /// The item only exists on the right side.
+    Added,
+    /// The item is a file on both sides, but its contents are different.
+    ContentChanged,
+    /// The item only exists on the left side.
+    Removed,
+    /// The item is a file on one side and a directory on the other.
+    TypeChanged,
+}
+
+impl Difference {
+    /// Get the marker displayed in front of the item. The Git status markers
+    /// and their colors are reused.
+    pub fn marker(&self, args: &GlobalArgs, nomad_style: &NomadStyle) -> String {
+        let git_style = &nomad_style.git;
+        let (color, marker) = match self {
+            Difference::Added => (git_style.staged_added_color, &git_style.staged_added_marker),
+            Difference::ContentChanged => (git_style.modified_color, &git_style.modified_marker),
+            Difference::Removed => (
+                git_style.staged_deleted_color,
+                &git_style.staged_deleted_marker,
+            ),
+            Difference::TypeChanged => (git_style.typechanged_color, &git_style.typechanged_marker),
+        };
+
+        if args.style.no_colors {
+            marker.to_string()
+        } else {
+            color.paint(marker).to_string()
+        }
+    }
+}
+
+/// Contains an item found on one side of a comparison.
+#[derive(Debug)]
+pub struct SideItem {
+    /// Indicates whether this is a directory.
+    pub is_dir: bool,
+    /// Indicates whether this is an empty directory.
+    pub is_empty: bool,
+    /// The last modified time of the item since the Unix epoch.
+    pub modified: Option<Duration>,
+    /// Indicates whether the item is on disk and may be opened. This is not the
+    /// case for items that were stored in a snapshot.
+    pub on_disk: bool,
+    /// The absolute path to the item.
+    pub path: PathBuf,
+    /// The size of the item in bytes.
+    pub size: u64,
+}
+
+/// Contains the items on one side of a comparison.
+pub struct Side {
+    /// The total size of each directory if `--du` or `--top` is used. Items that
+    /// are not on disk do not have sizes.
+    pub directory_sizes: Option<Arc<DirectorySizes>>,
+    /// The items keyed by their paths relative to the root.
+    pub items: BTreeMap<PathBuf, SideItem>,
+    /// The absolute path to the directory containing the items.
+    pub root: String,
+}
+
+/// Find the items within a directory. The items are found with the same
+/// settings as the normal tree.
+pub fn collect_side(
+    args: &GlobalArgs,
+    nomad_style: &NomadStyle,
+    target_directory: &str,
+) -> Result<Side, NomadError> {
+    // The largest items are only selected with `--top` once both sides are
+    // combined, so items are not found with the directory sizes. Otherwise,
+    // items that are only among the largest items on one side would appear to
+    // be added or removed.
+    let mut walker = build_walker(args, target_directory, None)?;
+    let directory_sizes = walker.directory_sizes.take();
+
+    let items = find_items(args, nomad_style, target_directory, walker)?
+        .transform(target_directory)?
+        .map(|item| {
+            let metadata = fs::metadata(&item.path).ok();
+
+            (
+                item.components.iter().collect::<PathBuf>(),
+                SideItem {
+                    is_dir: item.is_dir,
+                    is_empty: item.is_empty,
+                    modified: metadata
+                        .as_ref()
+                        .and_then(|metadata| metadata.modified().ok())
+                        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok()),
+                    on_disk: true,
+                    path: PathBuf::from(item.path),
+                    size: metadata.map_or(0, |metadata| metadata.len()),
+                },
+            )
+        })
+        .collect();
+
+    Ok(Side {
+        directory_sizes,
+        items,
+        root: target_directory.to_string(),
+    })
+}
+
+/// Get the difference between the two sides of an item. Returns `None` if the
+/// item did not change.
+pub fn get_difference(
+    compare_contents: bool,
+    left: Option<&SideItem>,
+    right: Option<&SideItem>,
+) -> Option<Difference> {
+    match (left, right) {
+        (Some(_), None) => Some(Difference::Removed),
+        (None, Some(_)) => Some(Difference::Added),
+        (Some(left), Some(right)) if left.is_dir != right.is_dir => Some(Difference::TypeChanged),
+        (Some(left), Some(right))
+            if !left.is_dir && files_differ(compare_contents, left, right) =>
+        {
+            Some(Difference::ContentChanged)
+        }
+        _ => None,
+    }
+}
+
+/// Check whether two files are different. Files with different sizes are always
+/// different. Otherwise, their contents are compared if `compare_contents` is
+/// enabled and both files are on disk, or their modified times if not.
+fn files_differ(compare_contents: bool, left: &SideItem, right: &SideItem) -> bool {
+    if left.size != right.size {
+        true
+    } else if compare_contents && left.on_disk && right.on_disk {
+        !have_same_contents(&left.path, &right.path)
+    } else {
+        left.modified != right.modified
+    }
+}
+
+/// Check whether two files contain the same bytes without reading either file
+/// into memory.
+fn have_same_contents(left: &Path, right: &Path) -> bool {
+    let (mut left, mut right) = match (File::open(left), File::open(right)) {
+        (Ok(left), Ok(right)) => (BufReader::new(left), BufReader::new(right)),
+        _ => return false,
+    };
+
+    loop {
+        let (left_chunk, right_chunk) = match (left.fill_buf(), right.fill_buf()) {
+            (Ok(left_chunk), Ok(right_chunk)) => (left_chunk, right_chunk),
+            _ => return false,
+        };
+
+        if left_chunk.is_empty() || right_chunk.is_empty() {
+            return left_chunk.is_empty() && right_chunk.is_empty();
+        }
+
+        let length = left_chunk.len().min(right_chunk.len());
+        if left_chunk[..length] != right_chunk[..length] {
+            return false;
+        }
+
+        left.consume(length);
+        right.consume(length);
+    }
+}
+
+/// Counts the items with each difference.
+#[derive(Debug, Default)]
+pub struct DifferenceCounts {
+    /// The number of added items.
+    added: usize,
+    /// The number of items whose contents changed.
+    content_changed: usize,
+    /// The number of removed items.
+    removed: usize,
+    /// The number of items whose type changed.
+    type_changed: usize,
+}
+
+impl DifferenceCounts {
+    /// Count an item with a difference.
+    pub fn add(&mut self, difference: Difference) {
+        match difference {
+            Difference::Added => self.added += 1,
+            Difference::ContentChanged => self.content_changed += 1,
+            Difference::Removed => self.removed += 1,
+            Difference::TypeChanged => self.type_changed += 1,
+        }
+    }
+
+    /// Display the number of items with each difference.
+    pub fn display(&self) {
+        println!(
+            "| {} added | {} removed | {} type changed | {} content changed |\n",
+            self.added, self.removed, self.type_changed, self.content_changed
+        );
+    }
+}
+
+/// Describe an item of the tree in the export's document.
+pub fn export_node(relative_path: &Path, node_type: NodeType) -> ExportNode {
+    ExportNode {
+        icon: (node_type != NodeType::Directory).then(|| get_file_icon(relative_path)),
+        ..ExportNode::new(
+            relative_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("?"),
+            node_type,
+            relative_path.to_str().unwrap_or("?"),
+        )
+    }
+}
+
+/// Compare two directories and display their differences in a single tree.
+pub fn compare_directories(
+    compare_options: &CompareOptions,
+    nomad_style: &NomadStyle,
+) -> Result<(), NomadError> {
+    let args = &compare_options.general;
+
+    let start = Instant::now();
+
+    let left = collect_side(
+        args,
+        nomad_style,
+        &canonicalize_path(&compare_options.left)?,
+    )?;
+    let right = collect_side(
+        args,
+        nomad_style,
+        &canonicalize_path(&compare_options.right)?,
+    )?;
+
+    display_differences(
+        args,
+        compare_options.contents,
+        ExportMode::Compare(&compare_options.left, &compare_options.right),
+        left,
+        nomad_style,
+        right,
+        &format!("{} ⇄ {}", compare_options.left, compare_options.right),
+        start,
+    )
+}
+
+/// Contains an item of the comparison tree and both of its sides.
+struct ComparedItem {
+    /// The difference between the two sides, if any.
+    difference: Option<Difference>,
+    /// The item on the left side.
+    left: Option<SideItem>,
+    /// The path of the item relative to the roots of both sides.
+    relative_path: PathBuf,
+    /// The item on the right side.
+    right: Option<SideItem>,
+}
+
+/// Combine the items on both sides of a comparison into the items of a single
+/// tree, sorted like the items of the normal tree. Returns the items and the
+/// sides of each item keyed by its displayed path. Each difference is added to
+/// the counts.
+fn compare_items(
+    args: &GlobalArgs,
+    compare_contents: bool,
+    counts: &mut DifferenceCounts,
+    left: &mut Side,
+    right: &mut Side,
+) -> Result<(Vec<TransformedItem>, HashMap<String, ComparedItem>), NomadError> {
+    let mut relative_paths = left
+        .items
+        .keys()
+        .chain(right.items.keys())
+        .cloned()
+        .collect::<BTreeSet<PathBuf>>()
+        .into_iter()
+        .collect::<Vec<PathBuf>>();
+
+    // Paths are sorted and limited to the largest items by the items they
+    // refer to on the side they are displayed with.
+    let get_side = |relative_path: &Path| {
+        if right.items.contains_key(relative_path) {
+            &right
+        } else {
+            &left
+        }
+    };
+
+    relative_paths.retain(|relative_path| {
+        let side = get_side(relative_path);
+        side.directory_sizes
+            .as_ref()
+            .is_none_or(|sizes| sizes.is_displayed(&Path::new(&side.root).join(relative_path)))
+    });
+
+    if relative_paths.is_empty() {
+        return Err(NomadError::NothingFound);
+    }
+
+    let sorter = ItemSorter::new(&args.sort, right.directory_sizes.clone(), &right.root);
+    relative_paths.sort_by(|a, b| {
+        sorter.compare_paths_by(a, b, |relative_path| {
+            Path::new(&get_side(relative_path).root).join(relative_path)
+        })
+    });
+
+    let mut compared_items = HashMap::new();
+    let mut items = Vec::new();
+
+    for relative_path in relative_paths {
+        let left_item = left.items.remove(&relative_path);
+        let right_item = right.items.remove(&relative_path);
+
+        let difference = get_difference(compare_contents, left_item.as_ref(), right_item.as_ref());
+        if let Some(difference) = difference {
+            counts.add(difference);
+        }
+
+        // Directories are displayed with the side they exist on, or the side
+        // they are a directory on if their type changed.
+        let (is_dir, is_empty, size) = match (&left_item, &right_item) {
+            (_, Some(item)) if item.is_dir => (true, item.is_empty, None),
+            (Some(item), _) if item.is_dir => (true, item.is_empty, None),
+            (_, Some(item)) | (Some(item), None) => (false, false, Some(item.size)),
+            (None, None) => continue,
+        };
+
+        let path = Path::new(&right.root)
+            .join(&relative_path)
+            .to_str()
+            .unwrap_or("?")
+            .to_string();
+
+        items.push(TransformedItem {
+            components: relative_path
+                .components()
+                .map(|component| component.as_os_str().to_str().unwrap_or("?").to_string())
+                .collect(),
+            content_matches: None,
+            depth: relative_path.components().count() as i32,
+            display_name: None,
+            hidden_items: None,
+            is_dir,
+            is_empty,
+            is_file: !is_dir,
+            link_problem: None,
+            marker: None,
+            matched: Vec::new(),
+            metadata: Some(ItemMetadata {
+                size,
+                ..ItemMetadata::default()
+            }),
+            path: path.clone(),
+        });
+        compared_items.insert(
+            path,
+            ComparedItem {
+                difference,
+                left: left_item,
+                relative_path,
+                right: right_item,
+            },
+        );
+    }
+
+    Ok((items, compared_items))
+}
+
+/// Write the items of a comparison into the tree. Each item is marked with its
+/// difference and displayed with the side it exists on, or the right side if it
+/// exists on both.
+fn write_differences(
+    args: &GlobalArgs,
+    items: Vec<TransformedItem>,
+    compared_items: &HashMap<String, ComparedItem>,
+    left: &Side,
+    nomad_style: &NomadStyle,
+    right: &Side,
+    state: &mut TreeState,
+    tree: &mut TreeWriter,
+) -> Result<(), NomadError> {
+    let mut current_depth = 0;
+    let mut previous_item = (PathBuf::from(&right.root), true);
+
+    let items = LimitedItems::new(args, items.into_iter(), nomad_style, &right.root)
+        .collect::<Vec<TransformedItem>>();
+    let last_items = find_last_items(items.iter().map(|item| {
+        (item.hidden_items.is_some() || item.is_dir || !args.modifiers.dirs)
+            .then_some(item.depth as usize)
+    }));
+
+    for (item, is_last) in items.into_iter().zip(last_items) {
+        let display_path = PathBuf::from(&item.path);
+        let depth = item.depth as usize;
+
+        check_nesting(
+            current_depth,
+            &display_path,
+            depth,
+            &previous_item.0,
+            previous_item.1,
+            tree,
+        )?;
+
+        let compared_item = match (&item.hidden_items, compared_items.get(&item.path)) {
+            (Some(hidden_items), _) => {
+                tree.add_empty_child(format_hidden_items(args, hidden_items), is_last)?;
+                previous_item = (display_path, false);
+                current_depth = depth;
+                continue;
+            }
+            (None, Some(compared_item)) => compared_item,
+            (None, None) => continue,
+        };
+
+        let marker = compared_item
+            .difference
+            .map(|difference| difference.marker(args, nomad_style));
+
+        if item.is_dir {
+            let (side_item, side) = match (&compared_item.right, &compared_item.left) {
+                (Some(side_item), _) if side_item.is_dir => (side_item, right),
+                (_, Some(side_item)) => (side_item, left),
+                (Some(side_item), None) => (side_item, right),
+                (None, None) => continue,
+            };
+            let directory_label = state.label_directory(side_item.path.to_str().unwrap_or("?"));
+
+            if args.statistics {
+                state.add_statistics(&side_item.path, true, None, &side.root);
+            }
+
+            let mut formatted_directory = format_directory(
+                args,
+                item.display_name.as_deref(),
+                side_item.is_empty,
+                &side_item.path,
+                (args.labels.label_directories || args.labels.all_labels)
+                    .then(|| directory_label.to_string()),
+                marker,
+                &[],
+                None,
+                nomad_style,
+                &side.root,
+            );
+
+            if let Some(size) = side
+                .directory_sizes
+                .as_ref()
+                .and_then(|sizes| sizes.get(&side_item.path))
+            {
+                formatted_directory = format!("{} {formatted_directory}", format_size(args, size));
+            }
+
+            let node = tree.is_documented().then(|| ExportNode {
+                label: Some(directory_label),
+                ..export_node(&compared_item.relative_path, NodeType::Directory)
+            });
+            tree.begin_node(formatted_directory, node, is_last)?;
+            previous_item = (display_path, true);
+        } else if !args.modifiers.dirs {
+            let numbers = [&compared_item.left, &compared_item.right]
+                .into_iter()
+                .flatten()
+                .filter(|side_item| side_item.on_disk)
+                .map(|side_item| state.number_file(side_item.path.to_str().unwrap_or("?")))
+                .map(|number| number.to_string())
+                .collect::<Vec<String>>()
+                .join("|");
+
+            let (side_item, side) = match (&compared_item.right, &compared_item.left) {
+                (Some(side_item), _) => (side_item, right),
+                (None, Some(side_item)) => (side_item, left),
+                (None, None) => continue,
+            };
+
+            if args.statistics {
+                state.add_statistics(&side_item.path, false, None, &side.root);
+            }
+
+            let mut formatted_content = format_content(
+                args,
+                marker,
+                get_file_icon(&side_item.path),
+                &side_item.path,
+                &[],
+                None,
+                nomad_style,
+                (!numbers.is_empty() && (args.labels.numbers || args.labels.all_labels))
+                    .then(|| numbers.to_string()),
+                &side.root,
+            );
+
+            if let Some(size) = side
+                .directory_sizes
+                .as_ref()
+                .and_then(|sizes| sizes.get(&side_item.path))
+            {
+                formatted_content = format!("{} {formatted_content}", format_size(args, size));
+            }
+
+            if let Some(id) = side_item
+                .path
+                .to_str()
+                .and_then(|path| state.duplicate_group(path))
+            {
+                formatted_content = format!("{formatted_content} {}", format_duplicate(args, id));
+            }
+
+            let node = tree.is_documented().then(|| ExportNode {
+                label: (!numbers.is_empty()).then_some(numbers),
+                ..export_node(&compared_item.relative_path, NodeType::File)
+            });
+            tree.add_node(formatted_content, node, is_last)?;
+            previous_item = (display_path, false);
+        }
+
+        current_depth = depth;
+    }
+
+    Ok(())
+}
+
+/// Display the items on both sides of a comparison in a single tree. Each item
+/// is marked with its difference, and the number of items with each difference
+/// is displayed below the tree.
+///
+/// The items are sorted, compacted, and limited like the items of the normal
+/// tree. Items are displayed with the side they exist on, or the right side if
+/// they exist on both.
+///
+/// Files that are on disk on both sides are labeled with two numbers so that
+/// either side may be opened with `nd bat` or `nd edit`. The first number
+/// refers to the left side.
+pub fn display_differences(
+    args: &GlobalArgs,
+    compare_contents: bool,
+    export_mode: ExportMode,
+    mut left: Side,
+    nomad_style: &NomadStyle,
+    mut right: Side,
+    root_name: &str,
+    start: Instant,
+) -> Result<(), NomadError> {
+    let mut counts = DifferenceCounts::default();
+    let (mut items, compared_items) =
+        compare_items(args, compare_contents, &mut counts, &mut left, &mut right)?;
+
+    let mut state = TreeState::default();
+
+    // Duplicates are only found among the displayed files. Otherwise, every
+    // unchanged file would be a duplicate of itself on the other side.
+    if args.meta.dupes {
+        state.find_duplicates(
+            items
+                .iter()
+                .filter(|item| item.is_file)
+                .filter_map(|item| compared_items.get(&item.path))
+                .filter_map(|compared_item| {
+                    compared_item
+                        .right
+                        .as_ref()
+                        .or(compared_item.left.as_ref())
+                        .filter(|item| item.on_disk)
+                        .and_then(|item| item.path.to_str())
+                }),
+        );
+    }
+
+    if args.modifiers.compact {
+        items = compact_directories(items, args.modifiers.dirs);
+    }
+
+    let export = get_export(args, export_mode)?;
+
+    println!();
+    let mut tree = TreeWriter::new(
+        nomad_style,
+        TreeOutput::Print(export),
+        format_root(
+            args,
+            &NomadMode::Normal,
+            root_name,
+            right
+                .directory_sizes
+                .as_ref()
+                .and_then(|sizes| sizes.get(Path::new(&right.root))),
+            Path::new(&right.root),
+        ),
+        Some(export_root(args, root_name, "", Path::new(&right.root))),
+    )?;
+
+    write_differences(
+        args,
+        items,
+        &compared_items,
+        &left,
+        nomad_style,
+        &right,
+        &mut state,
+        &mut tree,
+    )?;
+
+    state.store_labels()?;
+
+    let (_, export) = tree.finish()?;
+
+    println!();
+    counts.display();
+
+    if args.meta.dupes {
+        state.display_duplicates(args, nomad_style, Path::new(&right.root));
+    }
+    if args.statistics {
+        for root in [&left.root, &right.root] {
+            state.count_skipped(args, root)?;
+        }
+        state.display_statistics(
+            args,
+            start.elapsed().as_millis(),
+            nomad_style,
+            Path::new(&right.root),
+        );
+    }
+
+    if let Some(export) = export {
+        export.display_success();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_compare {
+    use super::*;
+
+    use crate::utils::test_utils::create_fixture;
+
+    use structopt::StructOpt;
+
+    #[test]
+    fn test_differences() {
+        let fixture = create_fixture(&[
+            ("left/same.txt", "same"),
+            ("left/changed.txt", "left"),
+            ("left/retyped", ""),
+            ("right/same.txt", "same"),
+            ("right/changed.txt", "right"),
+            ("right/retyped/", ""),
+        ]);
+
+        let left = fixture.path().join("left");
+        let right = fixture.path().join("right");
+
+        let get_item = |path: PathBuf| {
+            let metadata = fs::metadata(&path).unwrap();
+
+            Some(SideItem {
+                is_dir: metadata.is_dir(),
+                is_empty: false,
+                modified: None,
+                on_disk: true,
+                path,
+                size: metadata.len(),
+            })
+        };
+
+        for (name, expected) in [
+            ("same.txt", None),
+            ("changed.txt", Some(Difference::ContentChanged)),
+            ("retyped", Some(Difference::TypeChanged)),
+        ] {
+            assert_eq!(
+                get_difference(
+                    true,
+                    get_item(left.join(name)).as_ref(),
+                    get_item(right.join(name)).as_ref()
+                ),
+                expected
+            );
+        }
+
+        assert_eq!(
+            get_difference(false, get_item(left.join("same.txt")).as_ref(), None),
+            Some(Difference::Removed)
+        );
+        assert_eq!(
+            get_difference(false, None, get_item(right.join("same.txt")).as_ref()),
+            Some(Difference::Added)
+        );
+    }
+
+    #[test]
+    fn test_truncated_nested_directory() {
+        let side = [
+            ("D0/D1/A/x.txt", "x"),
+            ("D0/D1/B/y.txt", "y"),
+            ("D0/D1/c.txt", "c"),
+            ("D0/e.txt", "e"),
+            ("D0/f.txt", "f"),
+        ];
+        let left = create_fixture(&side);
+        let right = create_fixture(&side);
+
+        let args = GlobalArgs::from_iter(["nd", "--max-entries", "2", "--no-colors", "--no-icons"]);
+        let nomad_style = NomadStyle::default();
+
+        let mut left = collect_side(&args, &nomad_style, left.path().to_str().unwrap()).unwrap();
+        let mut right = collect_side(&args, &nomad_style, right.path().to_str().unwrap()).unwrap();
+        let (items, compared_items) = compare_items(
+            &args,
+            true,
+            &mut DifferenceCounts::default(),
+            &mut left,
+            &mut right,
+        )
+        .unwrap();
+
+        let mut tree =
+            TreeWriter::new(&nomad_style, TreeOutput::Collect, "root".to_string(), None).unwrap();
+        write_differences(
+            &args,
+            items,
+            &compared_items,
+            &left,
+            &nomad_style,
+            &right,
+            &mut TreeState::default(),
+            &mut tree,
+        )
+        .unwrap();
+        let (lines, _) = tree.finish().unwrap();
+
+        // The summary of `D1` is followed by the rest of `D0`, not the root.
+        assert_eq!(
+            lines.unwrap(),
+            vec![
+                "root",
+                "└── D0",
+                "    ├── D1",
+                "    │   ├── A",
+                "    │   │   └── x.txt",
+                "    │   ├── B",
+                "    │   │   └── y.txt",
+                "    │   └── … 1 more item (1 B)",
+                "    ├── e.txt",
+                "    └── … 1 more item (1 B)",
+            ]
+        );
+    }
+}
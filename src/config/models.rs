@@ -14,6 +14,8 @@ pub struct NomadConfig {
 /// Contains settings for the standard tree.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TreeSettings {
+    /// Contains the names of directories whose contents are always collapsed.
+    pub collapsed_directories: Option<Vec<String>>,
     /// Contains settings for all things related to Git in the standard tree.
     pub git: Option<TreeGit>,
     /// Contains settings for the color of tree labels (items and directories).
@@ -51,8 +53,12 @@ pub struct TreeItems {
 /// Contains settings for tree items' appearance.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TreeItemColor {
+    /// The color for dangling symlinks and symlink loops.
+    pub broken_symlink_color: Option<String>,
     /// The color for directories.
     pub directory_color: Option<String>,
+    /// The color for empty directories.
+    pub empty_directory_color: Option<String>,
 }
 
 /// Contains settings for the color of tree labels (items and directories).
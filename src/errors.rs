@@ -7,6 +7,10 @@ use std::io;
 /// Contains options for errors that may be raised throughout this program.
 #[derive(Debug, Error)]
 pub enum NomadError {
+    /// Something went wrong when reading an archive.
+    #[error("Unable to read the archive: {0}")]
+    ArchiveError(String),
+
     /// Something went wrong when trying to `bat` a file.
     #[error("Bat error: {0}")]
     BatError(#[from] bat::error::Error),
@@ -47,6 +51,10 @@ pub enum NomadError {
     #[error("IO error: {0}")]
     IOError(#[from] io::Error),
 
+    /// Something went wrong when reading a checksum manifest.
+    #[error("Unable to read the manifest: {0}")]
+    ManifestError(String),
+
     /// Something went wrong with the MPSC receiver.
     #[error("MPSC error: {0}")]
     MPSCError(#[from] std::sync::mpsc::RecvError),
@@ -80,14 +88,6 @@ pub enum NomadError {
     #[error("{0}")]
     PlainGitError(#[from] git2::Error),
 
-    /// Something went wrong while displaying a `ptree`.
-    #[error("{context}: {source}")]
-    PTreeError {
-        context: String,
-        #[source]
-        source: io::Error,
-    },
-
     /// Something went wrong when compiling a regex expression.
     #[error("{0}")]
     RegexError(#[from] regex::Error),
@@ -100,10 +100,18 @@ pub enum NomadError {
     #[error("Serde JSON error: {0}")]
     SerdeJSONError(#[from] serde_json::Error),
 
+    /// A snapshot that does not exist was entered.
+    #[error("There is no snapshot named {0}! Run `nd snapshot list` to see the saved snapshots")]
+    SnapshotNotFound(String),
+
     /// Something went wrong when deserializing/serializing the TOML config file.
     #[error("TOML error: {0}")]
     TOMLError(#[from] toml::de::Error),
 
+    /// A user or group that does not exist was entered.
+    #[error("{0} is not a user or group on this system!")]
+    UnknownOwner(String),
+
     /// Something went wrong when decoding to UTF-8.
     #[error("UTF-8 error: {0}")]
     UTF8Error(#[from] std::str::Utf8Error),
@@ -5,22 +5,25 @@ use std::time::Instant;
 use crate::{
     cli::{
         git::BranchOptions,
-        global::{GlobalArgs, LabelArgs, MetaArgs, ModifierArgs, RegexArgs, StyleArgs},
+        global::{
+            GlobalArgs, LabelArgs, MetaArgs, ModifierArgs, RegexArgs, SortArgs, SortBy,
+            StatsFormat, StyleArgs, TimeField,
+        },
     },
     errors::NomadError,
     style::models::NomadStyle,
     traverse::{
         format::highlight_matched,
-        models::{DirItem, FoundBranch},
+        models::FoundBranch,
         modes::NomadMode,
         traits::{ToTree, TransformFound},
     },
+    utils::export::{get_export, ExportMode},
 };
 
 use ansi_term::Colour;
 use anyhow::{Result, __private};
 use git2::{Branch, BranchType, Repository};
-use ptree::{item::StringItem, PrintConfig};
 use regex::Regex;
 
 use super::utils::get_repo_branch;
@@ -31,7 +34,7 @@ pub fn display_branches(
     nomad_style: &NomadStyle,
     repo: &Repository,
     target_directory: &str,
-) -> Result<Option<(StringItem, PrintConfig, Option<Vec<DirItem>>)>, NomadError> {
+) -> Result<(), NomadError> {
     let regex_expression = if let Some(ref pattern) = args.pattern {
         match Regex::new(&pattern.clone()) {
             Ok(regex) => Some(regex),
@@ -190,24 +193,57 @@ pub fn display_branches(
     // Hm... There is probably a better solution, but fuck it. Leaving it for now.
     let global_args = GlobalArgs {
         export: args.export.clone(),
+        export_format: args.export_format,
         labels: LabelArgs {
             all_labels: false,
             label_directories: false,
             numbers: args.numbers,
         },
         meta: MetaArgs {
+            apparent_size: false,
+            du: false,
+            dupes: false,
             metadata: false,
             tokei: false,
+            top: None,
         },
         modifiers: ModifierArgs {
+            changed_within: None,
+            compact: false,
             dirs: false,
             disrespect: false,
+            group: None,
             hidden: false,
+            ignore_file: Vec::new(),
+            ignore_glob: Vec::new(),
             max_depth: None,
+            max_entries: None,
             max_filesize: None,
+            min_filesize: None,
+            newer: None,
+            no_follow: false,
+            older_than: None,
+            parallel: false,
+            perm: Vec::new(),
+            setuid: false,
+            threads: None,
+            time_field: TimeField::Modified,
+            user: None,
         },
         regex: RegexArgs {
-            pattern: args.pattern.clone(),
+            contains: None,
+            exclude: Vec::new(),
+            ignore_case: false,
+            match_all: false,
+            match_dirs: false,
+            name_only: false,
+            patterns: args.pattern.clone().into_iter().collect(),
+            show_lines: false,
+        },
+        sort: SortArgs {
+            dirs_first: false,
+            reverse: false,
+            sort_by: SortBy::Name,
         },
         style: StyleArgs {
             no_colors: false,
@@ -216,18 +252,22 @@ pub fn display_branches(
             plain: false,
         },
         statistics: args.statistics,
+        stats_format: StatsFormat::Table,
     };
 
-    Ok(if args.flat {
-        None
-    } else {
-        Some(branches.transform(target_directory)?.to_tree(
+    if !args.flat {
+        branches.transform(target_directory)?.to_tree(
             &global_args,
+            None,
+            get_export(&global_args, ExportMode::GitBranch)?,
+            None,
             NomadMode::GitBranch,
             nomad_style,
             target_directory,
-        )?)
-    })
+        )?;
+    }
+
+    Ok(())
 }
 
 /// Format the branch into a flat view and then display it
@@ -243,12 +283,7 @@ fn display_flat_branch(
     upstream: Option<String>,
 ) {
     let branch_label = match matched {
-        Some(matched) => highlight_matched(
-            false,
-            nomad_style,
-            branch_name.to_string(),
-            (matched.0, matched.1),
-        ),
+        Some(matched) => highlight_matched(false, nomad_style, branch_name.to_string(), &[matched]),
         None => branch_name.to_string(),
     };
     let formatted_branch = if is_current_branch {
@@ -6,7 +6,25 @@ use crate::{cli::global::StyleArgs, errors::NomadError, style::models::NomadStyl
 use anyhow::Result;
 use git2::{Repository, Status, StatusOptions, StatusShow};
 
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// The order in which Git statuses are displayed when items are sorted by status.
+const STATUS_ORDER: [Status; 11] = [
+    Status::CONFLICTED,
+    Status::INDEX_DELETED,
+    Status::INDEX_MODIFIED,
+    Status::INDEX_NEW,
+    Status::INDEX_RENAMED,
+    Status::INDEX_TYPECHANGE,
+    Status::WT_DELETED,
+    Status::WT_MODIFIED,
+    Status::WT_NEW,
+    Status::WT_RENAMED,
+    Status::WT_TYPECHANGE,
+];
 
 /// Try to extend the `HashMap` containing status markers and their corresponding
 /// filenames with new Git repository items.
@@ -183,3 +201,89 @@ pub fn get_status_markers(
 
     Ok(formatted_items)
 }
+
+/// Get the name of a Git status, ie. `modified` or `staged_added`. The names
+/// match the marker settings in `nomad.toml`, and statuses take precedence in
+/// the same order as their markers.
+fn get_status_name(status: Status) -> Option<&'static str> {
+    [
+        (Status::INDEX_DELETED, "staged_deleted"),
+        (Status::INDEX_MODIFIED, "staged_modified"),
+        (Status::INDEX_NEW, "staged_added"),
+        (Status::INDEX_RENAMED, "staged_renamed"),
+        (Status::INDEX_TYPECHANGE, "staged_typechanged"),
+        (Status::WT_DELETED, "deleted"),
+        (Status::WT_MODIFIED, "modified"),
+        (Status::WT_NEW, "untracked"),
+        (Status::WT_RENAMED, "renamed"),
+        (Status::WT_TYPECHANGE, "typechanged"),
+        (Status::CONFLICTED, "conflicted"),
+    ]
+    .into_iter()
+    .find_map(|(flag, name)| status.contains(flag).then_some(name))
+}
+
+/// Get the name of the Git status of each changed item in the repository. The
+/// items are absolute paths, like the items in the marker map.
+pub fn get_status_names(
+    repo: &Repository,
+    target_directory: &str,
+) -> Result<HashMap<String, &'static str>, NomadError> {
+    let mut status_options = StatusOptions::new();
+    status_options
+        .show(StatusShow::IndexAndWorkdir)
+        .include_untracked(true)
+        .recurse_untracked_dirs(true);
+
+    let mut status_names = HashMap::new();
+    for repo_item in repo.statuses(Some(&mut status_options))?.iter() {
+        if let Some(name) = get_status_name(repo_item.status()) {
+            let item_name = repo
+                .path()
+                .parent()
+                .unwrap_or_else(|| Path::new(target_directory))
+                .join(repo_item.path().unwrap_or("?"))
+                .to_str()
+                .unwrap_or("?")
+                .to_string();
+
+            status_names.insert(item_name, name);
+        }
+    }
+
+    Ok(status_names)
+}
+
+/// Get the rank of each changed item in the repository based on its Git status.
+/// Directories take the rank of the highest ranked item they contain. Lower
+/// ranks are displayed first when items are sorted by status.
+pub fn get_status_ranks(repo: &Repository) -> Result<HashMap<PathBuf, usize>, NomadError> {
+    let mut status_options = StatusOptions::new();
+    status_options
+        .show(StatusShow::IndexAndWorkdir)
+        .include_untracked(true)
+        .recurse_untracked_dirs(true);
+
+    let repo_root = repo.path().parent().unwrap_or_else(|| Path::new("/"));
+    let mut ranks: HashMap<PathBuf, usize> = HashMap::new();
+
+    for repo_item in repo.statuses(Some(&mut status_options))?.iter() {
+        if let Some(rank) = STATUS_ORDER
+            .iter()
+            .position(|status| repo_item.status().contains(*status))
+        {
+            let item_path = repo_root.join(repo_item.path().unwrap_or("?"));
+
+            for ancestor in item_path.ancestors() {
+                if !ancestor.starts_with(repo_root) {
+                    break;
+                }
+
+                let current_rank = ranks.entry(ancestor.to_path_buf()).or_insert(rank);
+                *current_rank = (*current_rank).min(rank);
+            }
+        }
+    }
+
+    Ok(ranks)
+}
@@ -5,6 +5,7 @@ pub mod branch;
 pub mod commit;
 pub mod diff;
 pub mod markers;
+pub mod revision;
 pub mod status;
 pub mod trees;
 pub mod utils;
@@ -0,0 +1,214 @@
+//! Display the tree of a Git revision without checking it out.
+
+use super::utils::get_repo;
+use crate::{
+    cli::global::GlobalArgs,
+    errors::NomadError,
+    style::models::NomadStyle,
+    traverse::{
+        models::ItemMetadata,
+        modes::NomadMode,
+        traits::{ToTree, TransformFound},
+        virtual_items::{find_virtual_items, VirtualItem},
+    },
+    utils::export::{get_export, ExportMode},
+};
+
+use anyhow::{anyhow, Result};
+use git2::{ObjectType, Repository, Tree, TreeWalkMode, TreeWalkResult};
+
+use std::path::{Path, PathBuf};
+
+/// Get the tree of a revision for the target directory. The target directory
+/// may be anywhere within the repository.
+fn get_revision_tree<'a>(
+    repo: &'a Repository,
+    revision: &str,
+    target_directory: &str,
+) -> Result<Tree<'a>, NomadError> {
+    let tree = repo.revparse_single(revision)?.peel_to_tree()?;
+
+    let relative_path = repo
+        .workdir()
+        .and_then(|workdir| {
+            Path::new(target_directory)
+                .strip_prefix(workdir.canonicalize().ok()?)
+                .ok()
+        })
+        .filter(|relative_path| !relative_path.as_os_str().is_empty());
+
+    match relative_path {
+        Some(relative_path) => Ok(tree
+            .get_path(relative_path)?
+            .to_object(repo)?
+            .peel_to_tree()?),
+        None => Ok(tree),
+    }
+}
+
+/// Get the path that items of a revision are displayed under, ie. `nomad@v1.0.0`.
+///
+/// This path does not exist on disk, which allows `nd bat` to tell items of a
+/// revision apart from items in the working directory.
+fn get_revision_root(revision: &str, target_directory: &str) -> String {
+    format!("{target_directory}@{revision}")
+}
+
+/// Display the tree of a Git revision for the target directory.
+pub fn walk_revision(
+    args: &GlobalArgs,
+    nomad_style: &NomadStyle,
+    revision: &str,
+    target_directory: &str,
+) -> Result<(), NomadError> {
+    let repo = get_repo(target_directory).ok_or_else(|| {
+        NomadError::Error(anyhow!(
+            "Unable to display {revision}: {target_directory} is not in a Git repository!"
+        ))
+    })?;
+    let tree = get_revision_tree(&repo, revision, target_directory)?;
+    let odb = repo.odb()?;
+
+    let mut items = Vec::new();
+    tree.walk(TreeWalkMode::PreOrder, |parent, entry| {
+        let is_dir = entry.kind() != Some(ObjectType::Blob);
+
+        items.push(VirtualItem {
+            is_dir,
+            metadata: ItemMetadata {
+                mode: Some(entry.filemode() as u32),
+                modified: None,
+                size: if is_dir {
+                    None
+                } else {
+                    odb.read_header(entry.id())
+                        .ok()
+                        .map(|(size, _)| size as u64)
+                },
+            },
+            path: format!("{parent}{}", entry.name().unwrap_or("?")),
+        });
+
+        TreeWalkResult::Ok
+    })?;
+
+    let root = get_revision_root(revision, target_directory);
+    let export = get_export(args, ExportMode::Revision(revision))?;
+
+    find_virtual_items(args, items, &root)?
+        .into_iter()
+        .transform(&root)?
+        .to_tree(
+            args,
+            None,
+            export,
+            None,
+            NomadMode::Normal,
+            nomad_style,
+            &root,
+        )?;
+
+    Ok(())
+}
+
+/// Split the path of an item that was displayed in the tree of a revision into
+/// the target directory, the revision, and the item's path within the target
+/// directory. Returns `None` if the path is not within the tree of a revision.
+pub fn find_revision_item(item: &str) -> Option<(String, String, String)> {
+    let path = Path::new(item);
+    if path.exists() {
+        return None;
+    }
+
+    path.ancestors().skip(1).find_map(|ancestor| {
+        let root = ancestor.to_str()?;
+
+        // The target directory may contain `@` itself, so every `@` is tried.
+        root.match_indices('@').find_map(|(index, _)| {
+            let (target_directory, revision) = (&root[..index], &root[index + 1..]);
+
+            // Revisions may contain `/` themselves, ie. `origin/main`, so only
+            // revisions that exist in the repository are kept.
+            let repo = Repository::discover(target_directory).ok()?;
+            repo.revparse_single(revision).ok()?;
+
+            Some((
+                target_directory.to_string(),
+                revision.to_string(),
+                path.strip_prefix(ancestor).ok()?.to_str()?.to_string(),
+            ))
+        })
+    })
+}
+
+/// Read the contents of a file at a revision.
+pub fn read_revision_blob(
+    target_directory: &str,
+    revision: &str,
+    item: &str,
+) -> Result<Vec<u8>, NomadError> {
+    let repo = Repository::discover(target_directory)?;
+    let tree = get_revision_tree(&repo, revision, target_directory)?;
+    let blob = tree
+        .get_path(&PathBuf::from(item))?
+        .to_object(&repo)?
+        .peel_to_blob()?;
+
+    Ok(blob.content().to_vec())
+}
+
+#[cfg(test)]
+mod test_revision {
+    use super::*;
+
+    use crate::utils::test_utils::create_fixture;
+
+    use git2::Signature;
+
+    use std::fs;
+
+    #[test]
+    fn test_revision_items() {
+        let fixture = create_fixture(&[("src/main.rs", "fn main() {}")]);
+        let target_directory = fixture.path().canonicalize().unwrap();
+        let root = target_directory.to_str().unwrap();
+
+        let repo = Repository::init(&target_directory).unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("src/main.rs")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("nomad", "nomad@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "Initial", &tree, &[])
+            .unwrap();
+
+        fs::write(
+            target_directory.join("src/main.rs"),
+            "fn main() { changed(); }",
+        )
+        .unwrap();
+
+        let item = format!("{}/src/main.rs", get_revision_root("HEAD", root));
+        let (directory, revision, relative_path) = find_revision_item(&item).unwrap();
+        assert_eq!(
+            (
+                directory.as_str(),
+                revision.as_str(),
+                relative_path.as_str()
+            ),
+            (root, "HEAD", "src/main.rs")
+        );
+        assert_eq!(
+            read_revision_blob(&directory, &revision, &relative_path).unwrap(),
+            b"fn main() {}"
+        );
+
+        let src = target_directory.join("src");
+        let blob = read_revision_blob(src.to_str().unwrap(), "HEAD", "main.rs").unwrap();
+        assert_eq!(blob, b"fn main() {}");
+
+        assert!(
+            find_revision_item(target_directory.join("src/main.rs").to_str().unwrap()).is_none()
+        );
+    }
+}
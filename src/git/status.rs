@@ -4,25 +4,34 @@ use super::markers::get_status_markers;
 use crate::{
     cli::{
         git,
-        global::{GlobalArgs, LabelArgs, MetaArgs, ModifierArgs, RegexArgs, StyleArgs},
+        global::{
+            GlobalArgs, LabelArgs, MetaArgs, ModifierArgs, RegexArgs, SortArgs, StatsFormat,
+            StyleArgs, TimeField,
+        },
     },
     errors::NomadError,
     style::models::NomadStyle,
     traverse::{
+        matcher::ItemMatcher,
         models::FoundItem,
         modes::NomadMode,
+        sizes::DirectorySizes,
+        sort::ItemSorter,
         traits::{ToTree, TransformFound},
     },
+    utils::export::{get_export, ExportMode},
 };
 
 use ansi_term::{Colour, Style};
-use anyhow::{Result, __private};
+use anyhow::Result;
 use git2::{ObjectType, Repository};
 use itertools::Itertools;
-use ptree::{item::StringItem, PrintConfig};
-use regex::Regex;
 
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 /// Build a tree that only contains items that are tracked in Git.
 pub fn display_status_tree(
@@ -30,28 +39,61 @@ pub fn display_status_tree(
     nomad_style: &NomadStyle,
     repo: &Repository,
     target_directory: &str,
-) -> Result<Option<(StringItem, PrintConfig)>, NomadError> {
+) -> Result<(), NomadError> {
     // Hm... There is probably a better solution, but fuck it. Leaving it for now.
     let global_args = GlobalArgs {
         export: args.export.clone(),
+        export_format: args.export_format,
         labels: LabelArgs {
             all_labels: args.labels.all_labels,
             label_directories: args.labels.label_directories,
             numbers: args.labels.numbers,
         },
         meta: MetaArgs {
+            apparent_size: args.meta.apparent_size,
+            du: args.meta.du,
+            dupes: false,
             metadata: args.meta.metadata,
             tokei: args.meta.tokei,
+            top: args.meta.top,
         },
         modifiers: ModifierArgs {
+            changed_within: None,
+            compact: false,
             dirs: false,
             disrespect: false,
+            group: None,
             hidden: false,
+            ignore_file: Vec::new(),
+            ignore_glob: Vec::new(),
             max_depth: None,
+            max_entries: None,
             max_filesize: None,
+            min_filesize: None,
+            newer: None,
+            no_follow: false,
+            older_than: None,
+            parallel: false,
+            perm: Vec::new(),
+            setuid: false,
+            threads: None,
+            time_field: TimeField::Modified,
+            user: None,
         },
         regex: RegexArgs {
-            pattern: args.regex.pattern.clone(),
+            contains: args.regex.contains.clone(),
+            exclude: args.regex.exclude.clone(),
+            ignore_case: args.regex.ignore_case,
+            match_all: args.regex.match_all,
+            match_dirs: args.regex.match_dirs,
+            name_only: args.regex.name_only,
+            patterns: args.regex.patterns.clone(),
+            show_lines: args.regex.show_lines,
+        },
+        sort: SortArgs {
+            dirs_first: args.sort.dirs_first,
+            reverse: args.sort.reverse,
+            sort_by: args.sort.sort_by,
         },
         style: StyleArgs {
             no_colors: args.style.no_colors,
@@ -60,6 +102,7 @@ pub fn display_status_tree(
             plain: args.style.plain,
         },
         statistics: args.statistics,
+        stats_format: StatsFormat::Table,
     };
 
     get_status_markers(&args.style, nomad_style, repo, target_directory).map_or_else(
@@ -73,14 +116,9 @@ pub fn display_status_tree(
                         .paint("Nothing to commit. Working tree clean.".to_string())
                 );
 
-                Ok(None)
+                Ok(())
             } else {
-                Ok(Some(build_status_tree(
-                    &global_args,
-                    marker_map,
-                    nomad_style,
-                    target_directory,
-                )?))
+                build_status_tree(&global_args, marker_map, nomad_style, target_directory)
             }
         },
     )
@@ -128,49 +166,86 @@ fn build_status_tree(
     marker_map: HashMap<String, String>,
     nomad_style: &NomadStyle,
     target_directory: &str,
-) -> Result<(StringItem, PrintConfig), NomadError> {
-    let regex_expression = if let Some(ref pattern) = args.regex.pattern {
-        match Regex::new(&pattern.clone()) {
-            Ok(regex) => Some(regex),
-            Err(error) => return __private::Err(NomadError::RegexError(error)),
-        }
+) -> Result<(), NomadError> {
+    let matcher = ItemMatcher::new(&args.regex)?;
+    let export = get_export(args, ExportMode::GitStatus)?;
+
+    let changed_items = marker_map
+        .into_iter()
+        .filter(|(absolute_path, _)| {
+            absolute_path.contains(target_directory)
+                && !matcher.is_excluded(
+                    Path::new(absolute_path)
+                        .strip_prefix(target_directory)
+                        .unwrap_or_else(|_| Path::new("?")),
+                )
+        })
+        .collect::<HashMap<String, String>>();
+
+    let directory_sizes = if args.meta.du || args.meta.top.is_some() {
+        Some(Arc::new(DirectorySizes::new(
+            args.meta.apparent_size,
+            changed_items.keys().map(PathBuf::from),
+            target_directory,
+            args.meta.top,
+        )))
     } else {
         None
     };
+    let sorter = ItemSorter::new(&args.sort, directory_sizes.clone(), target_directory);
 
-    let (tree, config, _) = marker_map
+    changed_items
         .iter()
         .filter_map(|(absolute_path, marker)| {
-            if absolute_path.contains(target_directory) {
-                match regex_expression {
-                    Some(ref regex) => match regex.find(
-                        Path::new(&absolute_path)
-                            .strip_prefix(target_directory)
-                            .unwrap_or_else(|_| Path::new("?"))
-                            .to_str()
-                            .unwrap_or("?"),
-                    ) {
-                        Some(matched) => Some(FoundItem {
-                            marker: Some(marker.to_string()),
-                            matched: Some((matched.start(), matched.end())),
-                            path: absolute_path.clone(),
-                        }),
-                        None => None,
-                    },
-                    None => Some(FoundItem {
-                        marker: Some(marker.to_string()),
-                        matched: None,
-                        path: absolute_path.to_string(),
-                    }),
-                }
+            if directory_sizes
+                .as_ref()
+                .is_none_or(|sizes| sizes.is_displayed(Path::new(absolute_path)))
+            {
+                let relative_path = Path::new(&absolute_path)
+                    .strip_prefix(target_directory)
+                    .unwrap_or_else(|_| Path::new("?"));
+
+                let matched = match matcher.find(relative_path) {
+                    Some(matched) => matched,
+                    None if args.regex.match_dirs
+                        && matcher.in_matched_directory(relative_path) =>
+                    {
+                        Vec::new()
+                    }
+                    None => return None,
+                };
+
+                let content_matches = if matcher.has_content_pattern() {
+                    Some(matcher.search_contents(Path::new(absolute_path))?)
+                } else {
+                    None
+                };
+
+                Some(FoundItem {
+                    content_matches,
+                    is_dir: false,
+                    is_empty: false,
+                    link_problem: None,
+                    marker: Some(marker.to_string()),
+                    matched,
+                    metadata: None,
+                    path: absolute_path.to_string(),
+                })
             } else {
                 None
             }
         })
-        .sorted_by_key(|found_item| found_item.path.to_string())
-        .collect::<Vec<FoundItem>>()
+        .sorted_by(|a, b| sorter.compare_paths(Path::new(&a.path), Path::new(&b.path)))
         .transform(target_directory)?
-        .to_tree(args, NomadMode::GitStatus, nomad_style, target_directory)?;
+        .to_tree(
+            args,
+            directory_sizes.as_deref(),
+            export,
+            None,
+            NomadMode::GitStatus,
+            nomad_style,
+            target_directory,
+        )?;
 
-    Ok((tree, config))
+    Ok(())
 }
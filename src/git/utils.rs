@@ -1,8 +1,6 @@
 //! Contains useful utilities that support Git functionality.
 
-use std::{ffi::OsStr, path::Path};
-
-use crate::{errors::NomadError, style::models::NomadStyle};
+use crate::{errors::NomadError, style::models::NomadStyle, traverse::format::paint_matches};
 
 use ansi_term::{Colour, Style};
 use anyhow::{anyhow, Result};
@@ -75,7 +73,7 @@ pub fn paint_git_item(
     filename: &str,
     marker: &str,
     nomad_style: &NomadStyle,
-    matched: Option<(usize, usize)>,
+    matched: &[(usize, usize)],
 ) -> String {
     let staged_deleted = &nomad_style
         .git
@@ -112,72 +110,5 @@ pub fn paint_git_item(
         _ => Style::new(),
     };
 
-    paint_with_highlight(filename, matched, nomad_style, style)
-}
-
-/// Highlight the filename in the Git status color. Also paint the pattern if a
-/// pattern is matched.
-fn paint_with_highlight(
-    filename: &str,
-    matched: Option<(usize, usize)>,
-    nomad_style: &NomadStyle,
-    style: Style,
-) -> String {
-    let painted_file = match matched {
-        Some(ranges) => {
-            if (0..filename.len()).contains(&ranges.0)
-                && (0..filename.len() + 1).contains(&ranges.1)
-            {
-                let mut painted_prefix = filename[..ranges.0]
-                    .chars()
-                    .into_iter()
-                    .map(|character| style.paint(format!("{character}")).to_string())
-                    .collect::<Vec<String>>();
-                let mut painted_matched = filename[ranges.0..ranges.1]
-                    .chars()
-                    .into_iter()
-                    .map(|character| {
-                        nomad_style
-                            .tree
-                            .regex
-                            .match_color
-                            .paint(format!("{character}"))
-                            .to_string()
-                    })
-                    .collect::<Vec<String>>();
-                let mut painted_suffix = filename[ranges.1..]
-                    .chars()
-                    .into_iter()
-                    .map(|character| style.paint(format!("{character}")).to_string())
-                    .collect::<Vec<String>>();
-
-                painted_prefix.append(&mut painted_matched);
-                painted_prefix.append(&mut painted_suffix);
-
-                painted_prefix.join("")
-            } else {
-                filename
-                    .chars()
-                    .into_iter()
-                    .map(|character| style.paint(format!("{character}")).to_string())
-                    .collect::<Vec<String>>()
-                    .join("")
-            }
-        }
-        None => filename
-            .chars()
-            .into_iter()
-            .map(|character| style.paint(format!("{character}")).to_string())
-            .collect::<Vec<String>>()
-            .join(""),
-    };
-
-    let filename = Path::new(&painted_file)
-        .file_name()
-        .unwrap_or_else(|| OsStr::new("?"))
-        .to_str()
-        .unwrap_or("?")
-        .to_string();
-
-    filename
+    paint_matches(filename, matched, nomad_style, style)
 }
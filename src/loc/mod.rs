@@ -6,6 +6,7 @@ pub mod utils;
 use std::path::PathBuf;
 
 use ansi_term::{Colour, Style};
+use serde::Serialize;
 use term_table::{
     row::Row,
     table_cell::{Alignment, TableCell},
@@ -28,6 +29,30 @@ pub fn loc_in_dir(target_directory: &str) -> Language {
     languages.total()
 }
 
+/// Contains the line counts for a single file.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct LocCounts {
+    /// The number of blank lines.
+    pub blanks: usize,
+    /// The number of lines of code.
+    pub code: usize,
+    /// The number of comments.
+    pub comments: usize,
+    /// The total number of lines.
+    pub lines: usize,
+}
+
+/// Get the line counts for a single file from the `Language` struct. Returns
+/// `None` if `tokei` has no data for the file.
+pub fn loc_counts(file_path: &str, tokei: &Language) -> Option<LocCounts> {
+    get_file_report(&tokei.children, PathBuf::from(file_path)).map(|report| LocCounts {
+        blanks: report.stats.blanks,
+        code: report.stats.code,
+        comments: report.stats.comments,
+        lines: report.stats.lines(),
+    })
+}
+
 /// Get the `CodeStats` for a single file from the `Language` struct.
 pub fn loc_in_file(args: &GlobalArgs, file_path: &str, tokei: &Language) -> Vec<String> {
     let report = get_file_report(&tokei.children, PathBuf::from(file_path));
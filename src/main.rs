@@ -12,31 +12,43 @@
 #![allow(clippy::wildcard_in_or_patterns)]
 #![allow(clippy::zero_prefixed_literal)]
 
+mod archive;
+mod checksum;
 mod cli;
+mod compare;
 mod config;
 mod errors;
 mod git;
 mod loc;
 mod models;
 mod releases;
+mod snapshot;
 mod style;
 mod switches;
 mod traverse;
 mod ui;
 mod utils;
 
+use archive::{is_archive, walk_archive};
 use cli::{get_args, SubCommands};
+use compare::compare_directories;
 use config::toml::parse_config;
+use git::revision::walk_revision;
 use loc::run_tokei;
 use releases::{check_for_update, update_self};
 use style::settings::process_settings;
-use switches::{config::run_config, filetype::run_filetypes, git::run_git, release::run_releases};
+use switches::{
+    checksum::run_checksum, config::run_config, filetype::run_filetypes, git::run_git,
+    release::run_releases, snapshot::run_snapshot,
+};
 use syntect::{highlighting::ThemeSet, parsing::SyntaxSet};
-use traverse::{modes::NomadMode, utils::build_walker, walk_directory};
+use traverse::{
+    modes::NomadMode, utils::build_walker, walk_directories, walk_directory, watch::watch_directory,
+};
 use ui::{enter_rootless_mode, ExitMode};
 use utils::{
     bat::run_bat,
-    export::{export_tree, ExportMode},
+    export::{get_export, ExportMode},
     icons::{get_icons_by_extension, get_icons_by_name},
     open::open_files,
     paint::{paint_error, show_banner},
@@ -48,7 +60,7 @@ use anyhow::Result;
 use errors::NomadError;
 use lazy_static::lazy_static;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, path::Path};
 
 lazy_static! {
     /// The alphabet in `Vec<char>`.
@@ -98,7 +110,7 @@ fn main() -> Result<(), NomadError> {
         let (nomad_config, config_path) = parse_config()?;
         let nomad_style = process_settings(nomad_config);
 
-        let target_directory = if let Some(ref directory) = args.directory {
+        let target_directory = if let Some(directory) = args.directories.first() {
             canonicalize_path(directory).map_or_else(
                 |error| {
                     paint_error(error);
@@ -133,6 +145,14 @@ fn main() -> Result<(), NomadError> {
                             }
                         }
                     }
+                    SubCommands::Checksum(checksum_options) => {
+                        run_checksum(checksum_options, &nomad_style, &target_directory);
+                    }
+                    SubCommands::Compare(compare_options) => {
+                        if let Err(error) = compare_directories(compare_options, &nomad_style) {
+                            paint_error(error);
+                        }
+                    }
                     SubCommands::Config(config_options) => {
                         run_config(config_options, config_path, &nomad_style);
                     }
@@ -178,6 +198,9 @@ fn main() -> Result<(), NomadError> {
                     SubCommands::Releases(release_option) => {
                         run_releases(release_option);
                     }
+                    SubCommands::Snapshot(snapshot_options) => {
+                        run_snapshot(snapshot_options, &nomad_style, &target_directory);
+                    }
                     SubCommands::Tokei => {
                         run_tokei(&target_directory);
                     }
@@ -191,31 +214,72 @@ fn main() -> Result<(), NomadError> {
                         }
                     }
                 }
-            } else {
-                // Run `nomad` in normal mode.
-                match build_walker(&args.global, &target_directory, None) {
-                    Ok(mut walker) => {
-                        match walk_directory(
+            } else if args.watch {
+                // Run `nomad` in watch mode.
+                if let Err(error) = watch_directory(&args.global, &nomad_style, &target_directory) {
+                    paint_error(error);
+                }
+            } else if let Some(ref revision) = args.rev {
+                // Run `nomad` for a Git revision.
+                if let Err(error) =
+                    walk_revision(&args.global, &nomad_style, revision, &target_directory)
+                {
+                    paint_error(error);
+                }
+            } else if is_archive(Path::new(&target_directory)) {
+                // Run `nomad` for an archive.
+                if let Err(error) = walk_archive(&args.global, &nomad_style, &target_directory) {
+                    paint_error(error);
+                }
+            } else if args.directories.len() > 1 {
+                // Run `nomad` in normal mode for several directories.
+                let target_directories = args
+                    .directories
+                    .iter()
+                    .filter_map(|directory| {
+                        canonicalize_path(directory).map_or_else(
+                            |error| {
+                                paint_error(error);
+                                None
+                            },
+                            Some,
+                        )
+                    })
+                    .collect::<Vec<String>>();
+
+                match get_export(&args.global, ExportMode::Normal) {
+                    Ok(export) => {
+                        if let Err(error) = walk_directories(
                             &args.global,
-                            NomadMode::Normal,
+                            export,
                             &nomad_style,
-                            &target_directory,
-                            &mut walker,
+                            &target_directories,
                         ) {
-                            Ok((tree, config, _)) => {
-                                if let Some(export) = args.global.export {
-                                    if let Err(error) =
-                                        export_tree(config, ExportMode::Normal, &export, tree)
-                                    {
-                                        paint_error(error);
-                                    }
-                                }
-                            }
-                            Err(error) => paint_error(error),
+                            paint_error(error);
                         }
                     }
                     Err(error) => paint_error(error),
                 }
+            } else {
+                // Run `nomad` in normal mode.
+                match build_walker(&args.global, &target_directory, None) {
+                    Ok(walker) => match get_export(&args.global, ExportMode::Normal) {
+                        Ok(export) => {
+                            if let Err(error) = walk_directory(
+                                &args.global,
+                                export,
+                                NomadMode::Normal,
+                                &nomad_style,
+                                &target_directory,
+                                walker,
+                            ) {
+                                paint_error(error);
+                            }
+                        }
+                        Err(error) => paint_error(error),
+                    },
+                    Err(error) => paint_error(error),
+                }
             }
         }
     }
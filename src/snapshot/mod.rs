@@ -0,0 +1,492 @@
+//! Save snapshots of a directory and display what changed since then.
+
+use crate::{
+    cli::global::{Age, FileSize, GlobalArgs, Permission, TimeField},
+    compare::{collect_side, display_differences, Side, SideItem},
+    errors::NomadError,
+    style::models::NomadStyle,
+    utils::{cache::get_cache_directory, export::ExportMode},
+};
+
+use ansi_term::Colour;
+use anyhow::Result;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, create_dir_all, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// Contains the metadata of an item stored in a snapshot.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct StoredItem {
+    /// Indicates whether this is a directory.
+    pub is_dir: bool,
+    /// Indicates whether this is an empty directory.
+    pub is_empty: bool,
+    /// The last modified time of the item since the Unix epoch.
+    pub modified: Option<Duration>,
+    /// The size of the item in bytes.
+    pub size: u64,
+}
+
+/// Contains the arguments that select the items stored in a snapshot. They are
+/// applied again when the snapshot is diffed. Otherwise, items that are only
+/// selected on one side would appear to be added or removed.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct SnapshotFilters {
+    /// The value of `--changed-within`.
+    pub changed_within: Option<Age>,
+    /// The value of `--contains`.
+    pub contains: Option<String>,
+    /// The value of `--disrespect`.
+    pub disrespect: bool,
+    /// The value of `--exclude`.
+    pub exclude: Vec<String>,
+    /// The value of `--group`.
+    pub group: Option<String>,
+    /// The value of `--hidden`.
+    pub hidden: bool,
+    /// The value of `--ignore-case`.
+    pub ignore_case: bool,
+    /// The value of `--ignore-file`.
+    pub ignore_file: Vec<String>,
+    /// The value of `--ignore-glob`.
+    pub ignore_glob: Vec<String>,
+    /// The value of `--match-all`.
+    pub match_all: bool,
+    /// The value of `--match-dirs`.
+    pub match_dirs: bool,
+    /// The value of `--max-depth`.
+    pub max_depth: Option<usize>,
+    /// The value of `--max-filesize`.
+    pub max_filesize: Option<FileSize>,
+    /// The value of `--min-filesize`.
+    pub min_filesize: Option<FileSize>,
+    /// The value of `--name-only`.
+    pub name_only: bool,
+    /// The value of `--newer`.
+    pub newer: Option<String>,
+    /// The value of `--no-follow`.
+    pub no_follow: bool,
+    /// The value of `--older-than`.
+    pub older_than: Option<Age>,
+    /// The value of `--pattern`.
+    pub patterns: Vec<String>,
+    /// The value of `--perm`.
+    pub perm: Vec<Permission>,
+    /// The value of `--setuid`.
+    pub setuid: bool,
+    /// The value of `--time-field`.
+    pub time_field: TimeField,
+    /// The value of `--user`.
+    pub user: Option<String>,
+}
+
+impl SnapshotFilters {
+    /// Get the filters from the arguments.
+    pub fn new(args: &GlobalArgs) -> Self {
+        let modifiers = args.modifiers.clone();
+        let regex = args.regex.clone();
+
+        Self {
+            changed_within: modifiers.changed_within,
+            contains: regex.contains,
+            disrespect: modifiers.disrespect,
+            exclude: regex.exclude,
+            group: modifiers.group,
+            hidden: modifiers.hidden,
+            ignore_case: regex.ignore_case,
+            ignore_file: modifiers.ignore_file,
+            ignore_glob: modifiers.ignore_glob,
+            match_all: regex.match_all,
+            match_dirs: regex.match_dirs,
+            max_depth: modifiers.max_depth,
+            max_filesize: modifiers.max_filesize,
+            min_filesize: modifiers.min_filesize,
+            name_only: regex.name_only,
+            newer: modifiers.newer,
+            no_follow: modifiers.no_follow,
+            older_than: modifiers.older_than,
+            patterns: regex.patterns,
+            perm: modifiers.perm,
+            setuid: modifiers.setuid,
+            time_field: modifiers.time_field,
+            user: modifiers.user,
+        }
+    }
+
+    /// Replace the filters within a copy of the arguments. The arguments that
+    /// only change how the items are displayed are kept.
+    pub fn apply(&self, args: &GlobalArgs) -> GlobalArgs {
+        let mut args = args.clone();
+
+        let modifiers = &mut args.modifiers;
+        modifiers.changed_within = self.changed_within;
+        modifiers.disrespect = self.disrespect;
+        modifiers.group = self.group.clone();
+        modifiers.hidden = self.hidden;
+        modifiers.ignore_file = self.ignore_file.clone();
+        modifiers.ignore_glob = self.ignore_glob.clone();
+        modifiers.max_depth = self.max_depth;
+        modifiers.max_filesize = self.max_filesize;
+        modifiers.min_filesize = self.min_filesize;
+        modifiers.newer = self.newer.clone();
+        modifiers.no_follow = self.no_follow;
+        modifiers.older_than = self.older_than;
+        modifiers.perm = self.perm.clone();
+        modifiers.setuid = self.setuid;
+        modifiers.time_field = self.time_field;
+        modifiers.user = self.user.clone();
+
+        let regex = &mut args.regex;
+        regex.contains = self.contains.clone();
+        regex.exclude = self.exclude.clone();
+        regex.ignore_case = self.ignore_case;
+        regex.match_all = self.match_all;
+        regex.match_dirs = self.match_dirs;
+        regex.name_only = self.name_only;
+        regex.patterns = self.patterns.clone();
+
+        args
+    }
+}
+
+/// Contains the items within a directory at the time the snapshot was saved.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct Snapshot {
+    /// The local time the snapshot was saved.
+    pub created: String,
+    /// The arguments the items were selected with. Snapshots saved by older
+    /// versions do not contain them.
+    #[serde(default)]
+    pub filters: Option<SnapshotFilters>,
+    /// The items keyed by their paths relative to the root.
+    pub items: BTreeMap<String, StoredItem>,
+    /// The absolute path to the directory the snapshot was saved for.
+    pub root: String,
+}
+
+impl Snapshot {
+    /// Create a new `Snapshot` from the items found in a directory with the
+    /// arguments.
+    pub fn new(args: &GlobalArgs, side: Side) -> Self {
+        Self {
+            created: Local::now().format("%F %H:%M:%S").to_string(),
+            filters: Some(SnapshotFilters::new(args)),
+            items: side
+                .items
+                .into_iter()
+                .map(|(relative_path, item)| {
+                    (
+                        relative_path.to_str().unwrap_or("?").to_string(),
+                        StoredItem {
+                            is_dir: item.is_dir,
+                            is_empty: item.is_empty,
+                            modified: item.modified,
+                            size: item.size,
+                        },
+                    )
+                })
+                .collect(),
+            root: side.root,
+        }
+    }
+
+    /// Convert the stored items into one side of a comparison. The items are
+    /// not on disk anymore, so their contents cannot be compared or opened.
+    pub fn into_side(self) -> Side {
+        let root = self.root;
+
+        Side {
+            directory_sizes: None,
+            items: self
+                .items
+                .into_iter()
+                .map(|(relative_path, item)| {
+                    (
+                        PathBuf::from(&relative_path),
+                        SideItem {
+                            is_dir: item.is_dir,
+                            is_empty: item.is_empty,
+                            modified: item.modified,
+                            on_disk: false,
+                            path: Path::new(&root).join(&relative_path),
+                            size: item.size,
+                        },
+                    )
+                })
+                .collect(),
+            root,
+        }
+    }
+}
+
+/// Get the path to the directory containing all snapshots. Snapshots are stored
+/// in nomad's cache directory, next to `items.json`.
+fn get_snapshot_directory() -> Result<PathBuf, NomadError> {
+    Ok(get_cache_directory()?.join("snapshots"))
+}
+
+/// Get the path to the JSON file for a snapshot. Names containing path
+/// separators are rejected so that snapshots stay within the snapshot directory.
+fn get_snapshot_path(name: &str) -> Result<PathBuf, NomadError> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(NomadError::PathError(format!(
+            "{name} is not a valid snapshot name!"
+        )));
+    }
+
+    Ok(get_snapshot_directory()?.join(format!("{name}.json")))
+}
+
+/// Write a snapshot to a JSON file.
+fn write_snapshot(snapshot: &Snapshot, snapshot_path: &Path) -> Result<(), NomadError> {
+    if let Some(parent) = snapshot_path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    serde_json::to_writer(BufWriter::new(File::create(snapshot_path)?), snapshot)?;
+
+    Ok(())
+}
+
+/// Read a snapshot from a JSON file.
+fn read_snapshot(snapshot_path: &Path) -> Result<Snapshot, NomadError> {
+    Ok(serde_json::from_reader(BufReader::new(File::open(
+        snapshot_path,
+    )?))?)
+}
+
+/// Save a snapshot of the items within the target directory.
+pub fn save_snapshot(
+    args: &GlobalArgs,
+    name: &str,
+    nomad_style: &NomadStyle,
+    target_directory: &str,
+) -> Result<(), NomadError> {
+    let snapshot_path = get_snapshot_path(name)?;
+    let snapshot = Snapshot::new(args, collect_side(args, nomad_style, target_directory)?);
+
+    write_snapshot(&snapshot, &snapshot_path)?;
+
+    println!(
+        "\n{}\n",
+        Colour::Green.bold().paint(format!(
+            "Saved snapshot {name} of {} ({} items)",
+            snapshot.root,
+            snapshot.items.len()
+        ))
+    );
+
+    Ok(())
+}
+
+/// Display the items that appeared, disappeared, or changed in the snapshot's
+/// directory since the snapshot was saved.
+pub fn diff_snapshot(
+    args: &GlobalArgs,
+    name: &str,
+    nomad_style: &NomadStyle,
+) -> Result<(), NomadError> {
+    let start = Instant::now();
+
+    let snapshot_path = get_snapshot_path(name)?;
+    if !snapshot_path.exists() {
+        return Err(NomadError::SnapshotNotFound(name.to_string()));
+    }
+
+    let snapshot = read_snapshot(&snapshot_path)?;
+
+    // The current items are selected like the stored items.
+    let args = match snapshot.filters {
+        Some(ref filters) => {
+            if *filters != SnapshotFilters::new(args) {
+                println!(
+                    "\n{}",
+                    Colour::Fixed(172).bold().paint(format!(
+                        "Using the filters snapshot {name} was saved with instead of the filters passed to diff"
+                    ))
+                );
+            }
+
+            filters.apply(args)
+        }
+        None => args.clone(),
+    };
+
+    let root_name = format!("{name} ({}) ⇄ now", snapshot.created);
+    let current = collect_side(&args, nomad_style, &snapshot.root)?;
+
+    display_differences(
+        &args,
+        false,
+        ExportMode::Snapshot(name),
+        snapshot.into_side(),
+        nomad_style,
+        current,
+        &root_name,
+        start,
+    )
+}
+
+/// List all saved snapshots with the time they were saved and their directories.
+pub fn list_snapshots() -> Result<(), NomadError> {
+    let snapshot_directory = get_snapshot_directory()?;
+
+    let mut snapshots = Vec::new();
+    if snapshot_directory.exists() {
+        for entry in fs::read_dir(snapshot_directory)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                if let (Some(name), Ok(snapshot)) = (
+                    path.file_stem().and_then(|stem| stem.to_str()),
+                    read_snapshot(&path),
+                ) {
+                    snapshots.push((name.to_string(), snapshot.created, snapshot.root));
+                }
+            }
+        }
+    }
+
+    if snapshots.is_empty() {
+        return Err(NomadError::NothingFound);
+    }
+
+    snapshots.sort();
+
+    println!();
+    for (name, created, root) in snapshots {
+        println!(
+            "{} {} {}",
+            Colour::Fixed(172).bold().paint(name),
+            Colour::Fixed(245).paint(created),
+            root
+        );
+    }
+    println!();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_snapshot {
+    use super::*;
+
+    use crate::{
+        compare::{get_difference, Difference},
+        utils::test_utils::create_fixture,
+    };
+
+    use structopt::StructOpt;
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let fixture = create_fixture(&[
+            ("src/kept.rs", "fn main() {}"),
+            ("src/changed.rs", "fn main() {}"),
+            ("removed.txt", ""),
+        ]);
+        let target_directory = fixture.path();
+
+        let args = GlobalArgs::from_iter(["nd"]);
+        let nomad_style = NomadStyle::default();
+        let root = target_directory.to_str().unwrap();
+
+        let snapshot = Snapshot::new(&args, collect_side(&args, &nomad_style, root).unwrap());
+        let snapshot_directory = create_fixture(&[]);
+        let snapshot_path = snapshot_directory.path().join("snapshot.json");
+        write_snapshot(&snapshot, &snapshot_path).unwrap();
+
+        let stored = read_snapshot(&snapshot_path).unwrap();
+        assert_eq!(stored, snapshot);
+
+        fs::write(
+            target_directory.join("src/changed.rs"),
+            "fn main() { run(); }",
+        )
+        .unwrap();
+        fs::remove_file(target_directory.join("removed.txt")).unwrap();
+        fs::write(target_directory.join("added.txt"), "").unwrap();
+
+        let mut previous = stored.into_side();
+        let mut current = collect_side(&args, &nomad_style, root).unwrap();
+
+        let mut get_change = |relative_path: &str| {
+            get_difference(
+                false,
+                previous.items.remove(Path::new(relative_path)).as_ref(),
+                current.items.remove(Path::new(relative_path)).as_ref(),
+            )
+        };
+
+        assert_eq!(get_change("added.txt"), Some(Difference::Added));
+        assert_eq!(get_change("removed.txt"), Some(Difference::Removed));
+        assert_eq!(
+            get_change("src/changed.rs"),
+            Some(Difference::ContentChanged)
+        );
+        assert_eq!(get_change("src/kept.rs"), None);
+        assert_eq!(get_change("src"), None);
+    }
+
+    #[test]
+    fn test_snapshot_keeps_items_hidden_by_top() {
+        let fixture = create_fixture(&[("large.txt", &"a".repeat(100)), ("small.txt", "a")]);
+        let target_directory = fixture.path();
+
+        let args = GlobalArgs::from_iter(["nd", "--top", "1", "--apparent-size"]);
+        let root = target_directory.to_str().unwrap();
+
+        let side = collect_side(&args, &NomadStyle::default(), root).unwrap();
+        assert_eq!(
+            side.items.keys().collect::<Vec<&PathBuf>>(),
+            vec![Path::new("large.txt"), Path::new("small.txt")]
+        );
+        assert!(side
+            .directory_sizes
+            .is_some_and(|sizes| !sizes.is_displayed(&target_directory.join("small.txt"))));
+    }
+
+    #[test]
+    fn test_snapshot_filters() {
+        let saved = GlobalArgs::from_iter(["nd", "--hidden", "--exclude", "target", "--dirs"]);
+        let snapshot = Snapshot::new(
+            &saved,
+            Side {
+                directory_sizes: None,
+                items: BTreeMap::new(),
+                root: "root".to_string(),
+            },
+        );
+        let filters = snapshot.filters.unwrap();
+
+        // The filters replace the ones passed to diff, but `--dirs` and
+        // `--compact` only change how the items are displayed.
+        let diffed = filters.apply(&GlobalArgs::from_iter(["nd", "--compact"]));
+        assert!(diffed.modifiers.hidden);
+        assert_eq!(diffed.regex.exclude, vec!["target".to_string()]);
+        assert!(!diffed.modifiers.dirs);
+        assert!(diffed.modifiers.compact);
+        assert_eq!(SnapshotFilters::new(&diffed), filters);
+
+        let stored: Snapshot =
+            serde_json::from_str(r#"{"created":"2024-01-01 00:00:00","items":{},"root":"root"}"#)
+                .unwrap();
+        assert!(stored.filters.is_none());
+    }
+
+    #[test]
+    fn test_snapshot_names() {
+        assert!(get_snapshot_path("before-refactor").is_ok());
+        assert!(get_snapshot_path("../items").is_err());
+        assert!(get_snapshot_path("").is_err());
+    }
+}
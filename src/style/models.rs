@@ -67,6 +67,8 @@ pub struct GitStyle {
 /// Contains styles for the tree itself.
 #[derive(Debug)]
 pub struct TreeStyle {
+    /// Contains the names of directories whose contents are always collapsed.
+    pub collapsed_directories: Vec<String>,
     /// Contains the indentation setting.
     pub indent: usize,
     /// Contains indent characters for the tree itself.
@@ -84,8 +86,12 @@ pub struct TreeStyle {
 /// Contains the colors for items in the tree.
 #[derive(Debug)]
 pub struct ItemColors {
+    /// The color for dangling symlinks and symlink loops.
+    pub broken_symlink_color: Style,
     /// The color for directories.
     pub directory_color: Style,
+    /// The color for empty directories.
+    pub empty_directory_color: Style,
 }
 
 /// Contains colors for the tree labels.
@@ -191,6 +197,7 @@ impl Default for NomadStyle {
                 untracked_marker: "U".to_string(),
             },
             tree: TreeStyle {
+                collapsed_directories: Vec::new(),
                 indent: 4,
                 indent_chars: IndentStyles {
                     down: UTF_CHARS.down.to_string(),
@@ -200,7 +207,9 @@ impl Default for NomadStyle {
                     turn_right: UTF_CHARS.turn_right.to_string(),
                 },
                 item_colors: ItemColors {
+                    broken_symlink_color: Colour::Red.bold(),
                     directory_color: Colour::Blue.bold(),
+                    empty_directory_color: Colour::Fixed(243).bold(),
                 },
                 label_colors: LabelColors {
                     item_labels: Colour::Fixed(068).bold(),
@@ -19,6 +19,8 @@ pub fn process_settings(nomad_config: NomadConfig) -> NomadStyle {
     let mut nomad_style = NomadStyle::default();
 
     if let Some(tree_settings) = nomad_config.tree {
+        nomad_style.tree.collapsed_directories =
+            tree_settings.collapsed_directories.unwrap_or_default();
         nomad_style.tree.indent = tree_settings.indent.unwrap_or(4);
         nomad_style.tree.padding = tree_settings.padding.unwrap_or(1);
 
@@ -39,10 +41,18 @@ pub fn process_settings(nomad_config: NomadConfig) -> NomadStyle {
 
         if let Some(items) = tree_settings.items {
             if let Some(colors) = items.colors {
+                if let Some(broken_symlink_color) = colors.broken_symlink_color {
+                    nomad_style.tree.item_colors.broken_symlink_color =
+                        convert_to_ansi_style(&broken_symlink_color.to_lowercase());
+                }
                 if let Some(directory_color) = colors.directory_color {
                     nomad_style.tree.item_colors.directory_color =
                         convert_to_ansi_style(&directory_color.to_lowercase());
                 }
+                if let Some(empty_directory_color) = colors.empty_directory_color {
+                    nomad_style.tree.item_colors.empty_directory_color =
+                        convert_to_ansi_style(&empty_directory_color.to_lowercase());
+                }
             }
         }
 
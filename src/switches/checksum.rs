@@ -0,0 +1,34 @@
+//! Executing the checksum subcommand.
+
+use crate::{
+    checksum::{verify_manifest, write_manifest},
+    cli::checksum::ChecksumOptions,
+    style::models::NomadStyle,
+    utils::paint::paint_error,
+};
+
+use std::process;
+
+/// Write or verify a checksum manifest. Exits with a non-zero status if the
+/// directory does not match the manifest so that it may be used in scripts.
+pub fn run_checksum(
+    checksum_options: &ChecksumOptions,
+    nomad_style: &NomadStyle,
+    target_directory: &str,
+) {
+    let result = match checksum_options.verify {
+        Some(ref manifest) => {
+            verify_manifest(checksum_options, manifest, nomad_style, target_directory)
+        }
+        None => write_manifest(checksum_options, nomad_style, target_directory).map(|_| true),
+    };
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(error) => {
+            paint_error(error);
+            process::exit(1);
+        }
+    }
+}
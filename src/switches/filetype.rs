@@ -11,7 +11,7 @@ use crate::{
         walk_directory,
     },
     utils::{
-        export::{export_tree, ExportMode},
+        export::{get_export, ExportMode},
         paint::paint_error,
         table::{TableView, TabledItems},
     },
@@ -36,32 +36,24 @@ pub fn run_filetypes(
             ) {
                 Ok(types) => {
                     match build_walker(&match_options.general, target_directory, Some(types)) {
-                        Ok(mut walker) => {
-                            match walk_directory(
-                                &match_options.general,
-                                NomadMode::Normal,
-                                nomad_style,
-                                target_directory,
-                                &mut walker,
-                            ) {
-                                Ok((tree, config, _)) => {
-                                    if let Some(export) = &match_options.general.export {
-                                        if let Err(error) = export_tree(
-                                            config,
-                                            ExportMode::Filetype(
-                                                &match_options.filetypes,
-                                                &match_options.globs,
-                                            ),
-                                            export,
-                                            tree,
-                                        ) {
-                                            paint_error(error);
-                                        }
-                                    }
+                        Ok(walker) => match get_export(
+                            &match_options.general,
+                            ExportMode::Filetype(&match_options.filetypes, &match_options.globs),
+                        ) {
+                            Ok(export) => {
+                                if let Err(error) = walk_directory(
+                                    &match_options.general,
+                                    export,
+                                    NomadMode::Normal,
+                                    nomad_style,
+                                    target_directory,
+                                    walker,
+                                ) {
+                                    paint_error(error);
                                 }
-                                Err(error) => paint_error(error),
                             }
-                        }
+                            Err(error) => paint_error(error),
+                        },
                         Err(error) => paint_error(error),
                     }
                 }
@@ -77,32 +69,24 @@ pub fn run_filetypes(
             ) {
                 Ok(types) => {
                     match build_walker(&negate_options.general, target_directory, Some(types)) {
-                        Ok(mut walker) => {
-                            match walk_directory(
-                                &negate_options.general,
-                                NomadMode::Normal,
-                                nomad_style,
-                                target_directory,
-                                &mut walker,
-                            ) {
-                                Ok((tree, config, _)) => {
-                                    if let Some(export) = &negate_options.general.export {
-                                        if let Err(error) = export_tree(
-                                            config,
-                                            ExportMode::Filetype(
-                                                &negate_options.filetypes,
-                                                &negate_options.globs,
-                                            ),
-                                            export,
-                                            tree,
-                                        ) {
-                                            paint_error(error);
-                                        }
-                                    }
+                        Ok(walker) => match get_export(
+                            &negate_options.general,
+                            ExportMode::Filetype(&negate_options.filetypes, &negate_options.globs),
+                        ) {
+                            Ok(export) => {
+                                if let Err(error) = walk_directory(
+                                    &negate_options.general,
+                                    export,
+                                    NomadMode::Normal,
+                                    nomad_style,
+                                    target_directory,
+                                    walker,
+                                ) {
+                                    paint_error(error);
                                 }
-                                Err(error) => paint_error(error),
                             }
-                        }
+                            Err(error) => paint_error(error),
+                        },
                         Err(error) => paint_error(error),
                     }
                 }
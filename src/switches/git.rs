@@ -14,7 +14,6 @@ use crate::{
     },
     style::models::NomadStyle,
     utils::{
-        export::{export_tree, ExportMode},
         paint::paint_error,
         search::{indiscriminate_search, SearchMode},
     },
@@ -90,19 +89,10 @@ pub fn run_git(
                 Err(_) => paint_error(NomadError::GitBlameError),
             },
             GitOptions::Branch(branch_options) => {
-                match display_branches(branch_options, nomad_style, &repo, target_directory) {
-                    Ok(tree_items) => {
-                        if let Some((tree, config, _)) = tree_items {
-                            if let Some(export) = &branch_options.export {
-                                if let Err(error) =
-                                    export_tree(config, ExportMode::GitBranch, export, tree)
-                                {
-                                    paint_error(error);
-                                }
-                            }
-                        }
-                    }
-                    Err(error) => paint_error(error),
+                if let Err(error) =
+                    display_branches(branch_options, nomad_style, &repo, target_directory)
+                {
+                    paint_error(error);
                 }
             }
             GitOptions::Commit { message } => {
@@ -165,21 +155,10 @@ pub fn run_git(
                     }
                 }
 
-                match display_status_tree(status_options, nomad_style, &repo, target_directory) {
-                    Ok(tree_items) => {
-                        if let Some((tree, config)) = tree_items {
-                            if let Some(export) = &status_options.export {
-                                if let Err(error) =
-                                    export_tree(config, ExportMode::GitStatus, export, tree)
-                                {
-                                    paint_error(error);
-                                }
-                            }
-                        }
-                    }
-                    Err(error) => {
-                        paint_error(error);
-                    }
+                if let Err(error) =
+                    display_status_tree(status_options, nomad_style, &repo, target_directory)
+                {
+                    paint_error(error);
                 }
             }
         }
@@ -1,6 +1,8 @@
 //! Chunks of match commands for program execution.
 
+pub mod checksum;
 pub mod config;
 pub mod filetype;
 pub mod git;
 pub mod release;
+pub mod snapshot;
@@ -0,0 +1,32 @@
+//! Executing snapshot subcommands.
+
+use crate::{
+    cli::snapshot::SnapshotOptions,
+    snapshot::{diff_snapshot, list_snapshots, save_snapshot},
+    style::models::NomadStyle,
+    utils::paint::paint_error,
+};
+
+/// `match` the snapshot subcommand and execute it.
+pub fn run_snapshot(
+    snapshot_options: &SnapshotOptions,
+    nomad_style: &NomadStyle,
+    target_directory: &str,
+) {
+    let result = match snapshot_options {
+        SnapshotOptions::Diff(diff_options) => {
+            diff_snapshot(&diff_options.general, &diff_options.name, nomad_style)
+        }
+        SnapshotOptions::List => list_snapshots(),
+        SnapshotOptions::Save(save_options) => save_snapshot(
+            &save_options.general,
+            &save_options.name,
+            nomad_style,
+            target_directory,
+        ),
+    };
+
+    if let Err(error) = result {
+        paint_error(error);
+    }
+}
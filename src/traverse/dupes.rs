@@ -0,0 +1,221 @@
+//! Find files whose contents are identical to another file in the tree.
+
+use crate::{cli::global::GlobalArgs, style::models::NomadStyle, utils::meta::convert_bytes};
+
+use ansi_term::Colour;
+use blake3::{Hash, Hasher};
+
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io,
+    path::Path,
+};
+
+/// The marker displayed after a file whose contents are identical to another file.
+pub const DUPLICATE_MARKER: &str = "⧉";
+
+/// Contains files with identical contents.
+#[derive(Debug)]
+pub struct DuplicateGroup {
+    /// The ID displayed next to each file in the group.
+    pub id: usize,
+    /// The files in the group, in the order they are displayed in the tree.
+    pub items: Vec<String>,
+    /// The size of each file in the group.
+    pub size: u64,
+}
+
+impl DuplicateGroup {
+    /// The space taken up by every copy except for the first.
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.items.len() as u64 - 1)
+    }
+}
+
+/// Contains the groups of duplicate files found in the tree.
+#[derive(Debug, Default)]
+pub struct DuplicateReport {
+    /// The groups of duplicate files in the order they were found.
+    groups: Vec<DuplicateGroup>,
+    /// Maps each duplicate file to the ID of its group.
+    ids: HashMap<String, usize>,
+}
+
+impl DuplicateReport {
+    /// Find the files with identical contents and record them in new groups.
+    ///
+    /// Files are grouped by their size first so that only files that have the
+    /// same size as another file are read and hashed. Empty files and files that
+    /// cannot be read are skipped.
+    pub fn add<'a, I: IntoIterator<Item = &'a str>>(&mut self, items: I) {
+        let items = items.into_iter().collect::<Vec<&str>>();
+
+        let mut sizes: HashMap<u64, Vec<&str>> = HashMap::new();
+        for item in items.iter() {
+            if let Ok(metadata) = fs::metadata(item) {
+                if metadata.is_file() && metadata.len() > 0 {
+                    sizes.entry(metadata.len()).or_default().push(item);
+                }
+            }
+        }
+
+        let mut hashes: HashMap<&str, (u64, Hash)> = HashMap::new();
+        let mut copies: HashMap<(u64, Hash), usize> = HashMap::new();
+        for (size, same_size) in sizes.into_iter().filter(|(_, items)| items.len() > 1) {
+            for item in same_size {
+                if let Ok(hash) = hash_file(Path::new(item)) {
+                    hashes.insert(item, (size, hash));
+                    *copies.entry((size, hash)).or_insert(0) += 1;
+                }
+            }
+        }
+
+        // Groups are numbered in the order their first file is displayed.
+        let mut groups: HashMap<(u64, Hash), usize> = HashMap::new();
+        for item in items {
+            let key = match hashes.remove(item) {
+                Some(key) if copies.get(&key).is_some_and(|count| *count > 1) => key,
+                _ => continue,
+            };
+
+            let index = *groups.entry(key).or_insert_with(|| {
+                self.groups.push(DuplicateGroup {
+                    id: self.groups.len() + 1,
+                    items: Vec::new(),
+                    size: key.0,
+                });
+
+                self.groups.len() - 1
+            });
+
+            self.groups[index].items.push(item.to_string());
+            self.ids.insert(item.to_string(), self.groups[index].id);
+        }
+    }
+
+    /// Get the ID of the group a file belongs to.
+    pub fn get(&self, item: &str) -> Option<usize> {
+        self.ids.get(item).copied()
+    }
+
+    /// Format the summary listing each group of duplicate files and the space it
+    /// wastes. Files are displayed relative to `root` next to their numbered
+    /// labels so that they may be used with other commands.
+    pub fn format(
+        &self,
+        args: &GlobalArgs,
+        labels: &HashMap<String, String>,
+        nomad_style: &NomadStyle,
+        root: &Path,
+    ) -> String {
+        let plain = args.style.plain || args.style.no_colors;
+        let format_size = |size: u64| {
+            i64::try_from(size)
+                .map_or("unknown size".to_string(), convert_bytes)
+                .trim()
+                .to_string()
+        };
+
+        let mut summary = format!(
+            "| {} | {} wasted |",
+            match self.groups.len() {
+                1 => "1 duplicate group".to_string(),
+                count => format!("{count} duplicate groups"),
+            },
+            format_size(self.groups.iter().map(|group| group.wasted()).sum())
+        );
+
+        for group in self.groups.iter() {
+            summary.push_str(&format!(
+                "\n  {} {} × {} ({} wasted)",
+                format_duplicate(args, group.id),
+                format_size(group.size),
+                group.items.len(),
+                format_size(group.wasted())
+            ));
+
+            for item in group.items.iter() {
+                let path = Path::new(item)
+                    .strip_prefix(root)
+                    .unwrap_or_else(|_| Path::new(item))
+                    .to_str()
+                    .unwrap_or("?");
+
+                summary.push_str(&match labels.get(item) {
+                    Some(number) if plain => format!("\n    [{number}] {path}"),
+                    Some(number) => format!(
+                        "\n    [{}] {path}",
+                        nomad_style.tree.label_colors.item_labels.paint(number)
+                    ),
+                    None => format!("\n    {path}"),
+                });
+            }
+        }
+
+        summary
+    }
+
+    /// Check whether no duplicate files were found.
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+}
+
+/// Hash the contents of a file.
+fn hash_file(item: &Path) -> Result<Hash, io::Error> {
+    let mut hasher = Hasher::new();
+    io::copy(&mut File::open(item)?, &mut hasher)?;
+
+    Ok(hasher.finalize())
+}
+
+/// Format the marker and group ID displayed after a duplicate file, ie. `⧉ 2`.
+pub fn format_duplicate(args: &GlobalArgs, id: usize) -> String {
+    let marker = format!("{DUPLICATE_MARKER} {id}");
+
+    if args.style.plain || args.style.no_colors {
+        marker
+    } else {
+        Colour::Purple.bold().paint(marker).to_string()
+    }
+}
+
+#[cfg(test)]
+mod test_dupes {
+    use super::*;
+
+    use crate::utils::test_utils::create_fixture;
+
+    #[test]
+    fn test_duplicate_groups() {
+        let contents = [
+            ("lib.rs", "fn lib() {}"),
+            ("other.rs", "fn one() {}"),
+            ("empty.txt", ""),
+            ("vendor/empty.txt", ""),
+            ("vendor/lib.rs", "fn lib() {}"),
+            ("vendor/copy.rs", "fn lib() {}"),
+            ("vendor/other.rs", "fn two() {}"),
+        ];
+        let fixture = create_fixture(&contents);
+
+        let items = contents
+            .iter()
+            .map(|(item, _)| fixture.path().join(item).to_str().unwrap().to_string())
+            .collect::<Vec<String>>();
+
+        let mut report = DuplicateReport::default();
+        report.add(items.iter().map(|item| item.as_str()));
+
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(
+            report.groups[0].items,
+            vec![items[0].clone(), items[4].clone(), items[5].clone()]
+        );
+        assert_eq!(report.groups[0].wasted(), 22);
+        assert_eq!(report.get(&items[5]), Some(1));
+        assert_eq!(report.get(&items[1]), None);
+        assert_eq!(report.get(&items[2]), None);
+    }
+}
@@ -0,0 +1,202 @@
+//! Decide which traversed items are displayed in the tree.
+
+use super::{
+    links::{get_link_error, LinkProblem},
+    matcher::ItemMatcher,
+    models::FoundItem,
+    permissions::PermissionFilter,
+    sizes::DirectorySizes,
+    times::TimeFilter,
+};
+use crate::{cli::global::GlobalArgs, errors::NomadError};
+
+use ignore::DirEntry;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Get whether a traversed entry is a directory, its symlink problem, and its path.
+///
+/// Dangling symlinks and symlink loops are reported as errors when symlinks are
+/// followed. They are kept as entries marked with their problem so that they are
+/// displayed. Symlinks with a problem are never traversed, so they are not
+/// directories. Returns `None` for any other error.
+pub fn read_entry(
+    dir_entry: &Result<DirEntry, ignore::Error>,
+    follow_links: bool,
+) -> Option<(bool, Option<LinkProblem>, PathBuf)> {
+    match dir_entry {
+        Ok(entry) => {
+            let link_problem = entry
+                .path_is_symlink()
+                .then(|| LinkProblem::of(entry.path(), follow_links))
+                .flatten();
+
+            Some((
+                link_problem.is_none()
+                    && entry
+                        .file_type()
+                        .is_some_and(|file_type| file_type.is_dir()),
+                link_problem,
+                entry.path().to_path_buf(),
+            ))
+        }
+        Err(error) => {
+            get_link_error(error).map(|(path, link_problem)| (false, Some(link_problem), path))
+        }
+    }
+}
+
+/// Applies the client's patterns and filters to the items found while the
+/// directory is traversed.
+pub struct ItemFilter {
+    /// The client's CLI parameters.
+    args: GlobalArgs,
+    /// The size of each item if directory sizes are displayed.
+    pub directory_sizes: Option<Arc<DirectorySizes>>,
+    /// Matches the patterns against each item.
+    matcher: ItemMatcher,
+    /// Filters files by their permissions if `--perm` or `--setuid` is used.
+    permission_filter: Option<PermissionFilter>,
+    /// Filters items by their timestamps if any of the time filters are used.
+    time_filter: Option<TimeFilter>,
+    /// The root of the tree.
+    target_directory: String,
+}
+
+impl ItemFilter {
+    /// Create a new `ItemFilter` for the tree rooted at `target_directory`.
+    pub fn new(
+        args: &GlobalArgs,
+        directory_sizes: Option<Arc<DirectorySizes>>,
+        target_directory: &str,
+    ) -> Result<Self, NomadError> {
+        Ok(Self {
+            args: args.clone(),
+            directory_sizes,
+            matcher: ItemMatcher::new(&args.regex)?,
+            permission_filter: PermissionFilter::new(&args.modifiers)?,
+            time_filter: TimeFilter::new(&args.modifiers)?,
+            target_directory: target_directory.to_string(),
+        })
+    }
+
+    /// Check whether a directory is at the depth limit. The items within it are
+    /// not traversed, so it is displayed on its own.
+    pub fn is_truncated(&self, directory: &Path) -> bool {
+        self.args.modifiers.max_depth.is_some_and(|max_depth| {
+            directory
+                .strip_prefix(&self.target_directory)
+                .is_ok_and(|relative_path| relative_path.components().count() >= max_depth)
+        })
+    }
+
+    /// Check whether a traversed item is displayed in the tree. Returns the item
+    /// without its Git status marker if it is displayed.
+    ///
+    /// Directories are only displayed on their own if they are empty or at the
+    /// depth limit, since they are displayed with the items within them otherwise.
+    pub fn find(
+        &self,
+        is_dir: bool,
+        is_empty: bool,
+        link_problem: Option<LinkProblem>,
+        path: PathBuf,
+    ) -> Option<FoundItem> {
+        let is_truncated = is_dir && self.is_truncated(&path);
+
+        if let Some(ref sizes) = self.directory_sizes {
+            if !sizes.is_displayed(&path) {
+                return None;
+            }
+        }
+
+        let relative_path = path
+            .strip_prefix(&self.target_directory)
+            .unwrap_or_else(|_| Path::new("?"));
+
+        let matched = if self.matcher.has_patterns() {
+            let directory_match = if is_dir && self.args.regex.match_dirs {
+                self.matcher.find_in_name(relative_path)
+            } else {
+                None
+            };
+
+            match directory_match {
+                Some(matched) => matched,
+                None if is_dir && !is_empty && !is_truncated => return None,
+                None => match self.matcher.find(relative_path) {
+                    Some(matched) => matched,
+                    None if self.args.regex.match_dirs
+                        && self.matcher.in_matched_directory(relative_path) =>
+                    {
+                        Vec::new()
+                    }
+                    None => return None,
+                },
+            }
+        } else if is_dir && !is_empty && !is_truncated {
+            return None;
+        } else {
+            Vec::new()
+        };
+
+        // Symlink problems do not have a size or permissions of their own, so
+        // they never match the filters for them.
+        if link_problem.is_some()
+            && (self.args.modifiers.max_filesize.is_some()
+                || self.args.modifiers.min_filesize.is_some()
+                || self.permission_filter.is_some())
+        {
+            return None;
+        }
+
+        if let Some(min_filesize) = self.args.modifiers.min_filesize {
+            // Empty directories do not contain any files that are large enough.
+            if is_empty
+                || (!is_dir
+                    && !fs::metadata(&path).is_ok_and(|metadata| metadata.len() >= min_filesize.0))
+            {
+                return None;
+            }
+        }
+
+        if let Some(ref permission_filter) = self.permission_filter {
+            // Only files are checked so that the directories containing them
+            // are still displayed.
+            if is_empty || (!is_dir && !permission_filter.is_match(&path)) {
+                return None;
+            }
+        }
+
+        if let Some(ref time_filter) = self.time_filter {
+            if !time_filter.is_match(&path) {
+                return None;
+            }
+        }
+
+        let content_matches = if self.matcher.has_content_pattern() {
+            if is_dir {
+                return None;
+            }
+
+            Some(self.matcher.search_contents(&path)?)
+        } else {
+            None
+        };
+
+        Some(FoundItem {
+            content_matches,
+            is_dir,
+            is_empty,
+            link_problem,
+            marker: None,
+            matched,
+            metadata: None,
+            path: path.to_str().unwrap_or("?").to_string(),
+        })
+    }
+}
@@ -5,49 +5,72 @@ use crate::{
     git::utils::paint_git_item,
     style::models::NomadStyle,
     utils::{
-        meta::get_metadata,
+        meta::{convert_bytes, format_item_metadata, get_metadata},
         paths::{get_filename, get_symlink},
     },
 };
 
-use ansi_term::Colour;
+use ansi_term::{Colour, Style};
 
 use std::{ffi::OsStr, path::Path};
 
-use super::models::TransformedBranch;
+use super::{
+    links::LinkProblem,
+    models::{HiddenItems, ItemMetadata, MatchedLine, TransformedBranch},
+};
 
 /// Format how directories are displayed in the tree.
+///
+/// The directory is displayed as `display_name` instead of its own name if a
+/// chain of directories was compacted into it.
 pub fn format_directory(
     args: &GlobalArgs,
+    display_name: Option<&str>,
+    is_empty: bool,
     item: &Path,
     label: Option<String>,
-    matched: Option<(usize, usize)>,
+    marker: Option<String>,
+    matched: &[(usize, usize)],
+    metadata: Option<&ItemMetadata>,
     nomad_style: &NomadStyle,
     target_directory: &str,
 ) -> String {
-    let icon = "\u{f115}".to_string(); // 
-    let metadata = get_metadata(args, item);
+    let icon = if is_empty {
+        "\u{f114}".to_string() // 
+    } else {
+        "\u{f115}".to_string() // 
+    };
+    let metadata = match metadata {
+        Some(metadata) => format_item_metadata(args, metadata),
+        None => get_metadata(args, item),
+    };
+    let name = display_name.map_or_else(|| get_filename(item), |name| name.to_string());
 
     let mut directory_label = if args.style.plain || args.style.no_colors {
-        get_filename(item)
+        name
     } else {
-        match matched {
-            Some(ranges) => highlight_matched(
-                true,
+        let directory_color = if is_empty {
+            nomad_style.tree.item_colors.empty_directory_color
+        } else {
+            nomad_style.tree.item_colors.directory_color
+        };
+
+        if matched.is_empty() {
+            directory_color.paint(name).to_string()
+        } else {
+            let relative_path = item
+                .strip_prefix(target_directory)
+                .unwrap_or_else(|_| Path::new("?"))
+                .to_str()
+                .unwrap_or("?");
+
+            paint_spans(
+                relative_path,
+                relative_path.len().saturating_sub(name.len()),
+                matched,
                 nomad_style,
-                item.strip_prefix(target_directory)
-                    .unwrap_or_else(|_| Path::new("?"))
-                    .to_str()
-                    .unwrap_or("?")
-                    .to_string(),
-                ranges,
-            ),
-            None => nomad_style
-                .tree
-                .item_colors
-                .directory_color
-                .paint(get_filename(item))
-                .to_string(),
+                directory_color,
+            )
         }
     };
 
@@ -70,6 +93,9 @@ pub fn format_directory(
         format!("{icon} {directory_label}")
     };
 
+    if let (Some(marker), false) = (marker, args.style.no_git || args.style.plain) {
+        formatted = format!("{marker} {formatted}");
+    }
     if let Some(label) = label {
         formatted = format!(
             "[{}] {formatted}",
@@ -90,13 +116,14 @@ pub fn format_content(
     git_marker: Option<String>,
     icon: String,
     item: &Path,
-    matched: Option<(usize, usize)>,
+    matched: &[(usize, usize)],
+    metadata: Option<&ItemMetadata>,
     nomad_style: &NomadStyle,
-    number: Option<i32>,
+    number: Option<String>,
     target_directory: &str,
 ) -> String {
     let mut filename = get_filename(item);
-    let metadata = get_metadata(args, item);
+    let link_problem = LinkProblem::of(item, !args.modifiers.no_follow);
 
     let mut item_string =
         if let (Some(marker), false) = (git_marker, args.style.no_git || args.style.plain) {
@@ -128,8 +155,8 @@ pub fn format_content(
         } else if args.style.no_colors {
             format!("{icon} {filename}")
         } else {
-            filename = if let Some(ranges) = matched {
-                highlight_matched(
+            if !matched.is_empty() {
+                filename = highlight_matched(
                     false,
                     nomad_style,
                     item.strip_prefix(target_directory)
@@ -137,11 +164,9 @@ pub fn format_content(
                         .to_str()
                         .unwrap_or("?")
                         .to_string(),
-                    ranges,
-                )
-            } else {
-                filename
-            };
+                    matched,
+                );
+            }
 
             format!("{icon} {filename}")
         };
@@ -149,27 +174,38 @@ pub fn format_content(
     if item.is_symlink() {
         let symlink = get_symlink(item);
 
-        item_string = format!(
-            "{item_string} {}",
-            if args.style.plain || args.style.no_colors {
-                symlink
-            } else {
-                Colour::Yellow.bold().paint(symlink).to_string()
+        item_string = match (link_problem, args.style.plain || args.style.no_colors) {
+            (Some(link_problem), true) => {
+                format!("{} {item_string} {symlink}", link_problem.marker())
             }
-        );
+            (Some(link_problem), false) => {
+                let broken_symlink_color = nomad_style.tree.item_colors.broken_symlink_color;
+
+                format!(
+                    "{} {item_string} {}",
+                    broken_symlink_color.paint(link_problem.marker()),
+                    broken_symlink_color.paint(symlink)
+                )
+            }
+            (None, true) => format!("{item_string} {symlink}"),
+            (None, false) => format!("{item_string} {}", Colour::Yellow.bold().paint(symlink)),
+        };
     }
 
     if let Some(number) = number {
         item_string = format!(
             "[{}] {item_string}",
-            nomad_style
-                .tree
-                .label_colors
-                .item_labels
-                .paint(format!("{number}"))
+            nomad_style.tree.label_colors.item_labels.paint(number)
         );
     }
-    if args.meta.metadata {
+    // Symlink problems are only displayed with their marker since the metadata
+    // would describe the item they point to, if it exists.
+    if args.meta.metadata && link_problem.is_none() {
+        let metadata = match metadata {
+            Some(metadata) => format_item_metadata(args, metadata),
+            None => get_metadata(args, item),
+        };
+
         item_string = format!("{metadata} {item_string}")
     }
 
@@ -181,75 +217,154 @@ pub fn highlight_matched(
     for_dir: bool,
     nomad_style: &NomadStyle,
     path: String,
-    ranges: (usize, usize),
+    ranges: &[(usize, usize)],
 ) -> String {
-    if (0..path.len()).contains(&ranges.0) && (0..path.len() + 1).contains(&ranges.1) {
-        let mut prefix = path[..ranges.0]
-            .chars()
-            .into_iter()
-            .map(|character| {
-                if for_dir {
-                    nomad_style
-                        .tree
-                        .item_colors
-                        .directory_color
-                        .paint(character.to_string())
-                        .to_string()
-                } else {
-                    format!("{character}")
-                }
-            })
-            .collect::<Vec<String>>();
-        let mut painted_matched = path[ranges.0..ranges.1]
-            .chars()
-            .into_iter()
-            .map(|character| {
-                nomad_style
-                    .tree
-                    .regex
-                    .match_color
-                    .paint(format!("{character}"))
-                    .to_string()
-            })
-            .collect::<Vec<String>>();
-        let mut suffix = path[ranges.1..]
-            .chars()
-            .into_iter()
-            .map(|character| {
-                if for_dir {
-                    nomad_style
-                        .tree
-                        .item_colors
-                        .directory_color
-                        .paint(character.to_string())
-                        .to_string()
-                } else {
-                    format!("{character}")
-                }
-            })
-            .collect::<Vec<String>>();
+    let style = if for_dir {
+        nomad_style.tree.item_colors.directory_color
+    } else {
+        Style::new()
+    };
 
-        prefix.append(&mut painted_matched);
-        prefix.append(&mut suffix);
+    paint_matches(&path, ranges, nomad_style, style)
+}
 
-        let highlighted_path = prefix.join("");
+/// Paint the item's name and highlight each pattern match within it. The ranges
+/// are relative to the start of the path, so only the parts of a match that are
+/// within the item's name are highlighted.
+pub fn paint_matches(
+    path: &str,
+    ranges: &[(usize, usize)],
+    nomad_style: &NomadStyle,
+    style: Style,
+) -> String {
+    match Path::new(path).file_name().and_then(OsStr::to_str) {
+        Some(name) => paint_spans(path, path.len() - name.len(), ranges, nomad_style, style),
+        None => "?".to_string(),
+    }
+}
 
-        Path::new(&highlighted_path)
-            .file_name()
-            .unwrap_or_else(|| OsStr::new("?"))
-            .to_str()
-            .unwrap_or("?")
-            .to_string()
+/// Paint the text starting at `position` and highlight each span within it.
+fn paint_spans(
+    text: &str,
+    mut position: usize,
+    ranges: &[(usize, usize)],
+    nomad_style: &NomadStyle,
+    style: Style,
+) -> String {
+    let mut painted = String::new();
+
+    for (start, end) in ranges {
+        let start = (*start).clamp(position, text.len());
+        let end = (*end).clamp(start, text.len());
+
+        if start == end || !text.is_char_boundary(start) || !text.is_char_boundary(end) {
+            continue;
+        }
+
+        if start > position {
+            painted.push_str(&style.paint(&text[position..start]).to_string());
+        }
+        painted.push_str(
+            &nomad_style
+                .tree
+                .regex
+                .match_color
+                .paint(&text[start..end])
+                .to_string(),
+        );
+
+        position = end;
+    }
+
+    if position < text.len() {
+        painted.push_str(&style.paint(&text[position..]).to_string());
+    }
+
+    painted
+}
+
+/// Format the number of lines that matched the `--contains` pattern, which is
+/// displayed after the file's name.
+pub fn format_match_count(args: &GlobalArgs, count: usize, nomad_style: &NomadStyle) -> String {
+    let plain_count = if count == 1 {
+        "(1 match)".to_string()
     } else {
-        Path::new(&path)
-            .file_name()
-            .unwrap_or_else(|| OsStr::new("?"))
-            .to_str()
-            .unwrap_or("?")
+        format!("({count} matches)")
+    };
+
+    if args.style.plain || args.style.no_colors {
+        plain_count
+    } else {
+        nomad_style
+            .tree
+            .regex
+            .match_color
+            .paint(plain_count)
             .to_string()
     }
 }
 
+/// Format a line that matched the `--contains` pattern. Each match within the
+/// line is highlighted.
+pub fn format_matched_line(
+    args: &GlobalArgs,
+    line: &MatchedLine,
+    nomad_style: &NomadStyle,
+) -> String {
+    if args.style.plain || args.style.no_colors {
+        format!("{}: {}", line.number, line.text)
+    } else {
+        format!(
+            "{} {}",
+            Colour::Fixed(243).paint(format!("{}:", line.number)),
+            paint_spans(&line.text, 0, &line.matched, nomad_style, Style::new())
+        )
+    }
+}
+
+/// Format the summary of the items in a directory that were not displayed, ie.
+/// `… 1,234 more items (12 MB)`.
+pub fn format_hidden_items(args: &GlobalArgs, hidden_items: &HiddenItems) -> String {
+    let mut digits = hidden_items.count.to_string();
+    let mut count = String::new();
+    while digits.len() > 3 {
+        count = format!(",{}{count}", &digits[digits.len() - 3..]);
+        digits.truncate(digits.len() - 3);
+    }
+    count = format!("{digits}{count}");
+
+    let plain_summary = format!(
+        "… {count} more {} ({})",
+        if hidden_items.count == 1 {
+            "item"
+        } else {
+            "items"
+        },
+        i64::try_from(hidden_items.size)
+            .map_or("unknown size".to_string(), convert_bytes)
+            .trim()
+    );
+
+    if args.style.plain || args.style.no_colors {
+        plain_summary
+    } else {
+        Style::new().dimmed().paint(plain_summary).to_string()
+    }
+}
+
+/// Highlight an item that was created or modified since the previous frame in
+/// watch mode. A marker is displayed in front of the formatted item.
+pub fn format_changed(args: &GlobalArgs, formatted_item: String) -> String {
+    if args.style.plain || args.style.no_colors {
+        format!("* {formatted_item}")
+    } else {
+        format!(
+            "{} {formatted_item}",
+            Colour::Yellow.bold().paint("\u{25cf}") // ●
+        )
+    }
+}
+
 /// Format how the branch looks depending on its metadata.
 pub fn format_branch(
     item: &TransformedBranch,
@@ -268,7 +383,8 @@ pub fn format_branch(
     }
 
     if let Some(ranges) = item.matched {
-        branch_name = highlight_matched(false, nomad_style, item.full_branch.to_string(), ranges);
+        branch_name =
+            highlight_matched(false, nomad_style, item.full_branch.to_string(), &[ranges]);
     }
 
     if let Some(marker) = &item.marker {
@@ -0,0 +1,298 @@
+//! Limit the number of items displayed in each directory.
+
+use super::models::{HiddenItems, TransformedItem};
+use crate::{cli::global::GlobalArgs, style::models::NomadStyle};
+
+use std::{collections::VecDeque, ffi::OsStr, fs, path::Path};
+
+/// Contains a directory whose items are currently being visited.
+struct OpenDirectory {
+    /// The directory path broken down into its individual components.
+    components: Vec<String>,
+    /// The depth of the directory relative to the root of the tree.
+    depth: i32,
+    /// The items in the directory that were not displayed.
+    hidden_items: HiddenItems,
+    /// Indicates whether the directory itself is not displayed.
+    is_hidden: bool,
+    /// The maximum number of items that are displayed in the directory.
+    limit: Option<usize>,
+    /// The absolute path to the directory.
+    path: String,
+    /// The number of items in the directory that were displayed.
+    shown: usize,
+}
+
+/// Lazily limits the number of `TransformedItem`s displayed in each directory.
+///
+/// The items that exceed the limit, and everything within them, are skipped.
+/// A summary item containing the number of skipped items and their total size
+/// is yielded once each directory has been visited.
+pub struct LimitedItems<I> {
+    /// The names of directories whose contents are always collapsed.
+    collapsed_directories: Vec<String>,
+    /// Indicates whether only directories are displayed. Files are not counted
+    /// as items if this is enabled.
+    dirs_only: bool,
+    /// The items that have not been visited yet.
+    items: I,
+    /// The maximum number of items that are displayed in each directory.
+    max_entries: Option<usize>,
+    /// The directories containing the current item, starting with the root.
+    open_directories: Vec<OpenDirectory>,
+    /// Items waiting to be yielded.
+    pending: VecDeque<TransformedItem>,
+}
+
+impl<I> LimitedItems<I> {
+    /// Create a new `LimitedItems` for the tree rooted at `target_directory`.
+    pub fn new(
+        args: &GlobalArgs,
+        items: I,
+        nomad_style: &NomadStyle,
+        target_directory: &str,
+    ) -> Self {
+        Self {
+            collapsed_directories: nomad_style.tree.collapsed_directories.clone(),
+            dirs_only: args.modifiers.dirs,
+            items,
+            max_entries: args.modifiers.max_entries,
+            open_directories: vec![OpenDirectory {
+                components: Vec::new(),
+                depth: 0,
+                hidden_items: HiddenItems::default(),
+                is_hidden: false,
+                limit: args.modifiers.max_entries,
+                path: target_directory.to_string(),
+                shown: 0,
+            }],
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Get the maximum number of items that are displayed in a directory.
+    fn get_limit(&self, directory: &str) -> Option<usize> {
+        let name = Path::new(directory)
+            .file_name()
+            .and_then(OsStr::to_str)
+            .unwrap_or("?");
+
+        if self
+            .collapsed_directories
+            .iter()
+            .any(|collapsed| collapsed == name)
+        {
+            Some(0)
+        } else {
+            self.max_entries
+        }
+    }
+
+    /// Queue a summary item for a directory that has been visited if any of
+    /// its items were not displayed.
+    fn close_directory(&mut self, directory: OpenDirectory) {
+        if directory.is_hidden || directory.hidden_items.count == 0 {
+            return;
+        }
+
+        let mut components = directory.components;
+        components.push("…".to_string());
+
+        self.pending.push_back(TransformedItem {
+            components,
+            content_matches: None,
+            depth: directory.depth + 1,
+            display_name: None,
+            hidden_items: Some(directory.hidden_items),
+            is_dir: false,
+            is_empty: false,
+            is_file: false,
+            link_problem: None,
+            marker: None,
+            matched: Vec::new(),
+            metadata: None,
+            path: Path::new(&directory.path)
+                .join("…")
+                .to_str()
+                .unwrap_or("?")
+                .to_string(),
+        });
+    }
+
+    /// Record an item that is not displayed, and open it if it is a directory
+    /// so that everything within it is skipped as well.
+    fn hide_item(&mut self, item: TransformedItem) {
+        if item.is_file {
+            if let Some(directory) = self
+                .open_directories
+                .iter_mut()
+                .rev()
+                .find(|directory| !directory.is_hidden)
+            {
+                directory.hidden_items.size += match item.metadata {
+                    Some(ref metadata) => metadata.size.unwrap_or(0),
+                    None => fs::metadata(&item.path).map_or(0, |metadata| metadata.len()),
+                };
+            }
+        }
+
+        if item.is_dir {
+            self.open_directories.push(OpenDirectory {
+                components: item.components,
+                depth: item.depth,
+                hidden_items: HiddenItems::default(),
+                is_hidden: true,
+                limit: None,
+                path: item.path,
+                shown: 0,
+            });
+        }
+    }
+}
+
+impl<I: Iterator<Item = TransformedItem>> Iterator for LimitedItems<I> {
+    type Item = TransformedItem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.max_entries.is_none() && self.collapsed_directories.is_empty() {
+            return self.items.next();
+        }
+
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Some(item);
+            }
+
+            let item = match self.items.next() {
+                Some(item) => item,
+                None => {
+                    while let Some(directory) = self.open_directories.pop() {
+                        self.close_directory(directory);
+                    }
+
+                    return self.pending.pop_front();
+                }
+            };
+
+            while self
+                .open_directories
+                .last()
+                .is_some_and(|directory| directory.depth > 0 && directory.depth >= item.depth)
+            {
+                if let Some(directory) = self.open_directories.pop() {
+                    self.close_directory(directory);
+                }
+            }
+
+            let parent = self
+                .open_directories
+                .last_mut()
+                .expect("The root directory is never closed before all items are visited!");
+
+            if parent.is_hidden {
+                self.hide_item(item);
+                continue;
+            }
+
+            if !item.is_dir && self.dirs_only {
+                self.pending.push_back(item);
+                continue;
+            }
+
+            if parent.limit.is_some_and(|limit| parent.shown >= limit) {
+                parent.hidden_items.count += 1;
+                self.hide_item(item);
+                continue;
+            }
+
+            parent.shown += 1;
+
+            if item.is_dir {
+                self.open_directories.push(OpenDirectory {
+                    components: item.components.clone(),
+                    depth: item.depth,
+                    hidden_items: HiddenItems::default(),
+                    is_hidden: false,
+                    limit: self.get_limit(&item.path),
+                    path: item.path.clone(),
+                    shown: 0,
+                });
+            }
+
+            self.pending.push_back(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_limits {
+    use super::*;
+
+    use structopt::StructOpt;
+
+    fn get_item(path: &str, is_dir: bool) -> TransformedItem {
+        let components = path
+            .split('/')
+            .map(|component| component.to_string())
+            .collect::<Vec<String>>();
+
+        TransformedItem {
+            content_matches: None,
+            depth: components.len() as i32,
+            display_name: None,
+            hidden_items: None,
+            is_dir,
+            is_empty: false,
+            is_file: !is_dir,
+            link_problem: None,
+            marker: None,
+            matched: Vec::new(),
+            metadata: None,
+            path: format!("/root/{path}"),
+            components,
+        }
+    }
+
+    #[test]
+    fn test_limited_items() {
+        let args = GlobalArgs::from_iter(["nd", "--max-entries", "2"]);
+        let mut nomad_style = NomadStyle::default();
+        nomad_style.tree.collapsed_directories = vec!["vendor".to_string()];
+
+        let items = LimitedItems::new(
+            &args,
+            vec![
+                get_item("src", true),
+                get_item("src/a.rs", false),
+                get_item("src/b.rs", false),
+                get_item("src/c.rs", false),
+                get_item("vendor", true),
+                get_item("vendor/lib.rs", false),
+                get_item("z", true),
+                get_item("z/d.rs", false),
+            ]
+            .into_iter(),
+            &nomad_style,
+            "/root",
+        );
+
+        assert_eq!(
+            items
+                .map(|item| (
+                    item.depth,
+                    item.path,
+                    item.hidden_items.map(|hidden| hidden.count)
+                ))
+                .collect::<Vec<(i32, String, Option<usize>)>>(),
+            vec![
+                (1, "/root/src".to_string(), None),
+                (2, "/root/src/a.rs".to_string(), None),
+                (2, "/root/src/b.rs".to_string(), None),
+                (2, "/root/src/…".to_string(), Some(1)),
+                (1, "/root/vendor".to_string(), None),
+                (2, "/root/vendor/…".to_string(), Some(1)),
+                (1, "/root/…".to_string(), Some(1)),
+            ]
+        );
+    }
+}
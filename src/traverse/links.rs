@@ -0,0 +1,203 @@
+//! Detect and report symlink problems found during traversal.
+
+use super::stats::LinkStatistics;
+use crate::{cli::global::GlobalArgs, style::models::NomadStyle};
+
+use ignore::Error;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The marker displayed in front of a symlink that points to an item that does not exist.
+pub const DANGLING_MARKER: &str = "✗";
+/// The marker displayed in front of a symlink that points to one of its parent directories.
+pub const LOOP_MARKER: &str = "↻";
+
+/// Variants for problems with a symlink.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LinkProblem {
+    /// The symlink points to an item that does not exist.
+    Dangling,
+    /// The symlink points to one of its parent directories, so following it
+    /// would never end.
+    Loop,
+}
+
+impl LinkProblem {
+    /// Check whether an item is a symlink with a problem. A symlink that points
+    /// to one of its parent directories is only a loop if symlinks are followed.
+    pub fn of(item: &Path, follow_links: bool) -> Option<Self> {
+        if !item.is_symlink() {
+            return None;
+        }
+
+        match item.canonicalize() {
+            Ok(_) if !follow_links => None,
+            Ok(points_to) => item
+                .parent()
+                .and_then(|parent| parent.canonicalize().ok())
+                .filter(|parent| parent.starts_with(&points_to))
+                .map(|_| LinkProblem::Loop),
+            Err(_) => Some(LinkProblem::Dangling),
+        }
+    }
+
+    /// Get the marker displayed in front of the symlink.
+    pub fn marker(&self) -> &'static str {
+        match self {
+            LinkProblem::Dangling => DANGLING_MARKER,
+            LinkProblem::Loop => LOOP_MARKER,
+        }
+    }
+}
+
+/// Get the path of the symlink that caused a traversal error and its problem.
+/// Returns `None` if the error was not caused by a dangling symlink or a
+/// symlink loop.
+pub fn get_link_error(error: &Error) -> Option<(PathBuf, LinkProblem)> {
+    match error {
+        Error::Loop { child, .. } => Some((child.to_path_buf(), LinkProblem::Loop)),
+        Error::WithDepth { err, .. } | Error::WithLineNumber { err, .. } => get_link_error(err),
+        Error::WithPath { path, err } => get_link_error(err).or_else(|| {
+            LinkProblem::of(path, true).map(|link_problem| (path.to_path_buf(), link_problem))
+        }),
+        _ => None,
+    }
+}
+
+/// Contains the symlink problems found in the tree.
+#[derive(Debug, Default)]
+pub struct LinkReport {
+    /// Symlinks that point to items that do not exist, and the items they point to.
+    dangling: Vec<(String, String)>,
+    /// Symlinks that point to one of their parent directories, and the
+    /// directories they point to.
+    loops: Vec<(String, String)>,
+}
+
+impl LinkReport {
+    /// Record a symlink problem. The symlink is displayed as `display_path` in
+    /// the report, which should be relative to the root of the tree.
+    pub fn add(&mut self, item: &Path, display_path: &Path, problem: LinkProblem) {
+        let points_to = fs::read_link(item).map_or("?".to_string(), |points_to| {
+            points_to.to_str().unwrap_or("?").to_string()
+        });
+        let entry = (display_path.to_str().unwrap_or("?").to_string(), points_to);
+
+        match problem {
+            LinkProblem::Dangling => self.dangling.push(entry),
+            LinkProblem::Loop => self.loops.push(entry),
+        }
+    }
+
+    /// Get the symlinks that point to items that do not exist.
+    pub fn dangling(&self) -> Vec<LinkStatistics> {
+        to_link_statistics(&self.dangling)
+    }
+
+    /// Get the symlinks that point to one of their parent directories.
+    pub fn loops(&self) -> Vec<LinkStatistics> {
+        to_link_statistics(&self.loops)
+    }
+
+    /// Format the statistics line listing the symlink problems. Returns `None`
+    /// if no problems were found.
+    pub fn format(&self, args: &GlobalArgs, nomad_style: &NomadStyle) -> Option<String> {
+        if self.dangling.is_empty() && self.loops.is_empty() {
+            return None;
+        }
+
+        let pluralize = |count: usize, noun: &str| {
+            if count == 1 {
+                format!("{count} {noun}")
+            } else {
+                format!("{count} {noun}s")
+            }
+        };
+
+        let mut report = format!(
+            "| {} | {} |",
+            pluralize(self.dangling.len(), "dangling symlink"),
+            pluralize(self.loops.len(), "symlink loop")
+        );
+
+        for (problem, items) in [
+            (LinkProblem::Dangling, &self.dangling),
+            (LinkProblem::Loop, &self.loops),
+        ] {
+            for (item, points_to) in items {
+                let line = format!("{} {item} ⇒ {points_to}", problem.marker());
+
+                report.push_str(&format!(
+                    "\n  {}",
+                    if args.style.plain || args.style.no_colors {
+                        line
+                    } else {
+                        nomad_style
+                            .tree
+                            .item_colors
+                            .broken_symlink_color
+                            .paint(line)
+                            .to_string()
+                    }
+                ));
+            }
+        }
+
+        Some(report)
+    }
+}
+
+/// Convert recorded symlink problems into `LinkStatistics`.
+fn to_link_statistics(items: &[(String, String)]) -> Vec<LinkStatistics> {
+    items
+        .iter()
+        .map(|(symlink, points_to)| LinkStatistics {
+            points_to: points_to.to_string(),
+            symlink: symlink.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_links {
+    use super::*;
+
+    use crate::utils::test_utils::create_fixture;
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn test_link_problems() {
+        use std::os::unix::fs::symlink;
+
+        let fixture = create_fixture(&[("nested/", ""), ("file.txt", "")]);
+        let target_directory = fixture.path();
+
+        symlink("file.txt", target_directory.join("working")).unwrap();
+        symlink("missing.txt", target_directory.join("dangling")).unwrap();
+        symlink("..", target_directory.join("nested/loop")).unwrap();
+
+        assert_eq!(
+            LinkProblem::of(&target_directory.join("file.txt"), true),
+            None
+        );
+        assert_eq!(
+            LinkProblem::of(&target_directory.join("working"), true),
+            None
+        );
+        assert_eq!(
+            LinkProblem::of(&target_directory.join("dangling"), true),
+            Some(LinkProblem::Dangling)
+        );
+        assert_eq!(
+            LinkProblem::of(&target_directory.join("nested/loop"), true),
+            Some(LinkProblem::Loop)
+        );
+        assert_eq!(
+            LinkProblem::of(&target_directory.join("nested/loop"), false),
+            None
+        );
+    }
+}
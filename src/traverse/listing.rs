@@ -0,0 +1,342 @@
+//! Traverse a directory on a single thread by listing each directory before its
+//! items are visited.
+
+use super::{
+    filter::{read_entry, ItemFilter},
+    links::LinkProblem,
+    models::TransformedItem,
+    sizes::DirectorySizes,
+    sort::ItemSorter,
+    utils::{configure_walk, configure_walk_at, is_empty_directory},
+};
+use crate::{cli::global::GlobalArgs, errors::NomadError};
+
+use ignore::{types::Types, DirEntry};
+
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+    vec,
+};
+
+/// A directory and the name of the last item displayed in it.
+type LastItem = (PathBuf, OsString);
+
+/// Traverses a directory on a single thread.
+///
+/// Whether a directory is displayed depends on the items within it, which are
+/// visited after the items before it. The entries of each directory are read and
+/// sorted when the traversal enters the directory, then checked from the last
+/// entry until one of them is displayed. The last item displayed in the directory
+/// is sent to the `LastItems` so that each item is written as soon as it is
+/// visited. The entries after it are skipped since nothing within them is
+/// displayed.
+pub struct ListedWalk {
+    /// The client's CLI parameters.
+    args: GlobalArgs,
+    /// Decides which entries are displayed.
+    filter: ItemFilter,
+    /// Sends the last item displayed in each directory that contains one.
+    last_items: Sender<LastItem>,
+    /// The entries that have not been visited yet in each directory containing
+    /// the current entry, starting with the root of the tree itself.
+    open_directories: Vec<vec::IntoIter<Result<DirEntry, ignore::Error>>>,
+    /// Sorts the entries of each directory.
+    sorter: Arc<ItemSorter>,
+    /// The root of the tree.
+    target_directory: String,
+    /// The filetypes that are displayed or skipped, if applicable.
+    types: Option<Types>,
+}
+
+impl ListedWalk {
+    /// Create a new `ListedWalk` for the tree rooted at `target_directory` and the
+    /// `LastItems` that receives the last item displayed in each directory.
+    pub fn new(
+        args: &GlobalArgs,
+        sorter: ItemSorter,
+        target_directory: &str,
+        types: Option<Types>,
+    ) -> Result<(Self, LastItems), NomadError> {
+        let mut root = configure_walk(args, target_directory, types.clone())?;
+        root.max_depth(Some(0));
+
+        let (sender, receiver) = channel();
+
+        Ok((
+            Self {
+                args: args.clone(),
+                filter: ItemFilter::new(args, None, target_directory)?,
+                last_items: sender,
+                open_directories: vec![root.build().collect::<Vec<_>>().into_iter()],
+                sorter: Arc::new(sorter),
+                target_directory: target_directory.to_string(),
+                types,
+            },
+            LastItems {
+                dirs_only: args.modifiers.dirs,
+                open_directories: Vec::new(),
+                receiver,
+            },
+        ))
+    }
+
+    /// Skip the items that are not displayed with the directory sizes, like the
+    /// items found in the traversal.
+    pub fn with_directory_sizes(mut self, directory_sizes: Option<Arc<DirectorySizes>>) -> Self {
+        self.filter.directory_sizes = directory_sizes;
+        self
+    }
+
+    /// Read the entries of a directory that is entered and find the last item
+    /// displayed in it. Files are not counted if only directories are displayed,
+    /// but they are still visited.
+    fn open(&mut self, directory: &Path) {
+        let follow_links = !self.args.modifiers.no_follow;
+
+        let mut entries = self.list(directory, true);
+        let mut num_visited = 0;
+        let mut last_item = None;
+
+        for (index, dir_entry) in entries.iter().enumerate().rev() {
+            if let Some((is_dir, link_problem, path)) = read_entry(dir_entry, follow_links) {
+                let is_written = is_dir || !self.args.modifiers.dirs;
+
+                if (num_visited == 0 || is_written)
+                    && self.is_displayed(is_dir, link_problem, &path)
+                {
+                    if num_visited == 0 {
+                        num_visited = index + 1;
+                    }
+                    if is_written {
+                        last_item = path.file_name().map(OsStr::to_os_string);
+                        break;
+                    }
+                }
+            }
+        }
+
+        // The tree is not written if nothing receives the last items, so it does
+        // not matter if they are not received.
+        if let Some(last_item) = last_item {
+            self.last_items
+                .send((directory.to_path_buf(), last_item))
+                .ok();
+        }
+
+        entries.truncate(num_visited);
+        self.open_directories.push(entries.into_iter());
+    }
+
+    /// Check whether an entry is displayed in the tree, either on its own or
+    /// because an item within it is displayed.
+    fn is_displayed(&self, is_dir: bool, link_problem: Option<LinkProblem>, path: &Path) -> bool {
+        let is_empty = is_dir && is_empty_directory(path);
+
+        if self
+            .filter
+            .find(is_dir, is_empty, link_problem, path.to_path_buf())
+            .is_some()
+        {
+            return true;
+        }
+        if !is_dir || is_empty || self.filter.is_truncated(path) {
+            return false;
+        }
+
+        let follow_links = !self.args.modifiers.no_follow;
+
+        self.list(path, false).iter().any(|dir_entry| {
+            read_entry(dir_entry, follow_links).is_some_and(|(is_dir, link_problem, path)| {
+                self.is_displayed(is_dir, link_problem, &path)
+            })
+        })
+    }
+
+    /// Read the entries of a directory that are not ignored, sorting them if they
+    /// are visited.
+    fn list(&self, directory: &Path, sorted: bool) -> Vec<Result<DirEntry, ignore::Error>> {
+        // The same configuration was already built for the root of the tree, so
+        // it only fails if an ignore file is removed during the traversal.
+        let mut walk = match configure_walk_at(
+            &self.args,
+            directory,
+            &self.target_directory,
+            self.types.clone(),
+        ) {
+            Ok(walk) => walk,
+            Err(_) => return Vec::new(),
+        };
+        walk.max_depth(Some(1));
+
+        if sorted {
+            let sorter = Arc::clone(&self.sorter);
+            walk.sort_by_file_path(move |a, b| sorter.compare_siblings(a, b));
+        }
+
+        walk.build().skip(1).collect()
+    }
+}
+
+impl Iterator for ListedWalk {
+    type Item = Result<DirEntry, ignore::Error>;
+
+    /// Visit the next entry. The entries of a directory are read right after the
+    /// directory itself is visited, unless it is at the depth limit.
+    fn next(&mut self) -> Option<Self::Item> {
+        let dir_entry = loop {
+            match self.open_directories.last_mut()?.next() {
+                Some(dir_entry) => break dir_entry,
+                None => {
+                    self.open_directories.pop();
+                }
+            }
+        };
+
+        let depth = self.open_directories.len() - 1;
+        if self
+            .args
+            .modifiers
+            .max_depth
+            .is_none_or(|max_depth| depth < max_depth)
+        {
+            if let Some((true, _, path)) = read_entry(&dir_entry, !self.args.modifiers.no_follow) {
+                self.open(&path);
+            }
+        }
+
+        Some(dir_entry)
+    }
+}
+
+/// Receives the last item displayed in each directory from a `ListedWalk`.
+pub struct LastItems {
+    /// Only directories are written, so files are never the last item.
+    dirs_only: bool,
+    /// The last item displayed in each directory containing the current item,
+    /// starting with the root of the tree.
+    open_directories: Vec<LastItem>,
+    /// Receives the last items in the order their directories are entered.
+    receiver: Receiver<LastItem>,
+}
+
+impl LastItems {
+    /// Check whether an item is the last item displayed in its directory. Items
+    /// have to be checked in traversal order. Summaries of the items that are not
+    /// displayed are always the last item in their directory.
+    pub fn is_last(&mut self, item: &TransformedItem) -> bool {
+        if item.hidden_items.is_some() {
+            return true;
+        }
+        if self.dirs_only && !item.is_dir {
+            return false;
+        }
+
+        let path = Path::new(&item.path);
+        let directory = match path.parent() {
+            Some(directory) => directory,
+            None => return true,
+        };
+
+        while self
+            .open_directories
+            .last()
+            .is_some_and(|(open_directory, _)| !directory.starts_with(open_directory))
+        {
+            self.open_directories.pop();
+        }
+
+        // Directories whose items are all hidden by a limit are skipped.
+        if self
+            .open_directories
+            .last()
+            .is_none_or(|(open_directory, _)| open_directory != directory)
+        {
+            while let Ok((listed_directory, last_item)) = self.receiver.try_recv() {
+                if listed_directory == directory {
+                    self.open_directories.push((listed_directory, last_item));
+                    break;
+                }
+            }
+        }
+
+        self.open_directories
+            .last()
+            .is_some_and(|(open_directory, last_item)| {
+                open_directory == directory && Some(last_item.as_os_str()) == path.file_name()
+            })
+    }
+}
+
+/// Contains variants for finding whether each item is the last item in its directory.
+pub enum ItemPositions {
+    /// The items were collected, so the position of each item was found by
+    /// looking ahead. Contains whether each remaining item is the last item in
+    /// its directory.
+    Collected(vec::IntoIter<bool>),
+    /// The items are written while the directory is traversed, so the last item
+    /// in each directory is received from the traversal.
+    Listed(LastItems),
+}
+
+impl ItemPositions {
+    /// Check whether the next item is the last item in its directory. This has to
+    /// be called for every item, in order.
+    pub fn is_last(&mut self, item: &TransformedItem) -> bool {
+        match self {
+            ItemPositions::Collected(last_items) => last_items.next().unwrap_or(true),
+            ItemPositions::Listed(last_items) => last_items.is_last(item),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_listing {
+    use super::*;
+
+    use crate::utils::test_utils::create_fixture;
+
+    use structopt::StructOpt;
+
+    #[test]
+    fn test_skip_entries_after_last_item() {
+        let fixture = create_fixture(&[
+            ("a/match.rs", ""),
+            ("a/other.txt", ""),
+            ("b/other.txt", ""),
+            ("c.rs", ""),
+            ("d/other.txt", ""),
+        ]);
+        let target_directory = fixture.path();
+
+        let args = GlobalArgs::from_iter(["nd", "-p", "rs"]);
+        let root = target_directory.to_str().unwrap();
+
+        let (walker, last_items) =
+            ListedWalk::new(&args, ItemSorter::new(&args.sort, None, root), root, None).unwrap();
+
+        let visited = walker
+            .filter_map(|dir_entry| dir_entry.ok())
+            .map(|entry| entry.path().strip_prefix(root).unwrap().to_path_buf())
+            .collect::<Vec<PathBuf>>();
+        assert_eq!(
+            visited,
+            ["", "a", "a/match.rs", "b", "c.rs"]
+                .iter()
+                .map(PathBuf::from)
+                .collect::<Vec<PathBuf>>()
+        );
+
+        assert_eq!(
+            last_items.receiver.try_iter().collect::<Vec<LastItem>>(),
+            vec![
+                (target_directory.to_path_buf(), OsString::from("c.rs")),
+                (target_directory.join("a"), OsString::from("match.rs")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,281 @@
+//! Match items against the patterns provided by the client.
+
+use super::models::{ContentMatches, MatchedLine};
+use crate::{cli::global::RegexArgs, errors::NomadError};
+
+use anyhow::Result;
+use regex::{Regex, RegexBuilder};
+
+use std::{fs, path::Path};
+
+/// The number of bytes that are checked for a null byte to detect binary files.
+const BINARY_CHECK_LENGTH: usize = 8000;
+
+/// Matches items against the patterns and exclude patterns provided by the client.
+///
+/// Each match is returned as a list of `(start, end)` spans within the item's
+/// path relative to the target directory so that every match may be highlighted.
+#[derive(Clone, Debug)]
+pub struct ItemMatcher {
+    /// Files have to contain a match for this pattern to be displayed.
+    contents: Option<Regex>,
+    /// Items matching any of these patterns are not displayed.
+    exclude: Vec<Regex>,
+    /// Only match items that match every pattern instead of any pattern.
+    match_all: bool,
+    /// Only match the item's name instead of its relative path.
+    name_only: bool,
+    /// Items have to match these patterns to be displayed.
+    patterns: Vec<Regex>,
+}
+
+impl ItemMatcher {
+    /// Compile the patterns and exclude patterns.
+    pub fn new(args: &RegexArgs) -> Result<Self, NomadError> {
+        let build = |pattern: &String| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(args.ignore_case)
+                .build()
+                .map_err(NomadError::RegexError)
+        };
+        let compile = |patterns: &Vec<String>| {
+            patterns
+                .iter()
+                .map(build)
+                .collect::<Result<Vec<Regex>, NomadError>>()
+        };
+
+        Ok(Self {
+            contents: args.contains.as_ref().map(build).transpose()?,
+            exclude: compile(&args.exclude)?,
+            match_all: args.match_all,
+            name_only: args.name_only,
+            patterns: compile(&args.patterns)?,
+        })
+    }
+
+    /// Check whether any patterns were provided.
+    pub fn has_patterns(&self) -> bool {
+        !self.patterns.is_empty()
+    }
+
+    /// Check whether a pattern was provided to search file contents with.
+    pub fn has_content_pattern(&self) -> bool {
+        self.contents.is_some()
+    }
+
+    /// Check whether any exclude patterns were provided.
+    pub fn has_exclusions(&self) -> bool {
+        !self.exclude.is_empty()
+    }
+
+    /// Check whether an item or any of the directories it is in match an exclude
+    /// pattern.
+    pub fn is_excluded(&self, relative_path: &Path) -> bool {
+        if self.exclude.is_empty() {
+            return false;
+        }
+
+        relative_path
+            .ancestors()
+            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+            .any(|ancestor| {
+                let target = if self.name_only {
+                    ancestor.file_name().and_then(|name| name.to_str())
+                } else {
+                    ancestor.to_str()
+                };
+
+                target.is_some_and(|target| self.exclude.iter().any(|regex| regex.is_match(target)))
+            })
+    }
+
+    /// Find the pattern matches for an item. Matches the item's relative path, or
+    /// only its name if `--name-only` was used. Returns `None` if the item does
+    /// not match.
+    pub fn find(&self, relative_path: &Path) -> Option<Vec<(usize, usize)>> {
+        if self.name_only {
+            self.find_in_name(relative_path)
+        } else {
+            self.find_in(relative_path.to_str()?, 0)
+        }
+    }
+
+    /// Find the pattern matches in an item's name. The spans are relative to the
+    /// start of the item's path so that they may be highlighted like any other match.
+    pub fn find_in_name(&self, relative_path: &Path) -> Option<Vec<(usize, usize)>> {
+        let path = relative_path.to_str()?;
+        let name = relative_path.file_name()?.to_str()?;
+
+        self.find_in(name, path.len() - name.len())
+    }
+
+    /// Check whether the patterns match the name of any directory the item is in.
+    pub fn in_matched_directory(&self, relative_path: &Path) -> bool {
+        relative_path.parent().is_some_and(|parent| {
+            parent.components().any(|component| {
+                component
+                    .as_os_str()
+                    .to_str()
+                    .is_some_and(|name| self.find_in(name, 0).is_some())
+            })
+        })
+    }
+
+    /// Search a file's contents for the `--contains` pattern. Returns `None` if
+    /// the file could not be read, is a binary file, or does not contain a match.
+    pub fn search_contents(&self, item: &Path) -> Option<ContentMatches> {
+        let regex = self.contents.as_ref()?;
+
+        let bytes = fs::read(item).ok()?;
+        if bytes[..bytes.len().min(BINARY_CHECK_LENGTH)].contains(&0) {
+            return None;
+        }
+
+        let lines = String::from_utf8_lossy(&bytes)
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| regex.is_match(line))
+            .map(|(index, line)| {
+                let text = line.trim();
+                let offset = line.len() - line.trim_start().len();
+
+                let matched = regex
+                    .find_iter(line)
+                    .filter(|matched| !matched.as_str().is_empty())
+                    .map(|matched| {
+                        (
+                            matched.start().saturating_sub(offset).min(text.len()),
+                            matched.end().saturating_sub(offset).min(text.len()),
+                        )
+                    })
+                    .collect::<Vec<(usize, usize)>>();
+
+                MatchedLine {
+                    matched,
+                    number: index + 1,
+                    text: text.to_string(),
+                }
+            })
+            .collect::<Vec<MatchedLine>>();
+
+        if lines.is_empty() {
+            None
+        } else {
+            Some(ContentMatches { lines })
+        }
+    }
+
+    /// Find every match of every pattern in the text. Each span is moved forward
+    /// by the offset. Overlapping spans are merged.
+    fn find_in(&self, text: &str, offset: usize) -> Option<Vec<(usize, usize)>> {
+        let mut spans = Vec::new();
+        let mut matched_patterns = 0;
+
+        for regex in self.patterns.iter() {
+            let mut is_match = false;
+
+            for matched in regex.find_iter(text) {
+                is_match = true;
+
+                if !matched.as_str().is_empty() {
+                    spans.push((matched.start() + offset, matched.end() + offset));
+                }
+            }
+
+            if is_match {
+                matched_patterns += 1;
+            } else if self.match_all {
+                return None;
+            }
+        }
+
+        if !self.patterns.is_empty() && matched_patterns == 0 {
+            return None;
+        }
+
+        spans.sort_unstable();
+
+        let mut merged: Vec<(usize, usize)> = Vec::new();
+        for (start, end) in spans {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+
+        Some(merged)
+    }
+}
+
+#[cfg(test)]
+mod test_matcher {
+    use super::*;
+
+    use crate::utils::test_utils::create_fixture;
+
+    fn get_matcher(patterns: &[&str], exclude: &[&str], match_all: bool) -> ItemMatcher {
+        ItemMatcher::new(&RegexArgs {
+            contains: None,
+            exclude: exclude.iter().map(|pattern| pattern.to_string()).collect(),
+            ignore_case: true,
+            match_all,
+            match_dirs: false,
+            name_only: false,
+            patterns: patterns.iter().map(|pattern| pattern.to_string()).collect(),
+            show_lines: false,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_every_match_is_found() {
+        let matcher = get_matcher(&["a", "s"], &[], false);
+
+        assert_eq!(
+            matcher.find(Path::new("src/main.rs")),
+            Some(vec![(0, 1), (5, 6), (10, 11)])
+        );
+    }
+
+    #[test]
+    fn test_match_all_patterns() {
+        let matcher = get_matcher(&["MAIN", "lib"], &[], true);
+
+        assert_eq!(matcher.find(Path::new("src/main.rs")), None);
+        assert_eq!(
+            matcher.find(Path::new("lib/main.rs")),
+            Some(vec![(0, 3), (4, 8)])
+        );
+    }
+
+    #[test]
+    fn test_exclude_parent_directory() {
+        let matcher = get_matcher(&[], &["^target$"], false);
+
+        assert!(matcher.is_excluded(Path::new("target/debug/nd")));
+        assert!(!matcher.is_excluded(Path::new("src/target.rs")));
+    }
+
+    #[test]
+    fn test_search_contents() {
+        let fixture = create_fixture(&[("main.rs", "fn main() {\n    let todo = 1; // TODO\n}\n")]);
+        let item = fixture.path().join("main.rs");
+
+        let mut matcher = get_matcher(&[], &[], false);
+        matcher.contents = Some(Regex::new("(?i)todo").unwrap());
+
+        let content_matches = matcher.search_contents(&item).unwrap();
+        assert_eq!(
+            content_matches.lines,
+            vec![MatchedLine {
+                matched: vec![(4, 8), (17, 21)],
+                number: 2,
+                text: "let todo = 1; // TODO".to_string(),
+            }]
+        );
+
+        matcher.contents = Some(Regex::new("missing").unwrap());
+        assert!(matcher.search_contents(&item).is_none());
+    }
+}
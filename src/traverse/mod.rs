@@ -1,44 +1,216 @@
 //! Traverse the target directory.
 
+pub mod dupes;
+pub mod filter;
 pub mod format;
+pub mod limits;
+pub mod links;
+pub mod listing;
+pub mod matcher;
 pub mod models;
 pub mod modes;
+pub mod permissions;
+pub mod sizes;
+pub mod sort;
+pub mod state;
+pub mod stats;
+pub mod times;
 pub mod traits;
 pub mod utils;
+pub mod virtual_items;
+pub mod watch;
+pub mod writer;
 
 use self::{
+    filter::{read_entry, ItemFilter},
     models::{DirItem, FoundItem},
     modes::NomadMode,
-    traits::{ToTree, TransformFound},
+    state::TreeState,
+    traits::{ToTree, TransformFound, WriteItems},
+    utils::{build_tree, build_walker, export_root, format_root, is_empty_directory, Walker},
+    writer::TreeOutput,
 };
 use crate::{
-    cli::global::GlobalArgs, errors::NomadError, git::markers::extend_marker_map,
-    style::models::NomadStyle, utils::paths::canonicalize_path,
+    cli::global::GlobalArgs,
+    errors::NomadError,
+    git::markers::extend_marker_map,
+    style::models::NomadStyle,
+    utils::{export::TreeExport, paths::canonicalize_path},
 };
 
-use anyhow::{Result, __private};
-use ignore::{self, Walk};
-use ptree::{item::StringItem, PrintConfig};
-use regex::Regex;
+use anyhow::Result;
 
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    iter,
+    path::{Path, PathBuf},
+    time::Instant,
+};
 
 /// Traverse the directory and display files and directories accordingly.
+///
+/// Items are written to the tree while the directory is traversed. Returns the
+/// lines of the tree and all directory items if `nomad` is in Rootless mode.
 pub fn walk_directory(
     args: &GlobalArgs,
+    export: Option<TreeExport>,
     nomad_mode: NomadMode,
     nomad_style: &NomadStyle,
     target_directory: &str,
-    walker: &mut Walk,
-) -> Result<(StringItem, PrintConfig, Option<Vec<DirItem>>), NomadError> {
-    let regex_expression = if let Some(ref pattern) = args.regex.pattern {
-        match Regex::new(&pattern.clone()) {
-            Ok(regex) => Some(regex),
-            Err(error) => return __private::Err(NomadError::RegexError(error)),
+    mut walker: Walker,
+) -> Result<(Option<Vec<String>>, Option<Vec<DirItem>>), NomadError> {
+    let directory_sizes = walker.directory_sizes.clone();
+    let last_items = walker.last_items.take();
+
+    find_items(args, nomad_style, target_directory, walker)?
+        .transform(target_directory)?
+        .to_tree(
+            args,
+            directory_sizes.as_deref(),
+            export,
+            last_items,
+            nomad_mode,
+            nomad_style,
+            target_directory,
+        )
+}
+
+/// Traverse each directory and display their trees under a shared root.
+///
+/// The shared root is the deepest directory containing every target directory.
+/// Labels are shared by all of the trees so that items in any tree may be
+/// used with other commands.
+pub fn walk_directories(
+    args: &GlobalArgs,
+    export: Option<TreeExport>,
+    nomad_style: &NomadStyle,
+    target_directories: &[String],
+) -> Result<(), NomadError> {
+    let forest_root = get_common_root(target_directories);
+    let mut state = TreeState::for_forest(&forest_root);
+
+    println!();
+    let mut tree = build_tree(
+        args,
+        &NomadMode::Normal,
+        nomad_style,
+        TreeOutput::Print(export),
+        None,
+        &forest_root,
+    )?;
+
+    let start = Instant::now();
+
+    // Files may be copies of files in other trees, so duplicates are found
+    // across every tree before the trees are written.
+    if args.meta.dupes {
+        let mut items = Vec::new();
+        for target_directory in target_directories {
+            let walker = build_walker(args, target_directory, None)?;
+            items.extend(
+                find_items(args, nomad_style, target_directory, walker)?
+                    .filter(|item| !item.is_dir)
+                    .map(|item| item.path),
+            );
+        }
+
+        state.find_duplicates(items.iter().map(|item| item.as_str()));
+    }
+
+    for (index, target_directory) in target_directories.iter().enumerate() {
+        let mut walker = build_walker(args, target_directory, None)?;
+        let directory_sizes = walker.directory_sizes.clone();
+        let last_items = walker.last_items.take();
+
+        let name = Path::new(target_directory)
+            .strip_prefix(&forest_root)
+            .ok()
+            .and_then(|relative_path| relative_path.to_str())
+            .filter(|relative_path| !relative_path.is_empty())
+            .unwrap_or(target_directory);
+        let node = tree
+            .is_documented()
+            .then(|| export_root(args, name, name, Path::new(target_directory)));
+
+        tree.begin_node(
+            format_root(
+                args,
+                &NomadMode::Normal,
+                name,
+                directory_sizes
+                    .as_ref()
+                    .and_then(|sizes| sizes.get(Path::new(target_directory))),
+                Path::new(target_directory),
+            ),
+            node,
+            index == target_directories.len() - 1,
+        )?;
+
+        find_items(args, nomad_style, target_directory, walker)?
+            .transform(target_directory)?
+            .write_items(
+                args,
+                directory_sizes.as_deref(),
+                last_items,
+                &NomadMode::Normal,
+                nomad_style,
+                &mut state,
+                target_directory,
+                &mut tree,
+            )?;
+
+        if args.statistics {
+            state.count_skipped(args, target_directory)?;
+        }
+
+        tree.end_children(0)?;
+    }
+
+    state.store_labels()?;
+
+    let (_, export) = tree.finish()?;
+
+    println!();
+
+    if args.meta.dupes {
+        state.display_duplicates(args, nomad_style, &forest_root);
+    }
+    if args.statistics {
+        state.display_statistics(args, start.elapsed().as_millis(), nomad_style, &forest_root);
+    }
+
+    if let Some(export) = export {
+        export.display_success();
+    }
+
+    Ok(())
+}
+
+/// Get the deepest directory that contains every target directory.
+fn get_common_root(target_directories: &[String]) -> PathBuf {
+    let mut common_root = target_directories
+        .first()
+        .map_or_else(PathBuf::new, PathBuf::from);
+
+    for target_directory in target_directories.iter().skip(1) {
+        while !Path::new(target_directory).starts_with(&common_root) {
+            if !common_root.pop() {
+                break;
+            }
         }
-    } else {
-        None
-    };
+    }
+
+    common_root
+}
+
+/// Traverse the directory and find the items that are displayed in its tree.
+pub fn find_items<'a>(
+    args: &'a GlobalArgs,
+    nomad_style: &'a NomadStyle,
+    target_directory: &'a str,
+    walker: Walker,
+) -> Result<impl Iterator<Item = FoundItem> + 'a, NomadError> {
+    let filter = ItemFilter::new(args, walker.directory_sizes.clone(), target_directory)?;
 
     let mut git_markers: HashMap<String, String> = HashMap::new();
     extend_marker_map(
@@ -48,58 +220,206 @@ pub fn walk_directory(
         Path::new(target_directory).to_str().unwrap_or("?"),
     );
 
-    let (tree, config, directory_items) = walker
-        .filter_map(|dir_entry| {
-            if let Ok(entry) = dir_entry {
-                if entry.path().is_dir() {
-                    extend_marker_map(
-                        &args.style,
-                        &mut git_markers,
-                        nomad_style,
-                        entry.path().to_str().unwrap_or("?"),
-                    );
-                    None
-                } else if let Some(ref regex) = regex_expression {
-                    if let Some(matched) = regex.find(
-                        entry
-                            .path()
-                            .strip_prefix(target_directory)
-                            .unwrap_or_else(|_| Path::new("?"))
-                            .to_str()
-                            .unwrap_or("?"),
-                    ) {
-                        Some(FoundItem {
-                            marker: git_markers
-                                .get(
-                                    &canonicalize_path(entry.path().to_str().unwrap_or("?"))
-                                        .unwrap_or_else(|_| "?".to_string()),
-                                )
-                                .map(|marker| marker.to_string()),
-                            matched: Some((matched.start(), matched.end())),
-                            path: entry.path().to_str().unwrap_or("?").to_string(),
-                        })
-                    } else {
-                        None
-                    }
-                } else {
-                    Some(FoundItem {
-                        marker: git_markers
-                            .get(
-                                &canonicalize_path(entry.path().to_str().unwrap_or("?"))
-                                    .unwrap_or_else(|_| "?".to_string()),
-                            )
-                            .map(|marker| marker.to_string()),
-                        matched: None,
-                        path: entry.path().to_str().unwrap_or("?").to_string(),
-                    })
-                }
-            } else {
-                None
+    let follow_links = !args.modifiers.no_follow;
+    let mut entries = walker
+        .into_iter()
+        .filter_map(move |dir_entry| read_entry(&dir_entry, follow_links))
+        .peekable();
+
+    Ok(iter::from_fn(move || {
+        let (is_dir, link_problem, path) = entries.next()?;
+
+        // The next entry is checked first to avoid reading every directory.
+        let is_empty = is_dir
+            && entries
+                .peek()
+                .is_none_or(|(_, _, next)| !next.starts_with(&path))
+            && is_empty_directory(&path);
+
+        Some((is_dir, is_empty, link_problem, path))
+    })
+    .filter_map(move |(is_dir, is_empty, link_problem, path)| {
+        if is_dir {
+            extend_marker_map(
+                &args.style,
+                &mut git_markers,
+                nomad_style,
+                path.to_str().unwrap_or("?"),
+            );
+
+            if path == Path::new(target_directory) {
+                return None;
             }
+        }
+
+        let mut found_item = filter.find(is_dir, is_empty, link_problem, path)?;
+        found_item.marker = git_markers
+            .get(&canonicalize_path(&found_item.path).unwrap_or_else(|_| "?".to_string()))
+            .map(|marker| marker.to_string());
+
+        Some(found_item)
+    }))
+}
+
+#[cfg(test)]
+mod test_traverse {
+    use super::*;
+
+    use crate::{traverse::links::LinkProblem, utils::test_utils::create_fixture};
+
+    use structopt::StructOpt;
+
+    /// Get the relative paths of the items found in the directory, and whether
+    /// each item is a directory.
+    fn found_paths(args: &[&str], target_directory: &Path) -> Vec<(String, bool)> {
+        let args = GlobalArgs::from_iter(args);
+        let nomad_style = NomadStyle::default();
+        let root = target_directory.to_str().unwrap();
+
+        find_items(
+            &args,
+            &nomad_style,
+            root,
+            build_walker(&args, root, None).unwrap(),
+        )
+        .unwrap()
+        .map(|item| {
+            (
+                Path::new(&item.path)
+                    .strip_prefix(target_directory)
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
+                    .to_string(),
+                item.is_dir,
+            )
         })
-        .collect::<Vec<FoundItem>>()
-        .transform(target_directory)?
-        .to_tree(args, nomad_mode, nomad_style, target_directory)?;
+        .collect()
+    }
+
+    #[test]
+    fn test_directories_at_depth_limit() {
+        let fixture = create_fixture(&[
+            ("empty/", ""),
+            ("src/nested/main.rs", ""),
+            ("README.md", ""),
+        ]);
+        let target_directory = fixture.path();
+
+        assert_eq!(
+            found_paths(&["nd", "--max-depth", "1"], target_directory),
+            vec![
+                ("README.md".to_string(), false),
+                ("empty".to_string(), true),
+                ("src".to_string(), true),
+            ]
+        );
+        assert_eq!(
+            found_paths(&["nd", "--max-depth", "2"], target_directory),
+            vec![
+                ("README.md".to_string(), false),
+                ("empty".to_string(), true),
+                ("src/nested".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_common_root() {
+        assert_eq!(
+            get_common_root(&[
+                "/home/nomad/projects/a/src".to_string(),
+                "/home/nomad/projects/b".to_string(),
+                "/home/nomad/projects/a/manual".to_string(),
+            ]),
+            PathBuf::from("/home/nomad/projects")
+        );
+        assert_eq!(
+            get_common_root(&["/home/nomad".to_string(), "/tmp".to_string()]),
+            PathBuf::from("/")
+        );
+    }
+
+    #[test]
+    fn test_directory_sizes_at_depth_limit() {
+        let fixture = create_fixture(&[
+            ("big/nested/a.txt", &"a".repeat(300)),
+            ("small/b.txt", &"a".repeat(10)),
+            ("c.txt", &"a".repeat(100)),
+        ]);
+        let target_directory = fixture.path();
 
-    Ok((tree, config, directory_items))
+        let root = target_directory.to_str().unwrap();
+        let args = GlobalArgs::from_iter(["nd", "--du", "--apparent-size", "--max-depth", "1"]);
+        let directory_sizes = build_walker(&args, root, None)
+            .unwrap()
+            .directory_sizes
+            .unwrap();
+        assert_eq!(
+            directory_sizes.get(&target_directory.join("big")),
+            Some(300)
+        );
+        assert_eq!(
+            directory_sizes.get(&target_directory.join("small")),
+            Some(10)
+        );
+
+        assert_eq!(
+            found_paths(
+                &["nd", "--du", "--apparent-size", "--max-depth", "1"],
+                target_directory
+            ),
+            vec![
+                ("big".to_string(), true),
+                ("c.txt".to_string(), false),
+                ("small".to_string(), true),
+            ]
+        );
+        assert_eq!(
+            found_paths(
+                &["nd", "--top", "1", "--apparent-size", "--max-depth", "1"],
+                target_directory
+            ),
+            vec![("big".to_string(), true)]
+        );
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn test_link_problems_are_marked() {
+        use std::os::unix::fs::symlink;
+
+        let fixture = create_fixture(&[("nested/", ""), ("file.txt", &"a".repeat(2000))]);
+        let target_directory = fixture.path();
+        symlink("missing.txt", target_directory.join("dangling")).unwrap();
+        symlink("..", target_directory.join("nested/loop")).unwrap();
+
+        let args = GlobalArgs::from_iter(["nd"]);
+        let nomad_style = NomadStyle::default();
+        let root = target_directory.to_str().unwrap();
+        let link_problems = find_items(
+            &args,
+            &nomad_style,
+            root,
+            build_walker(&args, root, None).unwrap(),
+        )
+        .unwrap()
+        .map(|item| (item.path, item.link_problem))
+        .collect::<Vec<(String, Option<LinkProblem>)>>();
+
+        assert_eq!(
+            link_problems,
+            vec![
+                (format!("{root}/dangling"), Some(LinkProblem::Dangling)),
+                (format!("{root}/file.txt"), None),
+                (format!("{root}/nested/loop"), Some(LinkProblem::Loop)),
+            ]
+        );
+
+        for filter in [["nd", "--min-filesize", "1K"], ["nd", "--perm", "-x"]] {
+            assert!(found_paths(&filter, target_directory)
+                .iter()
+                .all(|(path, _)| path == "file.txt"));
+        }
+    }
 }
@@ -1,14 +1,30 @@
 //! Structs used during directory traversal.
 
+use super::links::LinkProblem;
+
 /// Contains the path of the found item and its corresponding Git marker if applicable.
 ///
 /// This struct is used to convert `DirEntry`s returned by the `Walk` object.
+/// Directories are usually built from the paths of the files within them, so a
+/// `FoundItem` is only a directory if it is empty, it is at the depth limit, or
+/// its name matched the pattern.
 #[derive(Debug)]
 pub struct FoundItem {
+    /// The lines matching the `--contains` pattern if content search is enabled.
+    pub content_matches: Option<ContentMatches>,
+    /// Indicates whether this is a directory.
+    pub is_dir: bool,
+    /// Indicates whether this is an empty directory.
+    pub is_empty: bool,
+    /// The problem with the item if it is a dangling symlink or a symlink loop.
+    pub link_problem: Option<LinkProblem>,
     /// The Git status marker indicating the change that was made to the file.
     pub marker: Option<String>,
-    /// The start and end of the pattern match in the path.
-    pub matched: Option<(usize, usize)>,
+    /// The start and end of each pattern match in the path.
+    pub matched: Vec<(usize, usize)>,
+    /// The metadata of the item if it is not on disk. The metadata is read from
+    /// disk otherwise.
+    pub metadata: Option<ItemMetadata>,
     /// The filepath.
     pub path: String,
 }
@@ -20,20 +36,75 @@ pub struct FoundItem {
 pub struct TransformedItem {
     /// The filepath broken down into its individual components.
     pub components: Vec<String>,
+    /// The lines matching the `--contains` pattern if content search is enabled.
+    pub content_matches: Option<ContentMatches>,
     /// The depth of the file relative to the root of the directory.
     pub depth: i32,
+    /// The name displayed for a directory if a chain of directories was
+    /// compacted into it, ie. `main/java/com`.
+    pub display_name: Option<String>,
+    /// The number and size of the items that were not displayed in a directory
+    /// if this is a summary of those items.
+    pub hidden_items: Option<HiddenItems>,
     /// Indicates whether this is a directory.
     pub is_dir: bool,
+    /// Indicates whether this is an empty directory.
+    pub is_empty: bool,
     /// Indicates whether this is a file.
     pub is_file: bool,
+    /// The problem with the item if it is a dangling symlink or a symlink loop.
+    pub link_problem: Option<LinkProblem>,
     /// The Git status marker indicating the change that was made to the file.
     pub marker: Option<String>,
-    /// The start and end of the pattern match in the path.
-    pub matched: Option<(usize, usize)>,
+    /// The start and end of each pattern match in the path.
+    pub matched: Vec<(usize, usize)>,
+    /// The metadata of the item if it is not on disk. The metadata is read from
+    /// disk otherwise.
+    pub metadata: Option<ItemMetadata>,
     /// The absolute filepath.
     pub path: String,
 }
 
+/// Contains the metadata of an item that is not on disk, such as a member of an
+/// archive. Fields that are unknown are displayed as `-`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ItemMetadata {
+    /// The mode of the item, including its file type bits.
+    pub mode: Option<u32>,
+    /// The last modified time of the item as a UNIX timestamp.
+    pub modified: Option<i64>,
+    /// The size of the item in bytes.
+    pub size: Option<u64>,
+}
+
+/// Contains the items in a directory that were not displayed because of
+/// `--max-entries` or the `collapsed_directories` setting.
+#[derive(Debug, Default, PartialEq)]
+pub struct HiddenItems {
+    /// The number of items in the directory that were not displayed.
+    pub count: usize,
+    /// The total size of the files within those items, in bytes.
+    pub size: u64,
+}
+
+/// Contains the lines of a file that matched the `--contains` pattern.
+#[derive(Debug)]
+pub struct ContentMatches {
+    /// Each matching line in the order it appears in the file.
+    pub lines: Vec<MatchedLine>,
+}
+
+/// Contains a single line that matched the `--contains` pattern.
+#[derive(Debug, PartialEq)]
+pub struct MatchedLine {
+    /// The start and end of each match within the line.
+    pub matched: Vec<(usize, usize)>,
+    /// The line number, starting at 1.
+    pub number: usize,
+    /// The line with its surrounding whitespace removed.
+    pub text: String,
+}
+
 /// Contains metadata for `git branch` items.
 ///
 /// This struct is used to convert Git branches into a struct containing metadata used for tree
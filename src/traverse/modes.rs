@@ -1,5 +1,7 @@
 //! Traversal modes for `nomad`.
 
+use std::collections::HashSet;
+
 /// Modes in which `nomad` may operate.
 pub enum NomadMode {
     /// Run `nomad` in `git branch` mode.
@@ -10,4 +12,7 @@ pub enum NomadMode {
     Normal,
     /// Run `nomad` in rootless (interactive) mode.
     Rootless,
+    /// Run `nomad` in watch mode. Contains the paths of the items that were
+    /// created or modified since the previous frame, which are highlighted.
+    Watch(HashSet<String>),
 }
@@ -0,0 +1,181 @@
+//! Filter items by their owners and permissions.
+
+use crate::{
+    cli::global::{ModifierArgs, Permission},
+    errors::NomadError,
+};
+
+use users::{get_group_by_name, get_user_by_name};
+
+use std::{fs, path::Path};
+
+#[cfg(target_family = "unix")]
+use std::os::unix::fs::MetadataExt;
+
+/// The setuid bit in an item's mode.
+const SETUID: u32 = 0o4000;
+
+/// Only keeps items with the specified owners and permissions.
+#[derive(Debug)]
+pub struct PermissionFilter {
+    /// The ID of the group that must own the items.
+    gid: Option<u32>,
+    /// The permission checks every item must pass.
+    permissions: Vec<Permission>,
+    /// Indicates whether items must have the setuid bit set.
+    setuid: bool,
+    /// The ID of the user that must own the items.
+    uid: Option<u32>,
+}
+
+impl PermissionFilter {
+    /// Create a new `PermissionFilter` from `--user`, `--group`, `--perm`, and
+    /// `--setuid`. Returns `None` if none of them were provided.
+    pub fn new(args: &ModifierArgs) -> Result<Option<Self>, NomadError> {
+        if args.user.is_none() && args.group.is_none() && args.perm.is_empty() && !args.setuid {
+            return Ok(None);
+        }
+
+        let uid = args
+            .user
+            .as_ref()
+            .map(|user| {
+                user.parse::<u32>()
+                    .ok()
+                    .or_else(|| get_user_by_name(user).map(|user| user.uid()))
+                    .ok_or_else(|| NomadError::UnknownOwner(user.to_string()))
+            })
+            .transpose()?;
+        let gid = args
+            .group
+            .as_ref()
+            .map(|group| {
+                group
+                    .parse::<u32>()
+                    .ok()
+                    .or_else(|| get_group_by_name(group).map(|group| group.gid()))
+                    .ok_or_else(|| NomadError::UnknownOwner(group.to_string()))
+            })
+            .transpose()?;
+
+        Ok(Some(Self {
+            gid,
+            permissions: args.perm.clone(),
+            setuid: args.setuid,
+            uid,
+        }))
+    }
+
+    /// Check whether the item has the specified owners and permissions. Items
+    /// whose metadata cannot be read are not kept.
+    pub fn is_match(&self, item: &Path) -> bool {
+        get_ownership(item).is_some_and(|(uid, gid, mode)| {
+            self.uid.is_none_or(|expected| uid == expected)
+                && self.gid.is_none_or(|expected| gid == expected)
+                && (!self.setuid || mode & SETUID != 0)
+                && self
+                    .permissions
+                    .iter()
+                    .all(|permission| has_permission(mode, permission))
+        })
+    }
+}
+
+/// Check whether the mode passes the permission check.
+fn has_permission(mode: u32, permission: &Permission) -> bool {
+    let get_bits = |class: char| {
+        let shift = match class {
+            'u' => 6,
+            'g' => 3,
+            _ => 0,
+        };
+
+        (mode >> shift) & 0o7
+    };
+
+    permission.permissions.iter().all(|bit| {
+        let bit = match bit {
+            'r' => 0o4,
+            'w' => 0o2,
+            _ => 0o1,
+        };
+
+        if permission.classes.is_empty() {
+            ['u', 'g', 'o']
+                .into_iter()
+                .any(|class| get_bits(class) & bit != 0)
+        } else {
+            permission
+                .classes
+                .iter()
+                .all(|class| (get_bits(*class) & bit != 0) == permission.granted)
+        }
+    })
+}
+
+/// Get the owner, group, and mode of an item.
+#[cfg(target_family = "unix")]
+fn get_ownership(item: &Path) -> Option<(u32, u32, u32)> {
+    fs::metadata(item)
+        .ok()
+        .map(|metadata| (metadata.uid(), metadata.gid(), metadata.mode()))
+}
+
+/// Get the owner, group, and mode of an item.
+///
+/// Owners and mode bits are not available on Windows, so nothing is kept.
+#[cfg(target_family = "windows")]
+fn get_ownership(_item: &Path) -> Option<(u32, u32, u32)> {
+    None
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod test_permissions {
+    use super::*;
+
+    use crate::{cli::global::GlobalArgs, utils::test_utils::create_fixture};
+
+    use structopt::StructOpt;
+
+    use std::{fs::Permissions, os::unix::fs::PermissionsExt};
+
+    #[test]
+    fn test_permission_filter() {
+        let fixture = create_fixture(&[("script.sh", ""), ("shared.txt", ""), ("private.txt", "")]);
+
+        let script = fixture.path().join("script.sh");
+        let shared = fixture.path().join("shared.txt");
+        let private = fixture.path().join("private.txt");
+        for (item, mode) in [(&script, 0o755), (&shared, 0o666), (&private, 0o600)] {
+            fs::set_permissions(item, Permissions::from_mode(mode)).unwrap();
+        }
+
+        let filter_items = |flags: &[&str]| {
+            let args = GlobalArgs::from_iter(["nd"].iter().chain(flags));
+            let filter = PermissionFilter::new(&args.modifiers).unwrap().unwrap();
+
+            [&script, &shared, &private]
+                .into_iter()
+                .filter(|item| filter.is_match(item))
+                .map(|item| item.file_name().unwrap().to_str().unwrap())
+                .collect::<Vec<&str>>()
+        };
+
+        assert_eq!(filter_items(&["--perm", "-x"]), vec!["script.sh"]);
+        assert_eq!(filter_items(&["--perm", "o+w"]), vec!["shared.txt"]);
+        assert_eq!(filter_items(&["--perm", "go-r"]), vec!["private.txt"]);
+        assert_eq!(
+            filter_items(&["--perm", "u+rw", "--perm", "o-w"]),
+            vec!["script.sh", "private.txt"]
+        );
+        assert!(filter_items(&["--setuid"]).is_empty());
+
+        let uid = fs::metadata(&script).unwrap().uid().to_string();
+        assert_eq!(filter_items(&["--user", &uid]).len(), 3);
+
+        assert!(PermissionFilter::new(
+            &GlobalArgs::from_iter(["nd", "--group", "nomad-missing-group"]).modifiers
+        )
+        .is_err());
+    }
+}
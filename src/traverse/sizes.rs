@@ -0,0 +1,169 @@
+//! Compute the total size of each directory in the tree.
+
+use crate::{cli::global::GlobalArgs, utils::meta::convert_bytes};
+
+use ansi_term::Colour;
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::Metadata,
+    path::{Path, PathBuf},
+};
+
+#[cfg(target_family = "unix")]
+use std::os::unix::fs::MetadataExt;
+
+/// Contains the size of each file and the total size of each directory.
+#[derive(Debug, Default)]
+pub struct DirectorySizes {
+    /// The size of each file and the total size of the contents of each directory.
+    sizes: HashMap<PathBuf, u64>,
+    /// The root of the tree.
+    target_directory: PathBuf,
+    /// The largest items in each directory if only the largest items are displayed.
+    top_items: Option<HashSet<PathBuf>>,
+}
+
+impl DirectorySizes {
+    /// Add up the sizes of the items. Each file's size is added to every
+    /// directory it is in, up to and including the target directory.
+    ///
+    /// Uses the apparent size of each file if `apparent_size` is `true`,
+    /// otherwise uses the space the file occupies on disk.
+    pub fn new<I: IntoIterator<Item = PathBuf>>(
+        apparent_size: bool,
+        items: I,
+        target_directory: &str,
+        top: Option<usize>,
+    ) -> Self {
+        let target_directory = PathBuf::from(target_directory);
+        let mut sizes: HashMap<PathBuf, u64> = HashMap::new();
+
+        for item in items {
+            let size = match item.metadata() {
+                Ok(metadata) if metadata.is_file() => get_size(apparent_size, &metadata),
+                _ => 0,
+            };
+
+            for ancestor in item.ancestors() {
+                *sizes.entry(ancestor.to_path_buf()).or_insert(0) += size;
+
+                if ancestor == target_directory {
+                    break;
+                }
+            }
+        }
+
+        let top_items = top.map(|top| {
+            let mut children: HashMap<&Path, Vec<(&PathBuf, u64)>> = HashMap::new();
+            for (item, size) in sizes.iter() {
+                if item != &target_directory && item.starts_with(&target_directory) {
+                    if let Some(parent) = item.parent() {
+                        children.entry(parent).or_default().push((item, *size));
+                    }
+                }
+            }
+
+            children
+                .into_values()
+                .flat_map(|mut siblings| {
+                    siblings.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+                    siblings.truncate(top);
+
+                    siblings.into_iter().map(|(item, _)| item.clone())
+                })
+                .collect::<HashSet<PathBuf>>()
+        });
+
+        Self {
+            sizes,
+            target_directory,
+            top_items,
+        }
+    }
+
+    /// Get the size of a file or the total size of a directory.
+    pub fn get(&self, item: &Path) -> Option<u64> {
+        self.sizes.get(item).copied()
+    }
+
+    /// Check whether an item is displayed. An item is only hidden if it or one
+    /// of its parent directories is not among the largest items in its directory.
+    pub fn is_displayed(&self, item: &Path) -> bool {
+        match self.top_items {
+            Some(ref top_items) => item
+                .ancestors()
+                .take_while(|ancestor| *ancestor != self.target_directory)
+                .all(|ancestor| top_items.contains(ancestor)),
+            None => true,
+        }
+    }
+}
+
+/// Get the size of a file.
+#[cfg(target_family = "unix")]
+fn get_size(apparent_size: bool, metadata: &Metadata) -> u64 {
+    if apparent_size {
+        metadata.len()
+    } else {
+        metadata.blocks() * 512
+    }
+}
+
+/// Get the size of a file.
+///
+/// The size on disk is not available on Windows, so the apparent size is always used.
+#[cfg(target_family = "windows")]
+fn get_size(_apparent_size: bool, metadata: &Metadata) -> u64 {
+    metadata.len()
+}
+
+/// Format the size that is displayed next to an item in the tree.
+pub fn format_size(args: &GlobalArgs, size: u64) -> String {
+    let plain_size = i64::try_from(size).map_or("unknown size".to_string(), convert_bytes);
+
+    if args.style.plain || args.style.no_colors {
+        plain_size
+    } else {
+        Colour::Fixed(172).paint(plain_size).to_string()
+    }
+}
+
+#[cfg(test)]
+mod test_sizes {
+    use super::*;
+
+    use crate::utils::test_utils::create_fixture;
+
+    #[test]
+    fn test_totals_and_top_items() {
+        let fixture = create_fixture(&[
+            ("big/nested/a.txt", &"a".repeat(300)),
+            ("big/b.txt", &"a".repeat(200)),
+            ("small/c.txt", &"a".repeat(10)),
+            ("d.txt", &"a".repeat(100)),
+        ]);
+        let target_directory = fixture.path();
+
+        let items = vec![
+            target_directory.join("big"),
+            target_directory.join("big/nested"),
+            target_directory.join("big/nested/a.txt"),
+            target_directory.join("big/b.txt"),
+            target_directory.join("small"),
+            target_directory.join("small/c.txt"),
+            target_directory.join("d.txt"),
+        ];
+
+        let sizes = DirectorySizes::new(true, items, target_directory.to_str().unwrap(), Some(1));
+
+        assert_eq!(sizes.get(&target_directory.join("big")), Some(500));
+        assert_eq!(sizes.get(&target_directory.join("small")), Some(10));
+        assert_eq!(sizes.get(target_directory), Some(610));
+
+        assert!(sizes.is_displayed(&target_directory.join("big/nested/a.txt")));
+        assert!(!sizes.is_displayed(&target_directory.join("big/b.txt")));
+        assert!(!sizes.is_displayed(&target_directory.join("small/c.txt")));
+        assert!(!sizes.is_displayed(&target_directory.join("d.txt")));
+    }
+}
@@ -0,0 +1,241 @@
+//! Sort the items in each directory of the tree.
+
+use super::sizes::DirectorySizes;
+use crate::{
+    cli::global::{SortArgs, SortBy},
+    git::{markers::get_status_ranks, utils::get_repo},
+};
+
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+/// Compares items in the tree based on the client's sort options.
+#[derive(Clone)]
+pub struct ItemSorter {
+    /// The total size of each directory. Directories are sorted by their total
+    /// size instead of their own size if this is available.
+    directory_sizes: Option<Arc<DirectorySizes>>,
+    /// Display directories before files.
+    dirs_first: bool,
+    /// Reverse the sort order.
+    reverse: bool,
+    /// The key the items are sorted by.
+    sort_by: SortBy,
+    /// The Git status rank of each changed item. Only used when sorting by status.
+    status_ranks: HashMap<PathBuf, usize>,
+}
+
+impl ItemSorter {
+    /// Create a new `ItemSorter` from the client's sort options.
+    pub fn new(
+        args: &SortArgs,
+        directory_sizes: Option<Arc<DirectorySizes>>,
+        target_directory: &str,
+    ) -> Self {
+        let status_ranks = if args.sort_by == SortBy::Status {
+            get_repo(target_directory)
+                .and_then(|repo| get_status_ranks(&repo).ok())
+                .unwrap_or_default()
+        } else {
+            HashMap::new()
+        };
+
+        Self {
+            directory_sizes,
+            dirs_first: args.dirs_first,
+            reverse: args.reverse,
+            sort_by: args.sort_by,
+            status_ranks,
+        }
+    }
+
+    /// Compare two items that are in the same directory.
+    pub fn compare_siblings(&self, a: &Path, b: &Path) -> Ordering {
+        if self.dirs_first {
+            let directory_order = b.is_dir().cmp(&a.is_dir());
+            if directory_order != Ordering::Equal {
+                return directory_order;
+            }
+        }
+
+        let order = match self.sort_by {
+            SortBy::Extension => a
+                .extension()
+                .cmp(&b.extension())
+                .then_with(|| a.file_name().cmp(&b.file_name())),
+            SortBy::Modified => get_modified(b).cmp(&get_modified(a)),
+            SortBy::Name => a.file_name().cmp(&b.file_name()),
+            SortBy::Size => self.get_size(b).cmp(&self.get_size(a)),
+            SortBy::Status => self.get_rank(a).cmp(&self.get_rank(b)),
+        }
+        .then_with(|| a.cmp(b));
+
+        if self.reverse {
+            order.reverse()
+        } else {
+            order
+        }
+    }
+
+    /// Compare two items anywhere in the tree. Each directory is placed before
+    /// its contents and the items within a directory are compared with
+    /// `compare_siblings()`, which matches the order of a sorted traversal.
+    pub fn compare_paths(&self, a: &Path, b: &Path) -> Ordering {
+        self.compare_paths_by(a, b, Path::to_path_buf)
+    }
+
+    /// Compare two items anywhere in the tree like `compare_paths()`. Each path
+    /// that is compared with `compare_siblings()` is passed through `resolve`
+    /// first, which allows relative paths to be sorted by the items they refer to.
+    pub fn compare_paths_by(
+        &self,
+        a: &Path,
+        b: &Path,
+        resolve: impl Fn(&Path) -> PathBuf,
+    ) -> Ordering {
+        let mut a_path = PathBuf::new();
+        let mut b_path = PathBuf::new();
+
+        let mut a_components = a.components();
+        let mut b_components = b.components();
+
+        loop {
+            match (a_components.next(), b_components.next()) {
+                (Some(a_component), Some(b_component)) => {
+                    a_path.push(a_component);
+                    b_path.push(b_component);
+
+                    if a_component != b_component {
+                        return self.compare_siblings(&resolve(&a_path), &resolve(&b_path));
+                    }
+                }
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+                (None, None) => return Ordering::Equal,
+            }
+        }
+    }
+
+    /// Get the size of an item, or the total size of a directory if directory
+    /// sizes were added up.
+    fn get_size(&self, item: &Path) -> u64 {
+        self.directory_sizes
+            .as_ref()
+            .and_then(|directory_sizes| directory_sizes.get(item))
+            .unwrap_or_else(|| get_size(item))
+    }
+
+    /// Get the Git status rank of an item. Unchanged items are ranked last.
+    fn get_rank(&self, item: &Path) -> usize {
+        self.status_ranks.get(item).copied().unwrap_or(usize::MAX)
+    }
+}
+
+/// Get the last modified time of an item.
+fn get_modified(item: &Path) -> Option<SystemTime> {
+    fs::metadata(item)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Get the size of an item.
+fn get_size(item: &Path) -> u64 {
+    fs::metadata(item).map_or(0, |metadata| metadata.len())
+}
+
+#[cfg(test)]
+mod test_sort {
+    use super::*;
+
+    use crate::utils::test_utils::create_fixture;
+
+    fn get_sorter(sort_by: SortBy, dirs_first: bool, reverse: bool) -> ItemSorter {
+        ItemSorter {
+            directory_sizes: None,
+            dirs_first,
+            reverse,
+            sort_by,
+            status_ranks: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_compare_paths_keeps_directories_together() {
+        let sorter = get_sorter(SortBy::Name, false, true);
+
+        let mut paths = vec![
+            PathBuf::from("root/a/one.rs"),
+            PathBuf::from("root/b"),
+            PathBuf::from("root/a"),
+            PathBuf::from("root/b/two.rs"),
+            PathBuf::from("root/c.rs"),
+        ];
+        paths.sort_by(|a, b| sorter.compare_paths(a, b));
+
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("root/c.rs"),
+                PathBuf::from("root/b"),
+                PathBuf::from("root/b/two.rs"),
+                PathBuf::from("root/a"),
+                PathBuf::from("root/a/one.rs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_paths_by_resolved_paths() {
+        let fixture = create_fixture(&[("left/z_removed/", ""), ("right/a_added.txt", "")]);
+
+        let left = fixture.path().join("left");
+        let right = fixture.path().join("right");
+
+        let sorter = get_sorter(SortBy::Name, true, false);
+
+        let mut paths = vec![PathBuf::from("a_added.txt"), PathBuf::from("z_removed")];
+        paths.sort_by(|a, b| {
+            sorter.compare_paths_by(a, b, |path| {
+                if right.join(path).exists() {
+                    right.join(path)
+                } else {
+                    left.join(path)
+                }
+            })
+        });
+
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("z_removed"), PathBuf::from("a_added.txt")]
+        );
+    }
+
+    #[test]
+    fn test_compare_siblings_by_extension() {
+        let sorter = get_sorter(SortBy::Extension, false, false);
+
+        let mut paths = vec![
+            PathBuf::from("b.toml"),
+            PathBuf::from("a.toml"),
+            PathBuf::from("c.md"),
+            PathBuf::from("Makefile"),
+        ];
+        paths.sort_by(|a, b| sorter.compare_siblings(a, b));
+
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("Makefile"),
+                PathBuf::from("c.md"),
+                PathBuf::from("a.toml"),
+                PathBuf::from("b.toml"),
+            ]
+        );
+    }
+}
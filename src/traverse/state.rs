@@ -0,0 +1,230 @@
+//! Labels and statistics that are collected while trees are written.
+
+use super::{
+    dupes::DuplicateReport,
+    links::{LinkProblem, LinkReport},
+    stats::TreeStatistics,
+    utils::store_directory_contents,
+};
+use crate::{
+    cli::global::GlobalArgs, errors::NomadError, git::markers::get_status_names,
+    style::models::NomadStyle, ALPHABET,
+};
+
+use anyhow::Result;
+use git2::Repository;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Contains the item labels and statistics for the tree that is being written.
+///
+/// A single `TreeState` is shared by every tree in a forest so that labels are
+/// unique across all of the trees.
+#[derive(Debug, Default)]
+pub struct TreeState {
+    /// The groups of duplicate files found in the tree if `--dupes` was used.
+    duplicates: DuplicateReport,
+    /// The root of the forest if several trees are written. The paths of symlink
+    /// problems are displayed relative to this directory instead of the root of
+    /// their own tree so that it is clear which tree the symlink is in.
+    forest_root: Option<PathBuf>,
+    /// Maps the working directory of each repository to the Git status names of
+    /// its changed items. Statuses are only read for trees exported as JSON.
+    git_statuses: HashMap<String, HashMap<String, &'static str>>,
+    /// Maps each directory label to its directory.
+    labeled_items: HashMap<String, String>,
+    /// The index pointing to a letter in the alphabet.
+    letter_index: usize,
+    /// The symlink problems found in the tree.
+    link_report: LinkReport,
+    /// The number of times the alphabet has been looped.
+    loop_count: usize,
+    /// The number of directories in the tree.
+    pub num_directories: i32,
+    /// The number of files in the tree. This is also the number of the next file.
+    pub num_files: i32,
+    /// Maps each file number to its file.
+    numbered_items: HashMap<String, String>,
+    /// The statistics collected for the items in the tree if `--stats` was used.
+    statistics: TreeStatistics,
+}
+
+impl TreeState {
+    /// Create a new `TreeState` for a forest.
+    pub fn for_forest(forest_root: &Path) -> Self {
+        Self {
+            forest_root: Some(forest_root.to_path_buf()),
+            ..Default::default()
+        }
+    }
+
+    /// Check whether several trees are written with this `TreeState`.
+    pub fn is_forest(&self) -> bool {
+        self.forest_root.is_some()
+    }
+
+    /// Assign the next letter label to a directory.
+    pub fn label_directory(&mut self, item: &str) -> String {
+        if self.letter_index == 26 {
+            self.loop_count += 1;
+            self.letter_index = 0;
+        }
+
+        let mut directory_label = ALPHABET.get(self.letter_index).unwrap_or(&'?').to_string();
+
+        if self.loop_count > 0 {
+            directory_label.push_str(&self.loop_count.to_string());
+        }
+
+        self.labeled_items
+            .insert(directory_label.to_string(), item.to_string());
+
+        self.letter_index += 1;
+        self.num_directories += 1;
+
+        directory_label
+    }
+
+    /// Assign the next number to a file.
+    pub fn number_file(&mut self, item: &str) -> i32 {
+        let number = self.num_files;
+
+        self.numbered_items
+            .insert(format!("{number}"), item.to_string());
+        self.num_files += 1;
+
+        number
+    }
+
+    /// Get the path of an item in the tree for `target_directory` relative to
+    /// the root of the forest, or the target directory if a single tree is written.
+    pub fn relative_path<'a>(&self, item: &'a Path, target_directory: &str) -> &'a Path {
+        let root = self
+            .forest_root
+            .as_deref()
+            .unwrap_or_else(|| Path::new(target_directory));
+
+        item.strip_prefix(root).unwrap_or(item)
+    }
+
+    /// Get the name of the Git status of an item, ie. `modified`. The statuses
+    /// of the repository containing the item are read the first time one of its
+    /// items is looked up.
+    pub fn git_status(&mut self, item: &str) -> Option<&'static str> {
+        let repo = Repository::discover(Path::new(item).parent()?).ok()?;
+        let workdir = repo.workdir()?.to_str()?.to_string();
+
+        self.git_statuses
+            .entry(workdir)
+            .or_insert_with_key(|workdir| get_status_names(&repo, workdir).unwrap_or_default())
+            .get(item)
+            .copied()
+    }
+
+    /// Record a symlink problem in the tree for `target_directory`.
+    pub fn add_link_problem(
+        &mut self,
+        item: &Path,
+        link_problem: LinkProblem,
+        target_directory: &str,
+    ) {
+        self.link_report.add(
+            item,
+            self.relative_path(item, target_directory),
+            link_problem,
+        );
+    }
+
+    /// Record an item of the tree for `target_directory` in the statistics. The
+    /// size of a file is read from disk if it is not provided.
+    pub fn add_statistics(
+        &mut self,
+        item: &Path,
+        is_dir: bool,
+        size: Option<u64>,
+        target_directory: &str,
+    ) {
+        let relative_path = self.relative_path(item, target_directory);
+
+        self.statistics
+            .add_item(relative_path.components().count(), relative_path);
+        if !is_dir {
+            self.statistics.add_file(item, size);
+        }
+    }
+
+    /// Count the hidden items and the items matching ignore rules that were
+    /// skipped in the tree for `target_directory`.
+    pub fn count_skipped(
+        &mut self,
+        args: &GlobalArgs,
+        target_directory: &str,
+    ) -> Result<(), NomadError> {
+        self.statistics.count_skipped(args, target_directory)
+    }
+
+    /// Find the files with identical contents among the files in a tree.
+    pub fn find_duplicates<'a, I: IntoIterator<Item = &'a str>>(&mut self, items: I) {
+        self.duplicates.add(items);
+    }
+
+    /// Get the ID of the duplicate group a file belongs to.
+    pub fn duplicate_group(&self, item: &str) -> Option<usize> {
+        self.duplicates.get(item)
+    }
+
+    /// Store the labeled directories and numbered files so that they may be used
+    /// by other commands.
+    pub fn store_labels(&self) -> Result<(), NomadError> {
+        store_directory_contents(self.labeled_items.clone(), self.numbered_items.clone())
+    }
+
+    /// Display each group of duplicate files and the space it wastes. Files are
+    /// listed relative to `root` with their numbered labels.
+    pub fn display_duplicates(&self, args: &GlobalArgs, nomad_style: &NomadStyle, root: &Path) {
+        if self.duplicates.is_empty() {
+            println!("No duplicate files were found.\n");
+            return;
+        }
+
+        println!(
+            "{}\n",
+            self.duplicates
+                .format(args, &self.file_labels(), nomad_style, root)
+        );
+    }
+
+    /// Display the traversal statistics. Files are listed relative to `root`
+    /// with their numbered labels.
+    pub fn display_statistics(
+        &mut self,
+        args: &GlobalArgs,
+        duration: u128,
+        nomad_style: &NomadStyle,
+        root: &Path,
+    ) {
+        let labels = self.file_labels();
+
+        self.statistics
+            .report(
+                self.num_directories,
+                duration,
+                self.num_files,
+                &labels,
+                &self.link_report,
+                self.forest_root.as_deref().unwrap_or(root),
+            )
+            .display(args, &self.link_report, nomad_style);
+    }
+
+    /// Map each numbered file to its number.
+    fn file_labels(&self) -> HashMap<String, String> {
+        self.numbered_items
+            .iter()
+            .map(|(number, item)| (item.to_string(), number.to_string()))
+            .collect()
+    }
+}
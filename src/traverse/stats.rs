@@ -0,0 +1,492 @@
+//! Collect statistics about the items in the tree.
+
+use super::{
+    links::{get_link_error, LinkReport},
+    matcher::ItemMatcher,
+    utils::configure_walk,
+};
+use crate::{
+    cli::global::{GlobalArgs, StatsFormat},
+    errors::NomadError,
+    style::models::NomadStyle,
+    utils::{
+        meta::convert_bytes,
+        table::{TableView, TabledItems},
+    },
+};
+
+use serde::Serialize;
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The number of largest files displayed in the statistics.
+pub const LARGEST_FILES: usize = 10;
+
+/// The name used for files without an extension.
+const NO_EXTENSION: &str = "(none)";
+
+/// Contains the statistics of the items written to the tree.
+#[derive(Debug, Default)]
+pub struct TreeStatistics {
+    /// The depth of the deepest item and its path.
+    deepest: Option<(usize, String)>,
+    /// The number of files with each extension and their total size.
+    extensions: HashMap<String, (usize, u64)>,
+    /// The largest files and their sizes. This holds more than `LARGEST_FILES`
+    /// files until it is trimmed.
+    largest_files: Vec<(u64, PathBuf)>,
+    /// The number of hidden items that were skipped.
+    skipped_hidden: usize,
+    /// The number of items matching ignore rules that were skipped.
+    skipped_ignored: usize,
+    /// The total size of the files.
+    total_size: u64,
+}
+
+impl TreeStatistics {
+    /// Record the depth of an item. `relative_path` is displayed if it is the
+    /// deepest item.
+    pub fn add_item(&mut self, depth: usize, relative_path: &Path) {
+        if self
+            .deepest
+            .as_ref()
+            .is_none_or(|(deepest, _)| depth > *deepest)
+        {
+            self.deepest = Some((depth, relative_path.to_str().unwrap_or("?").to_string()));
+        }
+    }
+
+    /// Record the extension and size of a file. The size is read from disk if
+    /// it is not provided.
+    pub fn add_file(&mut self, item: &Path, size: Option<u64>) {
+        let size = size
+            .or_else(|| fs::metadata(item).ok().map(|metadata| metadata.len()))
+            .unwrap_or(0);
+        let extension = item
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map_or(NO_EXTENSION.to_string(), |extension| {
+                extension.to_lowercase()
+            });
+
+        let (count, bytes) = self.extensions.entry(extension).or_insert((0, 0));
+        *count += 1;
+        *bytes += size;
+
+        self.total_size += size;
+
+        self.largest_files.push((size, item.to_path_buf()));
+        if self.largest_files.len() > LARGEST_FILES * 2 {
+            self.trim_largest_files();
+        }
+    }
+
+    /// Only keep the `LARGEST_FILES` largest files.
+    fn trim_largest_files(&mut self) {
+        self.largest_files
+            .sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        self.largest_files.truncate(LARGEST_FILES);
+    }
+
+    /// Count the hidden items and the items matching ignore rules that were
+    /// skipped while the directory was traversed. A skipped directory is counted
+    /// once rather than with its contents. Nothing is counted for trees that are
+    /// not on disk, such as archives.
+    ///
+    /// The directory is traversed once with the same settings as the tree. The
+    /// contents of each directory that was traversed are then listed, and the
+    /// items that were not found are the skipped items, so skipped directories
+    /// are never descended into.
+    pub fn count_skipped(
+        &mut self,
+        args: &GlobalArgs,
+        target_directory: &str,
+    ) -> Result<(), NomadError> {
+        if !Path::new(target_directory).is_dir() {
+            return Ok(());
+        }
+
+        let mut directories = Vec::new();
+        let mut found = HashSet::new();
+        for dir_entry in configure_walk(args, target_directory, None)?
+            .max_depth(args.modifiers.max_depth)
+            .build()
+        {
+            match dir_entry {
+                Ok(entry) => {
+                    if entry
+                        .file_type()
+                        .is_some_and(|file_type| file_type.is_dir())
+                        && args
+                            .modifiers
+                            .max_depth
+                            .is_none_or(|max_depth| entry.depth() < max_depth)
+                    {
+                        directories.push(entry.path().to_path_buf());
+                    }
+
+                    found.insert(entry.into_path());
+                }
+                Err(error) => {
+                    if let Some((path, _)) = get_link_error(&error) {
+                        found.insert(path);
+                    }
+                }
+            }
+        }
+
+        // Items that were excluded by a pattern or are too large were filtered
+        // out rather than skipped.
+        let matcher = ItemMatcher::new(&args.regex)?;
+        let is_filtered = |item: &Path| {
+            matcher.is_excluded(item.strip_prefix(target_directory).unwrap_or(item))
+                || args.modifiers.max_filesize.is_some_and(|max_filesize| {
+                    fs::metadata(item)
+                        .is_ok_and(|metadata| metadata.is_file() && metadata.len() > max_filesize.0)
+                })
+        };
+
+        for directory in directories {
+            let contents = match fs::read_dir(&directory) {
+                Ok(contents) => contents,
+                Err(_) => continue,
+            };
+
+            for item in contents
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+            {
+                if found.contains(&item) || is_filtered(&item) {
+                    continue;
+                }
+
+                let is_hidden = item
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with('.'));
+                if is_hidden && !args.modifiers.hidden {
+                    self.skipped_hidden += 1;
+                } else {
+                    self.skipped_ignored += 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build the report for the tree. File paths are displayed relative to
+    /// `root` next to their numbered labels.
+    pub fn report(
+        &mut self,
+        directories: i32,
+        duration: u128,
+        files: i32,
+        labels: &HashMap<String, String>,
+        link_report: &LinkReport,
+        root: &Path,
+    ) -> StatisticsReport {
+        self.trim_largest_files();
+
+        let mut extensions = self
+            .extensions
+            .iter()
+            .map(|(extension, (files, bytes))| ExtensionStatistics {
+                bytes: *bytes,
+                extension: extension.to_string(),
+                files: *files,
+            })
+            .collect::<Vec<ExtensionStatistics>>();
+        extensions.sort_by(|a, b| {
+            b.bytes
+                .cmp(&a.bytes)
+                .then_with(|| b.files.cmp(&a.files))
+                .then_with(|| a.extension.cmp(&b.extension))
+        });
+
+        StatisticsReport {
+            dangling_symlinks: link_report.dangling(),
+            deepest_path: self.deepest.as_ref().map(|(_, path)| path.to_string()),
+            directories,
+            duration_ms: duration,
+            extensions,
+            files,
+            largest_files: self
+                .largest_files
+                .iter()
+                .map(|(bytes, item)| LargestFile {
+                    bytes: *bytes,
+                    label: labels.get(item.to_str().unwrap_or("?")).cloned(),
+                    path: item
+                        .strip_prefix(root)
+                        .unwrap_or(item)
+                        .to_str()
+                        .unwrap_or("?")
+                        .to_string(),
+                })
+                .collect(),
+            max_depth: self.deepest.as_ref().map_or(0, |(depth, _)| *depth),
+            skipped_hidden: self.skipped_hidden,
+            skipped_ignored: self.skipped_ignored,
+            symlink_loops: link_report.loops(),
+            total_bytes: self.total_size,
+        }
+    }
+}
+
+/// Contains the number of files with an extension and their total size.
+#[derive(Debug, Serialize)]
+pub struct ExtensionStatistics {
+    /// The total size of the files in bytes.
+    pub bytes: u64,
+    /// The extension, or `(none)` for files without an extension.
+    pub extension: String,
+    /// The number of files.
+    pub files: usize,
+}
+
+/// Contains one of the largest files in the tree.
+#[derive(Debug, Serialize)]
+pub struct LargestFile {
+    /// The size of the file in bytes.
+    pub bytes: u64,
+    /// The file's numbered label.
+    pub label: Option<String>,
+    /// The path to the file relative to the root of the tree.
+    pub path: String,
+}
+
+/// Contains a symlink problem and the item the symlink points to.
+#[derive(Debug, Serialize)]
+pub struct LinkStatistics {
+    /// The item the symlink points to.
+    pub points_to: String,
+    /// The path to the symlink relative to the root of the tree.
+    pub symlink: String,
+}
+
+/// Contains the statistics displayed after the tree.
+#[derive(Debug, Serialize)]
+pub struct StatisticsReport {
+    /// Symlinks that point to items that do not exist.
+    pub dangling_symlinks: Vec<LinkStatistics>,
+    /// The path to the deepest item relative to the root of the tree.
+    pub deepest_path: Option<String>,
+    /// The number of directories in the tree.
+    pub directories: i32,
+    /// The time it took to write the tree in milliseconds.
+    pub duration_ms: u128,
+    /// The number of files with each extension and their total size, largest first.
+    pub extensions: Vec<ExtensionStatistics>,
+    /// The number of files in the tree.
+    pub files: i32,
+    /// The largest files in the tree, largest first.
+    pub largest_files: Vec<LargestFile>,
+    /// The depth of the deepest item.
+    pub max_depth: usize,
+    /// The number of hidden items that were skipped.
+    pub skipped_hidden: usize,
+    /// The number of items matching ignore rules that were skipped.
+    pub skipped_ignored: usize,
+    /// Symlinks that point to one of their parent directories.
+    pub symlink_loops: Vec<LinkStatistics>,
+    /// The total size of the files in bytes.
+    pub total_bytes: u64,
+}
+
+impl StatisticsReport {
+    /// Display the report as JSON or in tables. Symlink problems are displayed
+    /// below the tables with `link_report`.
+    pub fn display(&self, args: &GlobalArgs, link_report: &LinkReport, nomad_style: &NomadStyle) {
+        match args.stats_format {
+            StatsFormat::Json => match serde_json::to_string_pretty(self) {
+                Ok(json) => println!("{json}\n"),
+                Err(error) => println!("Unable to serialize the statistics: {error}\n"),
+            },
+            StatsFormat::Table => {
+                let format_size = |bytes: u64| {
+                    i64::try_from(bytes)
+                        .map_or("unknown size".to_string(), convert_bytes)
+                        .trim()
+                        .to_string()
+                };
+
+                TabledItems::new(
+                    vec![
+                        vec!["Directories".to_string(), self.directories.to_string()],
+                        vec!["Files".to_string(), self.files.to_string()],
+                        vec!["Total size".to_string(), format_size(self.total_bytes)],
+                        vec![
+                            "Max depth".to_string(),
+                            match self.deepest_path {
+                                Some(ref deepest_path) => {
+                                    format!("{} ({deepest_path})", self.max_depth)
+                                }
+                                None => "0".to_string(),
+                            },
+                        ],
+                        vec![
+                            "Skipped hidden items".to_string(),
+                            self.skipped_hidden.to_string(),
+                        ],
+                        vec![
+                            "Skipped ignored items".to_string(),
+                            self.skipped_ignored.to_string(),
+                        ],
+                        vec!["Duration".to_string(), format!("{} ms", self.duration_ms)],
+                    ],
+                    vec!["Statistic".to_string(), "Value".to_string()],
+                    120,
+                    None,
+                )
+                .display_table();
+
+                if !self.extensions.is_empty() {
+                    TabledItems::new(
+                        self.extensions
+                            .iter()
+                            .map(|extension| {
+                                vec![
+                                    extension.extension.to_string(),
+                                    extension.files.to_string(),
+                                    format_size(extension.bytes),
+                                ]
+                            })
+                            .collect(),
+                        vec![
+                            "Extension".to_string(),
+                            "Files".to_string(),
+                            "Size".to_string(),
+                        ],
+                        120,
+                        None,
+                    )
+                    .display_table();
+                }
+
+                if !self.largest_files.is_empty() {
+                    TabledItems::new(
+                        self.largest_files
+                            .iter()
+                            .map(|file| {
+                                vec![
+                                    file.label.clone().unwrap_or_default(),
+                                    format_size(file.bytes),
+                                    file.path.to_string(),
+                                ]
+                            })
+                            .collect(),
+                        vec!["Label".to_string(), "Size".to_string(), "File".to_string()],
+                        120,
+                        None,
+                    )
+                    .display_table();
+                }
+
+                if let Some(report) = link_report.format(args, nomad_style) {
+                    println!("{report}");
+                }
+
+                println!();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_stats {
+    use super::*;
+
+    use crate::utils::test_utils::create_fixture;
+
+    use structopt::StructOpt;
+
+    #[test]
+    fn test_tree_statistics() {
+        let root = Path::new("/nomad");
+        let mut statistics = TreeStatistics::default();
+
+        for (item, size) in [
+            ("src/main.rs", 300),
+            ("src/lib.RS", 100),
+            ("src/nested/deep/mod.rs", 50),
+            ("README", 20),
+            ("Cargo.toml", 400),
+        ] {
+            let path = root.join(item);
+            statistics.add_item(Path::new(item).components().count(), Path::new(item));
+            statistics.add_file(&path, Some(size));
+        }
+        for number in 0..LARGEST_FILES * 3 {
+            statistics.add_file(&root.join(format!("tiny_{number}.txt")), Some(1));
+        }
+
+        let labels = HashMap::from([(
+            root.join("Cargo.toml").to_str().unwrap().to_string(),
+            "4".to_string(),
+        )]);
+        let report = statistics.report(2, 0, 35, &labels, &LinkReport::default(), root);
+
+        assert_eq!(report.total_bytes, 900);
+        assert_eq!(report.max_depth, 4);
+        assert_eq!(
+            report.deepest_path.as_deref(),
+            Some("src/nested/deep/mod.rs")
+        );
+        assert_eq!(
+            report
+                .extensions
+                .iter()
+                .map(|extension| (
+                    extension.extension.as_str(),
+                    extension.files,
+                    extension.bytes
+                ))
+                .collect::<Vec<(&str, usize, u64)>>(),
+            vec![
+                ("rs", 3, 450),
+                ("toml", 1, 400),
+                ("txt", 30, 30),
+                ("(none)", 1, 20)
+            ]
+        );
+        assert_eq!(report.largest_files.len(), LARGEST_FILES);
+        assert_eq!(report.largest_files[0].path, "Cargo.toml");
+        assert_eq!(report.largest_files[0].label.as_deref(), Some("4"));
+        assert_eq!(report.largest_files[4].path, "README");
+    }
+
+    #[test]
+    fn test_count_skipped() {
+        let fixture = create_fixture(&[
+            (".ignore", "build\n"),
+            (".hidden", ""),
+            ("kept.txt", ""),
+            ("build/nested/output.o", ""),
+        ]);
+        let root = fixture.path().to_str().unwrap();
+
+        // Skipped directories are counted once rather than with their contents.
+        let mut statistics = TreeStatistics::default();
+        statistics
+            .count_skipped(&GlobalArgs::from_iter(["nd"]), root)
+            .unwrap();
+        assert_eq!(
+            (statistics.skipped_hidden, statistics.skipped_ignored),
+            (2, 1)
+        );
+
+        let mut statistics = TreeStatistics::default();
+        statistics
+            .count_skipped(&GlobalArgs::from_iter(["nd", "--hidden"]), root)
+            .unwrap();
+        assert_eq!(
+            (statistics.skipped_hidden, statistics.skipped_ignored),
+            (0, 1)
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Filter items by their timestamps.
+
+use crate::{
+    cli::global::{ModifierArgs, TimeField},
+    errors::NomadError,
+};
+
+use std::{
+    fs::{self, Metadata},
+    io,
+    path::Path,
+    time::SystemTime,
+};
+
+#[cfg(target_family = "unix")]
+use std::{os::unix::fs::MetadataExt, time::Duration};
+
+/// Only keeps items whose timestamps fall within a range of time.
+#[derive(Debug)]
+pub struct TimeFilter {
+    /// Items must be newer than this time.
+    newer_than: Option<SystemTime>,
+    /// Items must be older than this time.
+    older_than: Option<SystemTime>,
+    /// The timestamp that is compared.
+    time_field: TimeField,
+}
+
+impl TimeFilter {
+    /// Create a new `TimeFilter` from `--changed-within`, `--older-than`, and
+    /// `--newer`. Returns `None` if none of them were provided.
+    pub fn new(args: &ModifierArgs) -> Result<Option<Self>, NomadError> {
+        if args.changed_within.is_none() && args.older_than.is_none() && args.newer.is_none() {
+            return Ok(None);
+        }
+
+        let now = SystemTime::now();
+
+        let mut newer_than = args.changed_within.and_then(|age| now.checked_sub(age.0));
+        if let Some(ref item) = args.newer {
+            let item_time = fs::metadata(item)
+                .and_then(|metadata| get_time(&metadata, args.time_field))
+                .map_err(|error| {
+                    NomadError::PathError(format!(
+                        "Unable to read the timestamps of {item}: {error}"
+                    ))
+                })?;
+            newer_than = Some(newer_than.map_or(item_time, |time| time.max(item_time)));
+        }
+
+        Ok(Some(Self {
+            newer_than,
+            older_than: args.older_than.and_then(|age| now.checked_sub(age.0)),
+            time_field: args.time_field,
+        }))
+    }
+
+    /// Check whether the item's timestamp is within the range. Items whose
+    /// timestamps cannot be read are not kept.
+    pub fn is_match(&self, item: &Path) -> bool {
+        fs::metadata(item)
+            .and_then(|metadata| get_time(&metadata, self.time_field))
+            .is_ok_and(|time| {
+                self.newer_than.is_none_or(|newer_than| time > newer_than)
+                    && self.older_than.is_none_or(|older_than| time < older_than)
+            })
+    }
+}
+
+/// Get the timestamp of an item.
+#[cfg(target_family = "unix")]
+fn get_time(metadata: &Metadata, time_field: TimeField) -> io::Result<SystemTime> {
+    match time_field {
+        TimeField::Accessed => metadata.accessed(),
+        TimeField::Changed => Ok(SystemTime::UNIX_EPOCH
+            + Duration::new(metadata.ctime() as u64, metadata.ctime_nsec() as u32)),
+        TimeField::Modified => metadata.modified(),
+    }
+}
+
+/// Get the timestamp of an item.
+///
+/// The changed time is not available on Windows, so the creation time is used instead.
+#[cfg(target_family = "windows")]
+fn get_time(metadata: &Metadata, time_field: TimeField) -> io::Result<SystemTime> {
+    match time_field {
+        TimeField::Accessed => metadata.accessed(),
+        TimeField::Changed => metadata.created(),
+        TimeField::Modified => metadata.modified(),
+    }
+}
+
+#[cfg(test)]
+mod test_times {
+    use super::*;
+
+    use crate::{cli::global::GlobalArgs, utils::test_utils::create_fixture};
+
+    use structopt::StructOpt;
+
+    use std::{fs::File, time::Duration};
+
+    #[test]
+    fn test_time_filter() {
+        let fixture = create_fixture(&[("old.txt", ""), ("new.txt", "")]);
+
+        let old = fixture.path().join("old.txt");
+        let new = fixture.path().join("new.txt");
+        File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(3 * 86400))
+            .unwrap();
+
+        let recent = GlobalArgs::from_iter(["nd", "--changed-within", "2d"]);
+        let filter = TimeFilter::new(&recent.modifiers).unwrap().unwrap();
+        assert!(!filter.is_match(&old));
+        assert!(filter.is_match(&new));
+
+        let stale = GlobalArgs::from_iter(["nd", "--older-than", "1d"]);
+        let filter = TimeFilter::new(&stale.modifiers).unwrap().unwrap();
+        assert!(filter.is_match(&old));
+        assert!(!filter.is_match(&new));
+
+        let newer = GlobalArgs::from_iter(["nd", "--newer", old.to_str().unwrap()]);
+        let filter = TimeFilter::new(&newer.modifiers).unwrap().unwrap();
+        assert!(!filter.is_match(&old));
+        assert!(filter.is_match(&new));
+
+        assert!(TimeFilter::new(&GlobalArgs::from_iter(["nd"]).modifiers)
+            .unwrap()
+            .is_none());
+    }
+}
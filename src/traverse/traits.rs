@@ -1,27 +1,42 @@
 //! Exposing traits for directory traversal/item parsing.
 
 use super::{
+    dupes::format_duplicate,
     format::format_branch,
-    models::{DirItem, FoundBranch, FoundItem, TransformedBranch, TransformedItem},
+    limits::LimitedItems,
+    listing::{ItemPositions, LastItems},
+    models::{DirItem, FoundBranch, FoundItem, ItemMetadata, TransformedBranch, TransformedItem},
     modes::NomadMode,
+    sizes::{format_size, DirectorySizes},
+    state::TreeState,
+    writer::{find_last_items, TreeOutput, TreeWriter},
 };
 use crate::{
     cli::global::GlobalArgs,
     errors::NomadError,
-    loc::{loc_in_dir, loc_in_file},
+    loc::{loc_counts, loc_in_dir, loc_in_file},
     style::models::NomadStyle,
     traverse::{
-        format::{format_content, format_directory},
-        utils::{build_tree, check_nesting, get_file_icon, store_directory_contents},
+        format::{
+            format_changed, format_content, format_directory, format_hidden_items,
+            format_match_count, format_matched_line,
+        },
+        utils::{
+            build_tree, check_nesting, compact_directories, get_file_icon, store_directory_contents,
+        },
+    },
+    utils::{
+        export::{ExportNode, NodeType, TreeExport},
+        meta::get_export_metadata,
     },
-    ALPHABET,
 };
 
 use ansi_term::Colour;
-use ptree::{item::StringItem, print_tree_with, PrintConfig};
+use itertools::Either;
+use tokei::Language;
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     ffi::OsStr,
     path::{Component, Path},
     time::Instant,
@@ -29,75 +44,142 @@ use std::{
 
 /// Transform found items into a new struct containing useful metadata for tree building.
 pub trait TransformFound<T> {
-    /// Transforms a `Vec<U>` containing found items into a `Vec<T>` for tree building.
-    fn transform(self, target_directory: &str) -> Result<Vec<T>, NomadError>;
+    /// The collection of transformed items.
+    type Output: IntoIterator<Item = T>;
+
+    /// Transforms the found items into `T`s for tree building.
+    fn transform(self, target_directory: &str) -> Result<Self::Output, NomadError>;
 }
 
-impl TransformFound<TransformedItem> for Vec<FoundItem> {
-    /// Transforms a `Vec<FoundItem>` into a `Vec<TransformedItem>`.
-    fn transform(self, target_directory: &str) -> Result<Vec<TransformedItem>, NomadError> {
-        if self.is_empty() {
-            return Err(NomadError::NothingFound);
-        }
+/// Lazily transforms `FoundItem`s into `TransformedItem`s.
+///
+/// Found items have to be in traversal order. Each parent directory is yielded
+/// before the first item within it, so only the current directory needs to be
+/// held in memory.
+pub struct TransformedItems<I> {
+    /// The components of the directory the previous item was in.
+    current_directory: Vec<String>,
+    /// The found items that have not been transformed yet.
+    found_items: I,
+    /// Transformed items waiting to be yielded.
+    pending: VecDeque<TransformedItem>,
+    /// The root of the tree.
+    target_directory: String,
+}
 
-        let mut transformed: Vec<TransformedItem> = Vec::new();
-        let mut directories: HashSet<String> = HashSet::new();
+impl<I: Iterator<Item = FoundItem>> TransformedItems<I> {
+    /// Transform a `FoundItem` and its parent directories that have not been yielded yet.
+    fn queue(&mut self, found_item: FoundItem) {
+        let item = Path::new(&found_item.path)
+            .strip_prefix(&self.target_directory)
+            .unwrap_or_else(|_| Path::new("?"));
+
+        let components = item
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(section) => Some(section.to_str().unwrap_or("?").to_string()),
+                _ => None,
+            })
+            .collect::<Vec<String>>();
 
-        for found_item in self.iter() {
-            let item = Path::new(&found_item.path)
-                .strip_prefix(target_directory)
-                .unwrap_or_else(|_| Path::new("?"));
+        if components.is_empty() {
+            return;
+        }
 
-            let mut components = Vec::new();
-            let mut depth = 0;
-            for (index, component) in item.components().enumerate() {
-                if let Component::Normal(section) = component {
-                    components.push(section.to_str().unwrap_or("?").to_string());
-                    depth += 1;
+        let shared_depth = self
+            .current_directory
+            .iter()
+            .zip(components.iter())
+            .take_while(|(current, component)| current == component)
+            .count();
+
+        for depth in shared_depth..components.len() - 1 {
+            let directory_components = components[..=depth].to_vec();
+
+            self.pending.push_back(TransformedItem {
+                content_matches: None,
+                depth: depth as i32 + 1,
+                display_name: None,
+                hidden_items: None,
+                is_dir: true,
+                is_empty: false,
+                is_file: false,
+                link_problem: None,
+                marker: None,
+                matched: found_item.matched.clone(),
+                metadata: found_item
+                    .metadata
+                    .as_ref()
+                    .map(|_| ItemMetadata::default()),
+                path: Path::new(&self.target_directory)
+                    .join(directory_components.join("/"))
+                    .to_str()
+                    .unwrap_or("?")
+                    .to_string(),
+                components: directory_components,
+            });
+        }
 
-                    let joined_path = components.join("/").to_string();
+        self.current_directory = if found_item.is_dir {
+            components.clone()
+        } else {
+            components[..components.len() - 1].to_vec()
+        };
 
-                    if index < item.components().count() - 1 && !directories.contains(&joined_path)
-                    {
-                        transformed.push(TransformedItem {
-                            components: components.clone(),
-                            depth,
-                            is_dir: true,
-                            is_file: false,
-                            marker: None,
-                            matched: found_item.matched,
-                            path: Path::new(target_directory)
-                                .join(joined_path)
-                                .to_str()
-                                .unwrap_or("?")
-                                .to_string(),
-                        });
+        self.pending.push_back(TransformedItem {
+            content_matches: found_item.content_matches,
+            depth: components.len() as i32,
+            display_name: None,
+            hidden_items: None,
+            is_dir: found_item.is_dir,
+            is_empty: found_item.is_empty,
+            is_file: !found_item.is_dir,
+            link_problem: found_item.link_problem,
+            marker: found_item.marker,
+            matched: found_item.matched,
+            metadata: found_item.metadata,
+            path: Path::new(&self.target_directory)
+                .join(components.join("/"))
+                .to_str()
+                .unwrap_or("?")
+                .to_string(),
+            components,
+        });
+    }
+}
 
-                        directories.insert(components.join("/").to_string());
-                    } else if index == item.components().count() - 1 {
-                        transformed.push(TransformedItem {
-                            components: components.clone(),
-                            depth,
-                            is_dir: false,
-                            is_file: true,
-                            marker: found_item.marker.clone(),
-                            matched: found_item.matched,
-                            path: Path::new(target_directory)
-                                .join(joined_path)
-                                .to_str()
-                                .unwrap_or("?")
-                                .to_string(),
-                        });
-                    }
-                }
+impl<I: Iterator<Item = FoundItem>> Iterator for TransformedItems<I> {
+    type Item = TransformedItem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(transformed_item) = self.pending.pop_front() {
+                return Some(transformed_item);
             }
+
+            let found_item = self.found_items.next()?;
+            self.queue(found_item);
         }
+    }
+}
 
-        Ok(transformed)
+impl<I: Iterator<Item = FoundItem>> TransformFound<TransformedItem> for I {
+    type Output = TransformedItems<I>;
+
+    /// Wraps the found items in a `TransformedItems` iterator.
+    fn transform(self, target_directory: &str) -> Result<TransformedItems<I>, NomadError> {
+        Ok(TransformedItems {
+            current_directory: Vec::new(),
+            found_items: self,
+            pending: VecDeque::new(),
+            target_directory: target_directory.to_string(),
+        })
     }
 }
 
 impl TransformFound<TransformedBranch> for Vec<FoundBranch> {
+    type Output = Vec<TransformedBranch>;
+
     /// Transforms a `Vec<FoundBranch>` into a `Vec<TransformedBranch>`.
     fn transform(self, _: &str) -> Result<Vec<TransformedBranch>, NomadError> {
         if self.is_empty() {
@@ -164,44 +246,73 @@ impl TransformFound<TransformedBranch> for Vec<FoundBranch> {
     }
 }
 
-/// Converts a `Vec<TransformedItem>` into a `ptree` `StringItem` with its corresponding
-/// `PrintConfig`
+/// Streams transformed items into a tree with a `TreeWriter`.
 pub trait ToTree {
-    /// Convert the `Vec<TransformedItem>` to a `StringItem` and its corresponding `PrintConfig`.
-    /// May also return a `Vec` containing all directory items depending on the `NomadMode`.
+    /// Write the tree while the transformed items are visited. Returns the lines
+    /// of the tree and a `Vec` containing all directory items depending on the
+    /// `NomadMode`. Sizes are displayed next to each item if `directory_sizes`
+    /// is provided. The position of each item is found with the `last_items` if
+    /// the items are visited while the directory is traversed.
     fn to_tree(
         self,
         args: &GlobalArgs,
+        directory_sizes: Option<&DirectorySizes>,
+        export: Option<TreeExport>,
+        last_items: Option<LastItems>,
         nomad_mode: NomadMode,
         nomad_style: &NomadStyle,
         target_directory: &str,
-    ) -> Result<(StringItem, PrintConfig, Option<Vec<DirItem>>), NomadError>;
+    ) -> Result<(Option<Vec<String>>, Option<Vec<DirItem>>), NomadError>;
 }
 
-impl ToTree for Vec<TransformedItem> {
-    /// Build a tree from the `Vec<TransformedItem>`.
-    fn to_tree(
+/// Writes transformed items into a tree that has already been started.
+pub trait WriteItems {
+    /// Write the transformed items into an existing tree below its current
+    /// directory. Labels and statistics are recorded in the `TreeState`. Returns
+    /// a `Vec` containing all directory items if `nomad` is in Rootless mode.
+    ///
+    /// The items are collected first to find the position of each item unless the
+    /// `last_items` of the traversed directory are provided.
+    fn write_items(
         self,
         args: &GlobalArgs,
-        nomad_mode: NomadMode,
+        directory_sizes: Option<&DirectorySizes>,
+        last_items: Option<LastItems>,
+        nomad_mode: &NomadMode,
         nomad_style: &NomadStyle,
+        state: &mut TreeState,
         target_directory: &str,
-    ) -> Result<(StringItem, PrintConfig, Option<Vec<DirItem>>), NomadError> {
-        let mut numbered_items: HashMap<String, String> = HashMap::new();
-        let mut labeled_items: HashMap<String, String> = HashMap::new();
+        tree: &mut TreeWriter,
+    ) -> Result<Option<Vec<DirItem>>, NomadError>;
+}
 
+impl<I: Iterator<Item = TransformedItem>> WriteItems for I {
+    /// Write the `TransformedItem`s into the tree.
+    fn write_items(
+        self,
+        args: &GlobalArgs,
+        directory_sizes: Option<&DirectorySizes>,
+        last_items: Option<LastItems>,
+        nomad_mode: &NomadMode,
+        nomad_style: &NomadStyle,
+        state: &mut TreeState,
+        target_directory: &str,
+        tree: &mut TreeWriter,
+    ) -> Result<Option<Vec<DirItem>>, NomadError> {
         let mut current_depth = 0;
-        let mut letter_index = 0; // The index pointing to a letter in the alphabet.
-        let mut loop_count = 0; // Count the number of times the alphabet has been looped.
-        let mut num_directories = 0;
-        let mut num_files = 0;
-        let mut previous_item = &TransformedItem {
+        let mut previous_item = TransformedItem {
             components: vec![],
+            content_matches: None,
             depth: 0,
+            display_name: None,
+            hidden_items: None,
             is_dir: true,
+            is_empty: false,
             is_file: false,
+            link_problem: None,
             marker: None,
-            matched: None,
+            matched: Vec::new(),
+            metadata: None,
             path: target_directory.to_string(),
         };
 
@@ -222,143 +333,380 @@ impl ToTree for Vec<TransformedItem> {
             None
         };
 
-        let (config, mut tree) =
-            build_tree(args, &nomad_mode, nomad_style, Path::new(target_directory));
+        // Compacting directories requires knowing the contents of each directory,
+        // and finding duplicates requires knowing every file, so the items are
+        // collected first. Duplicates in a forest were already found across
+        // every tree.
+        let is_collected = args.modifiers.compact || args.meta.dupes;
+        let items = if is_collected {
+            let mut items = self.collect::<Vec<TransformedItem>>();
+
+            if args.meta.dupes && !state.is_forest() {
+                state.find_duplicates(
+                    items
+                        .iter()
+                        .filter(|item| item.is_file)
+                        .map(|item| item.path.as_str()),
+                );
+            }
+            if args.modifiers.compact {
+                items = compact_directories(items, args.modifiers.dirs);
+            }
+
+            Either::Left(items.into_iter())
+        } else {
+            Either::Right(self)
+        };
+
+        let items = LimitedItems::new(args, items, nomad_style, target_directory);
+
+        // Summaries are written, but files are not if only directories are displayed.
+        let is_written = |item: &TransformedItem| {
+            item.hidden_items.is_some() || item.is_dir || (item.is_file && !args.modifiers.dirs)
+        };
+        let (items, mut positions) = match last_items.filter(|_| !is_collected) {
+            Some(last_items) => (Either::Left(items), ItemPositions::Listed(last_items)),
+            None => {
+                let items = items.collect::<Vec<TransformedItem>>();
+                let last_items = find_last_items(
+                    items
+                        .iter()
+                        .map(|item| is_written(item).then_some(item.depth as usize)),
+                );
+
+                (
+                    Either::Right(items.into_iter()),
+                    ItemPositions::Collected(last_items.into_iter()),
+                )
+            }
+        };
+
+        for item in items {
+            let is_last = positions.is_last(&item);
 
-        let start = Instant::now();
-        for item in self.iter() {
             check_nesting(
                 current_depth,
                 Path::new(&target_directory)
                     .join(Path::new(&item.components.join("/")))
                     .as_path(),
-                &nomad_mode,
+                item.depth as usize,
                 Path::new(&target_directory)
                     .join(Path::new(&previous_item.components.join("/")))
                     .as_path(),
-                target_directory,
-                &mut tree,
-            );
+                previous_item.is_dir,
+                tree,
+            )?;
 
-            if item.is_dir {
-                if letter_index == 26 {
-                    loop_count += 1;
-                    letter_index = 0;
-                }
+            if let Some(ref hidden_items) = item.hidden_items {
+                tree.add_empty_child(format_hidden_items(args, hidden_items), is_last)?;
+            } else if item.is_dir {
+                let directory_label = state.label_directory(&item.path);
 
-                let mut directory_label = ALPHABET.get(letter_index).unwrap_or(&'?').to_string();
-
-                if loop_count > 0 {
-                    directory_label.push_str(&loop_count.to_string());
+                if args.statistics {
+                    state.add_statistics(Path::new(&item.path), true, None, target_directory);
                 }
 
-                labeled_items.insert(directory_label.to_string(), item.path.to_string());
-
-                letter_index += 1;
-
                 let label = if args.labels.label_directories || args.labels.all_labels {
-                    Some(directory_label)
+                    Some(directory_label.to_string())
                 } else {
                     None
                 };
 
-                tree.begin_child(format_directory(
+                let mut formatted_directory = format_directory(
                     args,
+                    item.display_name.as_deref(),
+                    item.is_empty,
                     Path::new(&item.path),
                     label,
-                    item.matched,
+                    None,
+                    &item.matched,
+                    item.metadata.as_ref(),
                     nomad_style,
                     target_directory,
-                ));
+                );
+
+                if let Some(size) =
+                    directory_sizes.and_then(|sizes| sizes.get(Path::new(&item.path)))
+                {
+                    formatted_directory =
+                        format!("{} {formatted_directory}", format_size(args, size));
+                }
 
-                num_directories += 1;
+                if let NomadMode::Watch(changed) = nomad_mode {
+                    if changed.contains(&item.path) {
+                        formatted_directory = format_changed(args, formatted_directory);
+                    }
+                }
+
+                let node = tree.is_documented().then(|| {
+                    export_item(args, &item, directory_label, state, target_directory, None)
+                });
+                tree.begin_node(formatted_directory, node, is_last)?;
             } else if item.is_file && !args.modifiers.dirs {
-                numbered_items.insert(format!("{num_files}"), item.path.to_string());
+                let file_number = state.number_file(&item.path);
+
+                // Symlink problems are only listed in the statistics, so they
+                // are not counted as files with a size.
+                if args.statistics {
+                    match item.link_problem {
+                        Some(link_problem) => state.add_link_problem(
+                            Path::new(&item.path),
+                            link_problem,
+                            target_directory,
+                        ),
+                        None => state.add_statistics(
+                            Path::new(&item.path),
+                            false,
+                            item.metadata.as_ref().and_then(|metadata| metadata.size),
+                            target_directory,
+                        ),
+                    }
+                }
 
                 let number = if args.labels.numbers || args.labels.all_labels {
-                    Some(num_files)
+                    Some(file_number.to_string())
                 } else {
                     None
                 };
 
                 let icon = get_file_icon(Path::new(&item.path));
 
-                if args.meta.tokei {
-                    tree.begin_child(format_content(
+                let mut formatted_content = format_content(
+                    args,
+                    item.marker.clone(),
+                    icon,
+                    Path::new(&item.path),
+                    &item.matched,
+                    item.metadata.as_ref(),
+                    nomad_style,
+                    number,
+                    target_directory,
+                );
+
+                if let Some(size) = directory_sizes
+                    .filter(|_| item.link_problem.is_none())
+                    .and_then(|sizes| sizes.get(Path::new(&item.path)))
+                {
+                    formatted_content = format!("{} {formatted_content}", format_size(args, size));
+                }
+
+                if let NomadMode::Watch(changed) = nomad_mode {
+                    if changed.contains(&item.path) {
+                        formatted_content = format_changed(args, formatted_content);
+                    }
+                }
+
+                if let Some(id) = state.duplicate_group(&item.path) {
+                    formatted_content =
+                        format!("{formatted_content} {}", format_duplicate(args, id));
+                }
+
+                if let Some(ref content_matches) = item.content_matches {
+                    formatted_content = format!(
+                        "{formatted_content} {}",
+                        format_match_count(args, content_matches.lines.len(), nomad_style)
+                    );
+                }
+
+                let matched_lines = item
+                    .content_matches
+                    .as_ref()
+                    .filter(|_| args.regex.show_lines)
+                    .map(|content_matches| &content_matches.lines);
+
+                let node = tree.is_documented().then(|| {
+                    export_item(
                         args,
-                        item.marker.clone(),
-                        icon,
-                        Path::new(&item.path),
-                        item.matched,
-                        nomad_style,
-                        number,
+                        &item,
+                        file_number.to_string(),
+                        state,
                         target_directory,
-                    ));
+                        tokei.as_ref(),
+                    )
+                });
+
+                if args.meta.tokei || matched_lines.is_some() {
+                    tree.begin_node(formatted_content, node, is_last)?;
 
+                    let mut details = Vec::new();
                     if let Some(ref tokei) = tokei {
-                        for stat in loc_in_file(args, &item.path, tokei) {
-                            tree.add_empty_child(stat);
-                        }
+                        details.extend(loc_in_file(args, &item.path, tokei));
+                    }
+                    if let Some(lines) = matched_lines {
+                        details.extend(
+                            lines
+                                .iter()
+                                .map(|line| format_matched_line(args, line, nomad_style)),
+                        );
+                    }
 
-                        tree.end_child();
+                    let num_details = details.len();
+                    for (index, detail) in details.into_iter().enumerate() {
+                        tree.add_detail(detail, index == num_details - 1)?;
                     }
+
+                    tree.end_child()?;
                 } else {
-                    tree.add_empty_child(format_content(
-                        args,
-                        item.marker.clone(),
-                        icon,
-                        Path::new(&item.path),
-                        item.matched,
-                        nomad_style,
-                        number,
-                        target_directory,
-                    ));
+                    tree.add_node(formatted_content, node, is_last)?;
                 }
-
-                num_files += 1;
             }
 
             current_depth = item.depth as usize;
-            previous_item = item;
 
             if let NomadMode::Rootless = nomad_mode {
+                // Summaries point to the directory containing the hidden items.
+                let path = if item.hidden_items.is_some() {
+                    Path::new(&item.path)
+                        .parent()
+                        .unwrap_or(Path::new(&item.path))
+                } else {
+                    Path::new(&item.path)
+                };
+
                 directory_items.push(DirItem {
                     marker: item.marker.clone(),
-                    path: Path::new(&item.path)
-                        .canonicalize()?
-                        .to_str()
-                        .unwrap_or("?")
-                        .to_string(),
+                    path: path.canonicalize()?.to_str().unwrap_or("?").to_string(),
                 })
             }
+
+            previous_item = item;
         }
 
-        store_directory_contents(labeled_items, numbered_items)?;
+        Ok(match nomad_mode {
+            NomadMode::Rootless => Some(directory_items),
+            _ => None,
+        })
+    }
+}
 
-        let final_tree = tree.build();
+/// Describe an item in the export's document. `label` is the item's directory
+/// label or file number.
+fn export_item(
+    args: &GlobalArgs,
+    item: &TransformedItem,
+    label: String,
+    state: &mut TreeState,
+    target_directory: &str,
+    tokei: Option<&Language>,
+) -> ExportNode {
+    let path = Path::new(&item.path);
+    let node_type = if item.is_dir {
+        NodeType::Directory
+    } else if path.is_symlink() {
+        NodeType::Symlink
+    } else {
+        NodeType::File
+    };
+
+    let mut node = ExportNode::new(
+        item.display_name.as_deref().unwrap_or_else(|| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("?")
+        }),
+        node_type,
+        state
+            .relative_path(path, target_directory)
+            .to_str()
+            .unwrap_or("?"),
+    );
+
+    node.git_status = item
+        .marker
+        .as_ref()
+        .and_then(|_| state.git_status(&item.path));
+    node.icon = Some(if !item.is_dir {
+        get_file_icon(path)
+    } else if item.is_empty {
+        "\u{f114}".to_string() // 
+    } else {
+        "\u{f115}".to_string() // 
+    });
+    node.label = Some(label);
+    node.metadata = (args.meta.metadata && item.link_problem.is_none())
+        .then(|| get_export_metadata(path, item.metadata.as_ref()));
+    node.tokei = tokei.and_then(|tokei| loc_counts(&item.path, tokei));
+
+    node
+}
 
-        match nomad_mode {
-            NomadMode::Normal | NomadMode::GitStatus => {
-                println!();
-                print_tree_with(&final_tree, &config)?;
+impl<I: Iterator<Item = FoundItem>> ToTree for TransformedItems<I> {
+    /// Build a tree from the `TransformedItems`.
+    fn to_tree(
+        self,
+        args: &GlobalArgs,
+        directory_sizes: Option<&DirectorySizes>,
+        export: Option<TreeExport>,
+        last_items: Option<LastItems>,
+        nomad_mode: NomadMode,
+        nomad_style: &NomadStyle,
+        target_directory: &str,
+    ) -> Result<(Option<Vec<String>>, Option<Vec<DirItem>>), NomadError> {
+        let mut items = self.peekable();
+        if items.peek().is_none() {
+            return Err(NomadError::NothingFound);
+        }
+
+        let output = match nomad_mode {
+            NomadMode::Rootless => TreeOutput::Collect,
+            _ => {
                 println!();
+                TreeOutput::Print(export)
             }
-            _ => {}
+        };
+        let mut tree = build_tree(
+            args,
+            &nomad_mode,
+            nomad_style,
+            output,
+            directory_sizes.and_then(|sizes| sizes.get(Path::new(target_directory))),
+            Path::new(target_directory),
+        )?;
+
+        let mut state = TreeState::default();
+
+        let start = Instant::now();
+        let directory_items = items.write_items(
+            args,
+            directory_sizes,
+            last_items,
+            &nomad_mode,
+            nomad_style,
+            &mut state,
+            target_directory,
+            &mut tree,
+        )?;
+
+        if args.statistics {
+            state.count_skipped(args, target_directory)?;
+        }
+
+        state.store_labels()?;
+
+        let (lines, export) = tree.finish()?;
+
+        match nomad_mode {
+            NomadMode::Rootless => {}
+            _ => println!(),
         }
 
+        if args.meta.dupes && !matches!(nomad_mode, NomadMode::Rootless) {
+            state.display_duplicates(args, nomad_style, Path::new(target_directory));
+        }
         if args.statistics {
-            let duration = start.elapsed().as_millis();
-            println!("| {num_directories} directories | {num_files} files | {duration} ms |\n");
+            state.display_statistics(
+                args,
+                start.elapsed().as_millis(),
+                nomad_style,
+                Path::new(target_directory),
+            );
         }
 
-        Ok((
-            final_tree,
-            config,
-            match nomad_mode {
-                NomadMode::Rootless => Some(directory_items),
-                _ => None,
-            },
-        ))
+        if let Some(export) = export {
+            export.display_success();
+        }
+
+        Ok((lines, directory_items))
     }
 }
 
@@ -367,10 +715,13 @@ impl ToTree for Vec<TransformedBranch> {
     fn to_tree(
         self,
         args: &GlobalArgs,
+        _: Option<&DirectorySizes>,
+        export: Option<TreeExport>,
+        _: Option<LastItems>,
         nomad_mode: NomadMode,
         nomad_style: &NomadStyle,
         target_directory: &str,
-    ) -> Result<(StringItem, PrintConfig, Option<Vec<DirItem>>), NomadError> {
+    ) -> Result<(Option<Vec<String>>, Option<Vec<DirItem>>), NomadError> {
         let labeled_items: HashMap<String, String> = HashMap::new();
         let mut numbered_items: HashMap<String, String> = HashMap::new();
 
@@ -389,31 +740,48 @@ impl ToTree for Vec<TransformedBranch> {
             upstream: None,
         };
 
-        let (config, mut tree) =
-            build_tree(args, &nomad_mode, nomad_style, Path::new(target_directory));
+        println!();
+        let mut tree = build_tree(
+            args,
+            &nomad_mode,
+            nomad_style,
+            TreeOutput::Print(export),
+            None,
+            Path::new(target_directory),
+        )?;
+
+        let last_items = find_last_items(
+            self.iter()
+                .map(|item| (item.is_parent || item.is_end).then_some(item.depth as usize)),
+        );
 
         let start = Instant::now();
-        for item in self.iter() {
+        for (item, is_last) in self.iter().zip(last_items) {
             check_nesting(
                 current_depth,
                 Path::new(&item.components.join("/")),
-                &nomad_mode,
+                item.depth as usize,
                 Path::new(&previous_item.components.join("/")),
-                target_directory,
+                Path::new(&previous_item.components.join("/")).is_dir(),
                 &mut tree,
-            );
+            )?;
+
+            let name = Path::new(&item.full_branch)
+                .file_name()
+                .unwrap_or_else(|| OsStr::new("?"))
+                .to_str()
+                .unwrap_or("?");
 
             if item.is_parent {
-                tree.begin_child(format!(
-                    "{}",
-                    Colour::Blue.bold().paint(
-                        Path::new(&item.full_branch)
-                            .file_name()
-                            .unwrap_or_else(|| OsStr::new("?"))
-                            .to_str()
-                            .unwrap_or("?")
-                    )
-                ));
+                let node = tree
+                    .is_documented()
+                    .then(|| ExportNode::new(name, NodeType::Directory, &item.full_branch));
+
+                tree.begin_node(
+                    format!("{}", Colour::Blue.bold().paint(name)),
+                    node,
+                    is_last,
+                )?;
             } else if item.is_end {
                 numbered_items.insert(format!("{num_branches}"), item.full_branch.to_string());
 
@@ -423,7 +791,12 @@ impl ToTree for Vec<TransformedBranch> {
                     None
                 };
 
-                tree.add_empty_child(format_branch(item, nomad_style, number));
+                let node = tree.is_documented().then(|| ExportNode {
+                    label: Some(num_branches.to_string()),
+                    ..ExportNode::new(name, NodeType::Branch, &item.full_branch)
+                });
+
+                tree.add_node(format_branch(item, nomad_style, number), node, is_last)?;
 
                 num_branches += 1;
             }
@@ -434,10 +807,8 @@ impl ToTree for Vec<TransformedBranch> {
 
         store_directory_contents(labeled_items, numbered_items)?;
 
-        let final_tree = tree.build();
+        let (_, export) = tree.finish()?;
 
-        println!();
-        print_tree_with(&final_tree, &config)?;
         println!();
 
         if args.statistics {
@@ -445,6 +816,10 @@ impl ToTree for Vec<TransformedBranch> {
             println!("| {num_branches} branches | {duration} ms |\n");
         }
 
-        Ok((final_tree, config, None))
+        if let Some(export) = export {
+            export.display_success();
+        }
+
+        Ok((None, None))
     }
 }
@@ -6,7 +6,8 @@ use crate::{
     style::models::NomadStyle,
     utils::{
         cache::{get_json_file, write_to_json},
-        meta::get_metadata,
+        export::{ExportNode, NodeType},
+        meta::{get_export_metadata, get_metadata},
     },
     EXTENSION_ICON_MAP, NAME_ICON_MAP,
 };
@@ -14,19 +15,34 @@ use crate::{
 use ansi_term::Colour;
 use anyhow::Result;
 use ignore::{
+    overrides::{Override, OverrideBuilder},
     types::{Types, TypesBuilder},
-    Walk, WalkBuilder,
+    DirEntry, WalkBuilder, WalkParallel, WalkState,
 };
-use ptree::{Color, PrintConfig, Style, TreeBuilder};
+use itertools::Either;
 use serde_json::{json, Value};
 
 use std::{
     collections::HashMap,
     ffi::OsStr,
-    path::{Component, Path},
+    fs,
+    path::{Path, PathBuf},
+    sync::{mpsc::channel, Arc},
 };
 
-use super::modes::NomadMode;
+use super::{
+    links::get_link_error,
+    listing::{LastItems, ListedWalk},
+    matcher::ItemMatcher,
+    models::TransformedItem,
+    modes::NomadMode,
+    sizes::{format_size, DirectorySizes},
+    sort::ItemSorter,
+    writer::{TreeOutput, TreeWriter},
+};
+
+/// The name of the files containing rules for items that are only ignored by `nomad`.
+pub const NOMAD_IGNORE_FILENAME: &str = ".nomadignore";
 
 /// Contains options for `Types` building.
 pub enum TypeOption {
@@ -74,36 +90,237 @@ pub fn build_types(
         .map_or_else(|error| Err(NomadError::IgnoreError(error)), Ok)
 }
 
-/// Build a `Walk` object based on the client's CLI parameters.
+/// Traverses the target directory.
+pub struct Walker {
+    /// The size of each item if directory sizes are displayed.
+    pub directory_sizes: Option<Arc<DirectorySizes>>,
+    /// Receives the last item displayed in each directory if the directory is
+    /// traversed on a single thread, so the position of each item is known while
+    /// it is visited.
+    pub last_items: Option<LastItems>,
+    /// The traversal itself.
+    traversal: Traversal,
+}
+
+/// Contains variants for single-threaded or parallel directory traversal.
+enum Traversal {
+    /// Traverse the directory with multiple threads. The `ItemSorter` restores
+    /// the order of a single-threaded traversal.
+    Parallel(WalkParallel, ItemSorter),
+    /// Traverse the directory on a single thread.
+    Sequential(Box<ListedWalk>),
+}
+
+impl IntoIterator for Walker {
+    type Item = Result<DirEntry, ignore::Error>;
+    type IntoIter = Box<dyn Iterator<Item = Result<DirEntry, ignore::Error>>>;
+
+    /// Iterate over the entries found by the walker. Entries found by a parallel
+    /// walker are sorted so that they are in the same order as a single-threaded
+    /// traversal. A single-threaded walker skips the items that are not displayed
+    /// with the directory sizes it still has.
+    fn into_iter(self) -> Self::IntoIter {
+        match self.traversal {
+            Traversal::Parallel(walker, sorter) => {
+                Box::new(collect_parallel(walker, &sorter).into_iter())
+            }
+            Traversal::Sequential(walker) => {
+                Box::new(walker.with_directory_sizes(self.directory_sizes))
+            }
+        }
+    }
+}
+
+/// Run a parallel walker and collect its entries in traversal order. Errors
+/// caused by symlink problems are kept so that the symlinks may be displayed.
+fn collect_parallel(
+    walker: WalkParallel,
+    sorter: &ItemSorter,
+) -> Vec<Result<DirEntry, ignore::Error>> {
+    let mut entries = run_parallel(walker);
+    entries.sort_by(|a, b| sorter.compare_paths(&a.0, &b.0));
+
+    entries
+        .into_iter()
+        .map(|(_, dir_entry)| dir_entry)
+        .collect()
+}
+
+/// Run a parallel walker and collect its entries and their paths in the order
+/// they were visited.
+fn run_parallel(walker: WalkParallel) -> Vec<(PathBuf, Result<DirEntry, ignore::Error>)> {
+    let (sender, receiver) = channel();
+
+    walker.run(|| {
+        let sender = sender.clone();
+
+        Box::new(move |dir_entry| {
+            let path = match dir_entry {
+                Ok(ref entry) => Some(entry.path().to_path_buf()),
+                Err(ref error) => get_link_error(error).map(|(path, _)| path),
+            };
+
+            if let Some(path) = path {
+                if sender.send((path, dir_entry)).is_err() {
+                    return WalkState::Quit;
+                }
+            }
+
+            WalkState::Continue
+        })
+    });
+    drop(sender);
+
+    receiver.into_iter().collect()
+}
+
+/// Build an `Override` that ignores the items matching any of the globs.
+///
+/// Globs in an `Override` whitelist the items they match, so each glob is
+/// negated to ignore them instead.
+fn build_ignore_globs(globs: &[String], target_directory: &str) -> Result<Override, NomadError> {
+    let mut builder = OverrideBuilder::new(target_directory);
+    for glob in globs {
+        builder.add(&format!("!{glob}"))?;
+    }
+
+    Ok(builder.build()?)
+}
+
+/// Configure a `WalkBuilder` based on the client's CLI parameters without
+/// limiting the depth of the traversal.
+pub fn configure_walk(
+    args: &GlobalArgs,
+    target_directory: &str,
+    types: Option<Types>,
+) -> Result<WalkBuilder, NomadError> {
+    configure_walk_at(args, Path::new(target_directory), target_directory, types)
+}
+
+/// Configure a `WalkBuilder` that starts at a directory within the tree rooted at
+/// `target_directory`. Globs and patterns are matched relative to the root of
+/// the tree, so the same items are skipped as in a traversal of the whole tree.
+pub fn configure_walk_at(
+    args: &GlobalArgs,
+    directory: &Path,
+    target_directory: &str,
+    types: Option<Types>,
+) -> Result<WalkBuilder, NomadError> {
+    let respect_ignore_files = !args.modifiers.disrespect;
+    let mut walk = WalkBuilder::new(directory);
+
+    walk.follow_links(!args.modifiers.no_follow)
+        .git_exclude(respect_ignore_files)
+        .git_global(respect_ignore_files)
+        .git_ignore(respect_ignore_files)
+        .hidden(!args.modifiers.hidden)
+        .ignore(respect_ignore_files)
+        .max_filesize(
+            args.modifiers
+                .max_filesize
+                .map(|max_filesize| max_filesize.0),
+        )
+        .parents(respect_ignore_files);
+
+    // `.nomadignore` files hide items from `nomad` without changing what
+    // Git ignores.
+    if respect_ignore_files {
+        walk.add_custom_ignore_filename(NOMAD_IGNORE_FILENAME);
+    }
+    for ignore_file in args.modifiers.ignore_file.iter() {
+        if let Some(error) = walk.add_ignore(ignore_file) {
+            return Err(NomadError::IgnoreError(error));
+        }
+    }
+    if !args.modifiers.ignore_glob.is_empty() {
+        walk.overrides(build_ignore_globs(
+            &args.modifiers.ignore_glob,
+            target_directory,
+        )?);
+    }
+
+    if let Some(types) = types {
+        walk.types(types);
+    }
+
+    let matcher = ItemMatcher::new(&args.regex)?;
+    if matcher.has_exclusions() {
+        let root = target_directory.to_string();
+
+        walk.filter_entry(move |entry| {
+            !matcher.is_excluded(entry.path().strip_prefix(&root).unwrap_or(entry.path()))
+        });
+    }
+
+    Ok(walk)
+}
+
+/// Build a `Walker` based on the client's CLI parameters.
 pub fn build_walker(
     args: &GlobalArgs,
     target_directory: &str,
     types: Option<Types>,
-) -> Result<Walk, NomadError> {
+) -> Result<Walker, NomadError> {
     if Path::new(target_directory).is_dir() {
-        let mut walk = WalkBuilder::new(target_directory);
-
-        walk.follow_links(true)
-            .git_exclude(!args.modifiers.disrespect)
-            .git_global(!args.modifiers.disrespect)
-            .git_ignore(!args.modifiers.disrespect)
-            .hidden(!args.modifiers.hidden)
-            .ignore(!args.modifiers.disrespect)
-            .max_depth(args.modifiers.max_depth)
-            .max_filesize(args.modifiers.max_filesize)
-            .parents(!args.modifiers.disrespect)
-            .sort_by_file_path(|a, b| a.cmp(b));
-
-        if let Some(types) = types {
-            walk.types(types);
-        }
+        let mut walk = configure_walk(args, target_directory, types.clone())?;
+        walk.threads(args.modifiers.threads.unwrap_or(0));
+
+        // Directory sizes include everything within the directory, so they are
+        // added up before the depth of the traversal is limited. The sizes do
+        // not depend on the order of the items, so they are added up with the
+        // same number of threads as the traversal.
+        let directory_sizes = if args.meta.du || args.meta.top.is_some() {
+            let items = if args.modifiers.parallel {
+                Either::Left(
+                    run_parallel(walk.build_parallel())
+                        .into_iter()
+                        .filter(|(_, dir_entry)| dir_entry.is_ok())
+                        .map(|(path, _)| path),
+                )
+            } else {
+                Either::Right(
+                    walk.build()
+                        .filter_map(|dir_entry| dir_entry.ok())
+                        .map(|entry| entry.into_path()),
+                )
+            };
+
+            Some(Arc::new(DirectorySizes::new(
+                args.meta.apparent_size,
+                items,
+                target_directory,
+                args.meta.top,
+            )))
+        } else {
+            None
+        };
+
+        walk.max_depth(args.modifiers.max_depth);
+
+        let sorter = ItemSorter::new(&args.sort, directory_sizes.clone(), target_directory);
+        let (last_items, traversal) = if args.modifiers.parallel {
+            (None, Traversal::Parallel(walk.build_parallel(), sorter))
+        } else {
+            let (walker, last_items) = ListedWalk::new(args, sorter, target_directory, types)?;
+
+            (Some(last_items), Traversal::Sequential(Box::new(walker)))
+        };
 
-        Ok(walk.build())
+        Ok(Walker {
+            directory_sizes,
+            last_items,
+            traversal,
+        })
     } else {
         Err(NomadError::NotADirectory(target_directory.into()))
     }
 }
 
+/// Check whether a directory does not contain anything.
+pub fn is_empty_directory(directory: &Path) -> bool {
+    fs::read_dir(directory).is_ok_and(|mut contents| contents.next().is_none())
+}
+
 /// Get the file's corresponding icon.
 pub fn get_file_icon(item_path: &Path) -> String {
     if let Some(icon) = EXTENSION_ICON_MAP.get(
@@ -127,21 +344,59 @@ pub fn get_file_icon(item_path: &Path) -> String {
     }
 }
 
-/// Build a `ptree` object and set the tree's style/configuration.
+/// Build a `TreeWriter` and write the root of the tree. The total size of the
+/// target directory is displayed next to the root if it is provided.
 pub fn build_tree(
     args: &GlobalArgs,
     nomad_mode: &NomadMode,
     nomad_style: &NomadStyle,
+    output: TreeOutput,
+    size: Option<u64>,
     target_directory: &Path,
-) -> (PrintConfig, TreeBuilder) {
-    let directory_icon = &"\u{f115}"; // 
-
-    let plain_name = target_directory
+) -> Result<TreeWriter, NomadError> {
+    let name = target_directory
         .file_name()
-        .unwrap_or_else(|| OsStr::new("?"))
+        .unwrap_or(target_directory.as_os_str())
         .to_str()
-        .unwrap_or("?")
-        .to_string();
+        .unwrap_or("?");
+    let tree_label = format_root(args, nomad_mode, name, size, target_directory);
+
+    TreeWriter::new(
+        nomad_style,
+        output,
+        tree_label,
+        Some(export_root(args, name, "", target_directory)),
+    )
+}
+
+/// Describe the root of a tree in the export's document. `path` is the path of
+/// the root relative to the root of the forest if several trees are written.
+pub fn export_root(
+    args: &GlobalArgs,
+    name: &str,
+    path: &str,
+    target_directory: &Path,
+) -> ExportNode {
+    ExportNode {
+        metadata: args
+            .meta
+            .metadata
+            .then(|| get_export_metadata(target_directory, None)),
+        ..ExportNode::new(name, NodeType::Directory, path)
+    }
+}
+
+/// Format the root of a tree, which is displayed as `name`.
+pub fn format_root(
+    args: &GlobalArgs,
+    nomad_mode: &NomadMode,
+    name: &str,
+    size: Option<u64>,
+    target_directory: &Path,
+) -> String {
+    let directory_icon = &"\u{f115}"; // 
+
+    let plain_name = name.to_string();
     let directory_name = match nomad_mode {
         NomadMode::GitBranch => format!(
             "{}{} [{}]",
@@ -167,67 +422,35 @@ pub fn build_tree(
     match nomad_mode {
         NomadMode::GitBranch => {}
         _ => {
-            if args.meta.metadata {
+            if let Some(size) = size {
+                tree_label = format!("{} {tree_label}", format_size(args, size));
+            }
+            // Roots that are not on disk, such as the root of a Git revision, do
+            // not have any metadata.
+            if args.meta.metadata && target_directory.exists() {
                 let metadata = get_metadata(args, target_directory);
                 tree_label = format!("{metadata} {tree_label}");
             }
         }
     }
 
-    let tree = TreeBuilder::new(tree_label);
-    let config = build_tree_style(nomad_style);
-
-    (config, tree)
-}
-
-/// Build a new `Style` based on the settings in `NomadStyle`.
-pub fn build_tree_style(nomad_style: &NomadStyle) -> PrintConfig {
-    let mut branch_style = Style::default();
-
-    branch_style.bold = true;
-    branch_style.foreground = Some(Color::White);
-
-    let mut config = PrintConfig::default();
-
-    config.branch = branch_style;
-    config.indent = nomad_style.tree.indent;
-    config.padding = nomad_style.tree.padding;
-
-    config.characters.down = nomad_style.tree.indent_chars.down.to_string();
-    config.characters.down_and_right = nomad_style.tree.indent_chars.down_and_right.to_string();
-    config.characters.empty = nomad_style.tree.indent_chars.empty.to_string();
-    config.characters.right = nomad_style.tree.indent_chars.right.to_string();
-    config.characters.turn_right = nomad_style.tree.indent_chars.turn_right.to_string();
-
-    config
+    tree_label
 }
 
 /// Run checks to ensure tree nesting is correct. Make any corrections if applicable.
+///
+/// The depths are the depths at which the items are displayed in the tree,
+/// which may be shallower than their depths on disk if directories are compacted.
 pub fn check_nesting(
     current_depth: usize,
     item: &Path,
-    nomad_mode: &NomadMode,
+    item_depth: usize,
     previous_item: &Path,
-    target_directory: &str,
-    tree: &mut TreeBuilder,
-) {
-    let mut item_depth = 0;
-    let item_components = match nomad_mode {
-        NomadMode::GitBranch => item.components(),
-        _ => item
-            .strip_prefix(target_directory)
-            .unwrap_or_else(|_| Path::new("?"))
-            .components(),
-    };
-
-    for component in item_components {
-        if let Component::Normal(_) = component {
-            item_depth += 1;
-        }
-    }
-
+    previous_is_dir: bool,
+    tree: &mut TreeWriter,
+) -> Result<(), NomadError> {
     if item_depth < current_depth {
-        if previous_item.is_dir() {
+        if previous_is_dir {
             let item_parent = item
                 .parent()
                 .expect("Could not get the current item's parent!");
@@ -236,16 +459,87 @@ pub fn check_nesting(
                 .expect("Could not get the previous item's parent!");
 
             if item_parent != previous_parent {
-                tree.end_child();
+                tree.end_child()?;
             }
         }
 
         for _ in 0..current_depth - item_depth {
-            tree.end_child();
+            tree.end_child()?;
+        }
+    } else if item_depth == current_depth && previous_is_dir {
+        tree.end_child()?;
+    }
+
+    Ok(())
+}
+
+/// Compact chains of directories that only contain a single directory into one
+/// item, ie. `main/java/com`. The deepest directory in each chain is kept and
+/// displayed with the names of the directories above it, and everything below
+/// the chain is moved up accordingly.
+///
+/// Files are not counted as contents if only directories are displayed.
+pub fn compact_directories(items: Vec<TransformedItem>, dirs_only: bool) -> Vec<TransformedItem> {
+    let is_displayed = |item: &TransformedItem| item.is_dir || !dirs_only;
+
+    // The number of displayed items and directories within each directory.
+    let mut contents: HashMap<PathBuf, (usize, usize)> = HashMap::new();
+    for item in items.iter().filter(|item| is_displayed(item)) {
+        if let Some(parent) = Path::new(&item.path).parent() {
+            let (num_items, num_directories) = contents.entry(parent.to_path_buf()).or_default();
+
+            *num_items += 1;
+            if item.is_dir {
+                *num_directories += 1;
+            }
         }
-    } else if item_depth == current_depth && previous_item.is_dir() {
-        tree.end_child();
     }
+
+    // The number of compacted directories above each directory, including itself.
+    let mut compacted_depths: HashMap<PathBuf, i32> = HashMap::new();
+    // The names of the compacted directories that are displayed with the next directory.
+    let mut chain_names: HashMap<PathBuf, String> = HashMap::new();
+
+    let mut compacted = Vec::new();
+    for mut item in items {
+        let path = PathBuf::from(&item.path);
+        let parent = path.parent().unwrap_or_else(|| Path::new(""));
+        let compacted_depth = compacted_depths.get(parent).copied().unwrap_or(0);
+
+        item.depth -= compacted_depth;
+
+        if !item.is_dir {
+            compacted.push(item);
+            continue;
+        }
+
+        let name = match chain_names.get(parent) {
+            Some(chain_name) => format!(
+                "{chain_name}/{}",
+                path.file_name().and_then(OsStr::to_str).unwrap_or("?")
+            ),
+            None => path
+                .file_name()
+                .and_then(OsStr::to_str)
+                .unwrap_or("?")
+                .to_string(),
+        };
+
+        if contents.get(&path) == Some(&(1, 1)) {
+            compacted_depths.insert(path.clone(), compacted_depth + 1);
+            chain_names.insert(path, name);
+        } else {
+            compacted_depths.insert(path, compacted_depth);
+
+            if name.contains('/') {
+                item.display_name = Some(name);
+            }
+
+            compacted.push(item);
+        }
+    }
+
+    compacted
 }
 
 /// Write the labeled directories or numbered directory contents to a temporary file.
@@ -273,3 +567,114 @@ fn write_map(items: HashMap<String, String>, json: &mut Value, target_key: &str)
             .insert(key.clone(), json!(value.clone()));
     }
 }
+
+#[cfg(test)]
+mod test_utils {
+    use super::*;
+
+    use crate::utils::test_utils::create_fixture;
+
+    use structopt::StructOpt;
+
+    fn get_item(path: &str, is_dir: bool) -> TransformedItem {
+        let components = path
+            .split('/')
+            .map(|component| component.to_string())
+            .collect::<Vec<String>>();
+
+        TransformedItem {
+            content_matches: None,
+            depth: components.len() as i32,
+            display_name: None,
+            hidden_items: None,
+            is_dir,
+            is_empty: false,
+            is_file: !is_dir,
+            link_problem: None,
+            marker: None,
+            matched: Vec::new(),
+            metadata: None,
+            path: format!("/root/{path}"),
+            components,
+        }
+    }
+
+    #[test]
+    fn test_compact_directories() {
+        let items = compact_directories(
+            vec![
+                get_item("src", true),
+                get_item("src/main", true),
+                get_item("src/main/java", true),
+                get_item("src/main/java/App.java", false),
+                get_item("src/main/java/util", true),
+                get_item("src/main/java/util/Strings.java", false),
+                get_item("README.md", false),
+            ],
+            false,
+        );
+
+        assert_eq!(
+            items
+                .iter()
+                .map(|item| (item.display_name.clone(), item.depth, item.path.as_str()))
+                .collect::<Vec<(Option<String>, i32, &str)>>(),
+            vec![
+                (Some("src/main/java".to_string()), 1, "/root/src/main/java"),
+                (None, 2, "/root/src/main/java/App.java"),
+                (None, 2, "/root/src/main/java/util"),
+                (None, 3, "/root/src/main/java/util/Strings.java"),
+                (None, 1, "/root/README.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ignore_rules() {
+        let fixture = create_fixture(&[
+            ("generated/schema.rs", ""),
+            ("main.rs", ""),
+            ("notes.log", ""),
+            ("app.min.js", ""),
+            (NOMAD_IGNORE_FILENAME, "generated/\n"),
+        ]);
+        let rules = create_fixture(&[("extra.ignore", "*.log\n")]);
+        let extra_rules = rules.path().join("extra.ignore");
+
+        let target = fixture.path().to_str().unwrap();
+        let find_items = |flags: &[&str]| {
+            let args = GlobalArgs::from_iter(["nd"].iter().chain(flags));
+            let mut items = build_walker(&args, target, None)
+                .unwrap()
+                .into_iter()
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.depth() > 0)
+                .map(|entry| entry.file_name().to_str().unwrap().to_string())
+                .collect::<Vec<String>>();
+            items.sort();
+
+            items
+        };
+
+        assert_eq!(find_items(&[]), vec!["app.min.js", "main.rs", "notes.log"]);
+        assert_eq!(
+            find_items(&[
+                "--ignore-file",
+                extra_rules.to_str().unwrap(),
+                "--ignore-glob",
+                "*.min.js"
+            ]),
+            vec!["main.rs"]
+        );
+        assert_eq!(
+            find_items(&["--disrespect"]),
+            vec![
+                "app.min.js",
+                "generated",
+                "main.rs",
+                "notes.log",
+                "schema.rs"
+            ]
+        );
+    }
+}
@@ -0,0 +1,325 @@
+//! Find items that are not on disk, such as the members of an archive.
+
+use super::{
+    matcher::ItemMatcher,
+    models::{FoundItem, ItemMetadata},
+};
+use crate::{
+    cli::global::{GlobalArgs, SortBy},
+    errors::NomadError,
+};
+
+use anyhow::Result;
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashSet},
+    path::Path,
+};
+
+/// Contains an item that is not on disk.
+#[derive(Debug)]
+pub struct VirtualItem {
+    /// Indicates whether this is a directory.
+    pub is_dir: bool,
+    /// The metadata stored for the item.
+    pub metadata: ItemMetadata,
+    /// The path to the item relative to the root of the tree, separated by `/`.
+    pub path: String,
+}
+
+/// Filter and sort items that are not on disk like the items of a directory
+/// traversal. The items are converted into `FoundItem`s below `target_directory`
+/// so that they may be displayed with the standard tree.
+///
+/// Directories that are not listed themselves are added for the items within
+/// them. Filters that read the items' metadata from disk, such as `--user` or
+/// `--changed-within`, are not applied.
+pub fn find_virtual_items(
+    args: &GlobalArgs,
+    items: Vec<VirtualItem>,
+    target_directory: &str,
+) -> Result<Vec<FoundItem>, NomadError> {
+    let matcher = ItemMatcher::new(&args.regex)?;
+
+    // Later items replace earlier items with the same path, which matches how
+    // archives are extracted.
+    let mut items = items
+        .into_iter()
+        .filter_map(|item| {
+            let path = item
+                .path
+                .trim_start_matches("./")
+                .trim_matches('/')
+                .to_string();
+
+            (!path.is_empty()).then_some((path, item))
+        })
+        .filter(|(path, _)| {
+            let relative_path = Path::new(path);
+
+            (args.modifiers.hidden || !path.split('/').any(|component| component.starts_with('.')))
+                && args
+                    .modifiers
+                    .max_depth
+                    .is_none_or(|max_depth| relative_path.components().count() <= max_depth)
+                && !matcher.is_excluded(relative_path)
+        })
+        .collect::<BTreeMap<String, VirtualItem>>();
+
+    let mut directories = HashSet::new();
+    for (path, item) in items.iter() {
+        if item.is_dir {
+            directories.insert(path.to_string());
+        }
+
+        let mut parent = Path::new(path).parent();
+        while let Some(directory) = parent.filter(|directory| !directory.as_os_str().is_empty()) {
+            directories.insert(directory.to_str().unwrap_or("?").to_string());
+            parent = directory.parent();
+        }
+    }
+
+    let is_empty = |path: &str| {
+        !items
+            .keys()
+            .any(|other| other.len() > path.len() && other.starts_with(&format!("{path}/")))
+    };
+
+    let mut found = items
+        .iter()
+        .filter_map(|(path, item)| {
+            let relative_path = Path::new(path);
+            let is_dir = directories.contains(path);
+            let is_empty = is_dir && is_empty(path);
+            let size = item.metadata.size.unwrap_or(0);
+
+            if !is_dir
+                && (args
+                    .modifiers
+                    .max_filesize
+                    .is_some_and(|max_filesize| size > max_filesize.0)
+                    || args
+                        .modifiers
+                        .min_filesize
+                        .is_some_and(|min_filesize| size < min_filesize.0))
+            {
+                return None;
+            }
+            if is_empty && args.modifiers.min_filesize.is_some() {
+                return None;
+            }
+
+            let matched = if matcher.has_patterns() {
+                let directory_match = if is_dir && args.regex.match_dirs {
+                    matcher.find_in_name(relative_path)
+                } else {
+                    None
+                };
+
+                match directory_match {
+                    Some(matched) => matched,
+                    // Directories containing items are displayed if any of
+                    // their items match.
+                    None if is_dir && !is_empty => Vec::new(),
+                    None => match matcher.find(relative_path) {
+                        Some(matched) => matched,
+                        None if args.regex.match_dirs
+                            && matcher.in_matched_directory(relative_path) =>
+                        {
+                            Vec::new()
+                        }
+                        None => return None,
+                    },
+                }
+            } else {
+                Vec::new()
+            };
+
+            Some((path.to_string(), is_dir, is_empty, matched))
+        })
+        .collect::<Vec<(String, bool, bool, Vec<(usize, usize)>)>>();
+
+    // Directories containing items are only displayed if any of their items are
+    // displayed.
+    let displayed = found
+        .iter()
+        .filter(|(_, is_dir, is_empty, _)| !is_dir || *is_empty)
+        .map(|(path, ..)| path.to_string())
+        .collect::<Vec<String>>();
+    found.retain(|(path, is_dir, is_empty, _)| {
+        !is_dir
+            || *is_empty
+            || displayed
+                .iter()
+                .any(|other| other.starts_with(&format!("{path}/")))
+    });
+
+    found.sort_by(|(a, ..), (b, ..)| compare_paths(args, &directories, &items, a, b));
+
+    // Directories are highlighted with the matches of the first item within them.
+    for index in (0..found.len()).rev() {
+        let (ref path, is_dir, is_empty, ref matched) = found[index];
+        if is_dir && !is_empty && matched.is_empty() {
+            if let Some((next_path, _, _, next_matched)) = found.get(index + 1) {
+                if next_path.starts_with(&format!("{path}/")) {
+                    found[index].3 = next_matched.clone();
+                }
+            }
+        }
+    }
+
+    Ok(found
+        .into_iter()
+        .map(|(path, is_dir, is_empty, matched)| FoundItem {
+            content_matches: None,
+            is_dir,
+            is_empty,
+            link_problem: None,
+            marker: None,
+            matched,
+            metadata: Some(
+                items
+                    .remove(&path)
+                    .map_or_else(ItemMetadata::default, |item| item.metadata),
+            ),
+            path: Path::new(target_directory)
+                .join(&path)
+                .to_str()
+                .unwrap_or("?")
+                .to_string(),
+        })
+        .collect())
+}
+
+/// Compare two items anywhere in the tree. Each directory is placed before its
+/// contents and the items within a directory are sorted by the client's sort
+/// options, which matches the order of a sorted traversal.
+fn compare_paths(
+    args: &GlobalArgs,
+    directories: &HashSet<String>,
+    items: &BTreeMap<String, VirtualItem>,
+    a: &str,
+    b: &str,
+) -> Ordering {
+    let a_components = a.split('/').collect::<Vec<&str>>();
+    let b_components = b.split('/').collect::<Vec<&str>>();
+
+    let shared = a_components
+        .iter()
+        .zip(b_components.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    if shared == a_components.len() || shared == b_components.len() {
+        return a_components.len().cmp(&b_components.len());
+    }
+
+    let a_sibling = a_components[..=shared].join("/");
+    let b_sibling = b_components[..=shared].join("/");
+
+    if args.sort.dirs_first {
+        let directory_order = directories
+            .contains(&b_sibling)
+            .cmp(&directories.contains(&a_sibling));
+        if directory_order != Ordering::Equal {
+            return directory_order;
+        }
+    }
+
+    let get_metadata = |path: &str| items.get(path).map(|item| &item.metadata);
+    let order = match args.sort.sort_by {
+        SortBy::Extension => Path::new(&a_sibling)
+            .extension()
+            .cmp(&Path::new(&b_sibling).extension())
+            .then_with(|| a_components[shared].cmp(b_components[shared])),
+        SortBy::Modified => get_metadata(&b_sibling)
+            .and_then(|metadata| metadata.modified)
+            .cmp(&get_metadata(&a_sibling).and_then(|metadata| metadata.modified)),
+        SortBy::Name | SortBy::Status => a_components[shared].cmp(b_components[shared]),
+        SortBy::Size => get_metadata(&b_sibling)
+            .and_then(|metadata| metadata.size)
+            .cmp(&get_metadata(&a_sibling).and_then(|metadata| metadata.size)),
+    }
+    .then_with(|| a_sibling.cmp(&b_sibling));
+
+    if args.sort.reverse {
+        order.reverse()
+    } else {
+        order
+    }
+}
+
+#[cfg(test)]
+mod test_virtual_items {
+    use super::*;
+
+    use structopt::StructOpt;
+
+    fn get_items() -> Vec<VirtualItem> {
+        [
+            ("./src/main.rs", false, 120),
+            ("src/", true, 0),
+            (".hidden", false, 1),
+            ("README.md", false, 4000),
+            ("docs/empty/", true, 0),
+            ("src/utils/paths.rs", false, 80),
+        ]
+        .into_iter()
+        .map(|(path, is_dir, size)| VirtualItem {
+            is_dir,
+            metadata: ItemMetadata {
+                mode: None,
+                modified: None,
+                size: Some(size),
+            },
+            path: path.to_string(),
+        })
+        .collect()
+    }
+
+    fn find_paths(flags: &[&str]) -> Vec<String> {
+        find_virtual_items(
+            &GlobalArgs::from_iter(["nd"].iter().chain(flags)),
+            get_items(),
+            "/archive.tar",
+        )
+        .unwrap()
+        .into_iter()
+        .map(|item| item.path)
+        .collect()
+    }
+
+    #[test]
+    fn test_find_virtual_items() {
+        assert_eq!(
+            find_paths(&[]),
+            vec![
+                "/archive.tar/README.md",
+                "/archive.tar/docs/empty",
+                "/archive.tar/src",
+                "/archive.tar/src/main.rs",
+                "/archive.tar/src/utils/paths.rs",
+            ]
+        );
+        assert_eq!(
+            find_paths(&["--dirs-first", "--hidden"]),
+            vec![
+                "/archive.tar/docs/empty",
+                "/archive.tar/src",
+                "/archive.tar/src/utils/paths.rs",
+                "/archive.tar/src/main.rs",
+                "/archive.tar/.hidden",
+                "/archive.tar/README.md",
+            ]
+        );
+        assert_eq!(
+            find_paths(&["-p", "paths"]),
+            vec!["/archive.tar/src", "/archive.tar/src/utils/paths.rs"]
+        );
+        assert_eq!(
+            find_paths(&["--min-filesize", "1k"]),
+            vec!["/archive.tar/README.md"]
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! Redraw the tree whenever the items within the target directory change.
+
+use super::{modes::NomadMode, utils::build_walker, walk_directory};
+use crate::{
+    cli::global::GlobalArgs, errors::NomadError, style::models::NomadStyle,
+    utils::paint::paint_error,
+};
+
+use ansi_term::Style;
+use anyhow::Result;
+use chrono::Local;
+use crossterm::{
+    cursor::MoveTo,
+    execute,
+    terminal::{Clear, ClearType},
+};
+use git2::Repository;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::stdout,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// The time to wait between checking the items for changes. The items are only
+/// checked if the file system reported events since the last check, unless the
+/// directory cannot be watched.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The time the items have to stay unchanged before the tree is redrawn.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// The time changed items stay highlighted.
+const HIGHLIGHT_DURATION: Duration = Duration::from_secs(2);
+
+/// The size and modified time of each item.
+type Fingerprint = HashMap<PathBuf, (u64, Option<SystemTime>)>;
+
+/// Checks a directory for changes to its items.
+///
+/// Changes are debounced, so they are only reported once the items have not
+/// changed for a short time. This avoids redrawing the tree for every single
+/// file that is written while a build or checkout is running.
+struct ChangeDetector {
+    /// The items when the changes were last reported.
+    fingerprint: Fingerprint,
+    /// The items that changed since the last report, and the time they last changed.
+    pending: Option<(Fingerprint, Instant)>,
+}
+
+impl ChangeDetector {
+    /// Create a new `ChangeDetector` for the target directory.
+    fn new(args: &GlobalArgs, target_directory: &str) -> Self {
+        Self {
+            fingerprint: get_fingerprint(args, target_directory),
+            pending: None,
+        }
+    }
+
+    /// Check whether changes were found that have not settled yet.
+    fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Check the directory for changes. Returns the paths of the items that
+    /// were created or modified once the changes have settled.
+    fn check(&mut self, args: &GlobalArgs, target_directory: &str) -> Option<HashSet<String>> {
+        let current = get_fingerprint(args, target_directory);
+        if current == self.fingerprint {
+            self.pending = None;
+            return None;
+        }
+
+        match self.pending.take() {
+            Some((pending, changed_at)) if pending == current => {
+                if changed_at.elapsed() < DEBOUNCE {
+                    self.pending = Some((pending, changed_at));
+                    return None;
+                }
+
+                let changed = current
+                    .iter()
+                    .filter(|(path, state)| self.fingerprint.get(*path) != Some(state))
+                    .filter_map(|(path, _)| path.to_str().map(|path| path.to_string()))
+                    .collect();
+
+                self.fingerprint = current;
+
+                Some(changed)
+            }
+            _ => {
+                self.pending = Some((current, Instant::now()));
+                None
+            }
+        }
+    }
+}
+
+/// Watches a directory for changes to its items.
+///
+/// The file system reports events within the directory, but the events do not
+/// respect the ignore rules and the other filters of the tree. The directory is
+/// therefore walked to find the changed items after events were reported. This
+/// is done on a background thread that sends the changed items over a channel.
+/// The thread stops once the watcher is dropped.
+pub struct DirectoryWatcher {
+    /// The arguments the items are found with.
+    args: GlobalArgs,
+    /// Receives the items that changed whenever the changes have settled.
+    changes: Receiver<HashSet<String>>,
+    /// The directory whose items are watched.
+    directory: String,
+    /// Disconnects from the background thread when it is dropped, which stops it.
+    _stop: Sender<()>,
+}
+
+impl DirectoryWatcher {
+    /// Create a new `DirectoryWatcher` for the target directory and start
+    /// checking it for changes.
+    pub fn new(args: &GlobalArgs, target_directory: &str) -> Self {
+        let (changes_sender, changes) = mpsc::channel();
+        let (stop, stop_receiver) = mpsc::channel::<()>();
+
+        let thread_args = args.clone();
+        let directory = target_directory.to_string();
+        thread::spawn(move || {
+            let (events_sender, events) = mpsc::channel();
+            let event_watcher = watch_events(&directory, events_sender);
+            let mut detector = ChangeDetector::new(&thread_args, &directory);
+
+            // The directory is checked at every interval if it cannot be watched.
+            let mut is_outdated = event_watcher.is_none();
+            while let Err(RecvTimeoutError::Timeout) = stop_receiver.recv_timeout(POLL_INTERVAL) {
+                is_outdated |= events.try_iter().count() > 0;
+                if !is_outdated {
+                    continue;
+                }
+
+                if let Some(changed) = detector.check(&thread_args, &directory) {
+                    if changes_sender.send(changed).is_err() {
+                        break;
+                    }
+                }
+
+                is_outdated = event_watcher.is_none() || detector.is_pending();
+            }
+        });
+
+        Self {
+            args: args.clone(),
+            changes,
+            directory: target_directory.to_string(),
+            _stop: stop,
+        }
+    }
+
+    /// Get the paths of the items that were created or modified since the
+    /// last call. Returns `None` if nothing changed. This does not block.
+    ///
+    /// The watcher starts over without reporting anything if a different
+    /// directory or different arguments are provided.
+    pub fn poll(&mut self, args: &GlobalArgs, target_directory: &str) -> Option<HashSet<String>> {
+        if self.directory != target_directory || self.args != *args {
+            *self = Self::new(args, target_directory);
+            return None;
+        }
+
+        self.changes.try_iter().reduce(|mut changed, more| {
+            changed.extend(more);
+            changed
+        })
+    }
+}
+
+/// Start watching the target directory for file system events. Git's directory
+/// is watched as well if the target directory is within a repository. Each
+/// event, or error such as a dropped event, is sent over the channel.
+///
+/// Returns `None` if the directory cannot be watched, ie. if the system's limit
+/// of watches was reached.
+fn watch_events(target_directory: &str, events: Sender<()>) -> Option<RecommendedWatcher> {
+    let mut watcher = notify::recommended_watcher(move |_: notify::Result<Event>| {
+        let _ = events.send(());
+    })
+    .ok()?;
+
+    watcher
+        .watch(Path::new(target_directory), RecursiveMode::Recursive)
+        .ok()?;
+    if let Ok(repo) = Repository::discover(target_directory) {
+        let _ = watcher.watch(repo.path(), RecursiveMode::NonRecursive);
+    }
+
+    Some(watcher)
+}
+
+/// Get the size and modified time of each item in the tree. Only the paths of
+/// directories are recorded since their modified times change whenever an item
+/// is added to or removed from them. Git's index and
+/// `HEAD` are included so that the Git markers are updated after staging or
+/// committing changes.
+///
+/// Directory sizes are not needed to find the items, so they are not calculated
+/// even if `--du` or `--top` is used.
+fn get_fingerprint(args: &GlobalArgs, target_directory: &str) -> Fingerprint {
+    let mut args = args.clone();
+    args.meta.du = false;
+    args.meta.top = None;
+
+    let mut paths = match build_walker(&args, target_directory, None) {
+        Ok(walker) => walker
+            .into_iter()
+            .filter_map(|dir_entry| dir_entry.ok())
+            .map(|entry| entry.into_path())
+            .collect::<Vec<PathBuf>>(),
+        Err(_) => Vec::new(),
+    };
+
+    if let Ok(repo) = Repository::discover(target_directory) {
+        paths.push(repo.path().join("index"));
+        paths.push(repo.path().join("HEAD"));
+    }
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let state = fs::metadata(&path)
+                .ok()
+                .filter(|metadata| !metadata.is_dir())
+                .map_or((0, None), |metadata| {
+                    (metadata.len(), metadata.modified().ok())
+                });
+
+            (path, state)
+        })
+        .collect()
+}
+
+/// Clear the screen and draw the tree. Items in `changed` are highlighted.
+fn draw_tree(
+    args: &GlobalArgs,
+    changed: HashSet<String>,
+    nomad_style: &NomadStyle,
+    target_directory: &str,
+) -> Result<(), NomadError> {
+    execute!(
+        stdout(),
+        Clear(ClearType::All),
+        Clear(ClearType::Purge),
+        MoveTo(0, 0)
+    )?;
+
+    let status = format!(
+        "Watching {target_directory} | Last updated {} | Press Ctrl+C to stop",
+        Local::now().format("%H:%M:%S")
+    );
+    if args.style.plain || args.style.no_colors {
+        println!("{status}");
+    } else {
+        println!("{}", Style::new().dimmed().paint(status));
+    }
+
+    let walker = build_walker(args, target_directory, None)?;
+    walk_directory(
+        args,
+        None,
+        NomadMode::Watch(changed),
+        nomad_style,
+        target_directory,
+        walker,
+    )?;
+
+    Ok(())
+}
+
+/// Draw the tree and redraw it whenever the items within the target directory
+/// change. Items that were created or modified are highlighted for a short time.
+///
+/// This runs until `nomad` is interrupted.
+pub fn watch_directory(
+    args: &GlobalArgs,
+    nomad_style: &NomadStyle,
+    target_directory: &str,
+) -> Result<(), NomadError> {
+    let mut watcher = DirectoryWatcher::new(args, target_directory);
+    let mut highlighted_at: Option<Instant> = None;
+
+    if let Err(error) = draw_tree(args, HashSet::new(), nomad_style, target_directory) {
+        paint_error(error);
+    }
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        if let Some(changed) = watcher.poll(args, target_directory) {
+            highlighted_at = (!changed.is_empty()).then(Instant::now);
+
+            if let Err(error) = draw_tree(args, changed, nomad_style, target_directory) {
+                paint_error(error);
+            }
+        } else if highlighted_at.is_some_and(|time| time.elapsed() >= HIGHLIGHT_DURATION) {
+            highlighted_at = None;
+
+            if let Err(error) = draw_tree(args, HashSet::new(), nomad_style, target_directory) {
+                paint_error(error);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_watch {
+    use super::*;
+
+    use crate::utils::test_utils::create_fixture;
+
+    use structopt::StructOpt;
+
+    #[test]
+    fn test_change_detector() {
+        let fixture = create_fixture(&[("kept.txt", "")]);
+        let target_directory = fixture.path();
+
+        let args = GlobalArgs::from_iter(["nd"]);
+        let root = target_directory.to_str().unwrap();
+
+        let mut detector = ChangeDetector::new(&args, root);
+        assert_eq!(detector.check(&args, root), None);
+
+        let added = target_directory.join("added.txt");
+        fs::write(&added, "new").unwrap();
+
+        // The change is only reported once it has settled.
+        assert_eq!(detector.check(&args, root), None);
+        thread::sleep(DEBOUNCE);
+        assert_eq!(
+            detector.check(&args, root),
+            Some(HashSet::from([added.to_str().unwrap().to_string()]))
+        );
+        assert_eq!(detector.check(&args, root), None);
+    }
+
+    #[test]
+    fn test_directory_watcher() {
+        let fixture = create_fixture(&[]);
+        let target_directory = fixture.path();
+
+        let args = GlobalArgs::from_iter(["nd"]);
+        let root = target_directory.to_str().unwrap();
+
+        let mut watcher = DirectoryWatcher::new(&args, root);
+        thread::sleep(POLL_INTERVAL * 2);
+        assert_eq!(watcher.poll(&args, root), None);
+
+        let added = target_directory.join("added.txt");
+        fs::write(&added, "new").unwrap();
+
+        // The changes are found in the background, so polling never waits for them.
+        let started = Instant::now();
+        let mut changed = None;
+        while changed.is_none() && started.elapsed() < Duration::from_secs(10) {
+            thread::sleep(POLL_INTERVAL);
+            changed = watcher.poll(&args, root);
+        }
+        assert_eq!(
+            changed,
+            Some(HashSet::from([added.to_str().unwrap().to_string()]))
+        );
+    }
+}
@@ -0,0 +1,397 @@
+//! Streaming tree output - writes each line as soon as its position in the tree is known.
+
+use crate::{
+    errors::NomadError,
+    style::models::NomadStyle,
+    utils::export::{ExportDocument, ExportNode, TreeExport},
+};
+
+use ansi_term::{Colour, Style};
+use anyhow::Result;
+use crossterm::tty::IsTty;
+
+use std::io::{stdout, Write};
+
+/// Contains the prefixes that are drawn in front of each tree item.
+struct Indent {
+    /// The prefix for an item that has siblings below it.
+    regular_prefix: String,
+    /// The prefix drawn for the children of an item that has siblings below it.
+    child_prefix: String,
+    /// The prefix for the last item in a directory.
+    last_regular_prefix: String,
+    /// The prefix drawn for the children of the last item in a directory.
+    last_child_prefix: String,
+}
+
+impl Indent {
+    /// Build the prefixes from the indent, padding, and indent characters in `NomadStyle`.
+    fn new(nomad_style: &NomadStyle) -> Self {
+        let characters = &nomad_style.tree.indent_chars;
+        let padding = nomad_style.tree.padding;
+
+        let width = if nomad_style.tree.indent > padding + 1 {
+            nomad_style.tree.indent - padding - 1
+        } else {
+            0
+        };
+
+        let right_pad = characters.right.repeat(width);
+        let empty_pad = characters.empty.repeat(width);
+        let item_pad = characters.empty.repeat(padding);
+
+        Self {
+            regular_prefix: format!("{}{right_pad}{item_pad}", characters.down_and_right),
+            child_prefix: format!("{}{empty_pad}{item_pad}", characters.down),
+            last_regular_prefix: format!("{}{right_pad}{item_pad}", characters.turn_right),
+            last_child_prefix: format!("{}{empty_pad}{item_pad}", characters.empty),
+        }
+    }
+}
+
+/// Variants for where the tree's lines are written to.
+pub enum TreeOutput {
+    /// Collect each line of the tree. This is used in Rootless mode.
+    Collect,
+    /// Print the tree to `stdout`. Also streams the tree into an export file if
+    /// one is provided.
+    Print(Option<TreeExport>),
+}
+
+/// Writes a tree line by line while it is being built.
+///
+/// Items are added with the same calls as a `TreeBuilder` (`begin_child()`,
+/// `add_empty_child()`, and `end_child()`), along with whether the item is the
+/// last item in its directory. The prefix of an item only depends on its own
+/// position and the positions of its parent directories, so each line is written
+/// as soon as the item is added.
+///
+/// If the tree is exported as JSON or HTML, each item is also added to the export's
+/// document. `begin_node()` and `add_node()` describe the item in the document,
+/// otherwise the item is added as text. Lines added with `add_detail()` are
+/// only displayed.
+pub struct TreeWriter {
+    /// The prefix style applied to the indent characters when printing to a terminal.
+    branch_style: Option<Style>,
+    /// Holds the lines of the tree if the tree is collected.
+    collected: Option<Vec<String>>,
+    /// The export file the tree is streamed into, if applicable.
+    export: Option<TreeExport>,
+    /// The prefixes drawn in front of each item.
+    indent: Indent,
+    /// The prefixes drawn in front of the children of each open directory,
+    /// starting with the root of the tree.
+    parent_prefixes: Vec<String>,
+    /// Indicates whether the tree is printed to `stdout`.
+    print: bool,
+}
+
+impl TreeWriter {
+    /// Create a new `TreeWriter` and write the root of the tree. `root_node`
+    /// describes the root in the export's document if it is provided.
+    pub fn new(
+        nomad_style: &NomadStyle,
+        output: TreeOutput,
+        root: String,
+        root_node: Option<ExportNode>,
+    ) -> Result<Self, NomadError> {
+        let (collected, export, print) = match output {
+            TreeOutput::Collect => (Some(Vec::new()), None, false),
+            TreeOutput::Print(export) => (None, export, true),
+        };
+
+        let mut tree_writer = Self {
+            branch_style: if print && stdout().is_tty() {
+                Some(Colour::White.bold())
+            } else {
+                None
+            },
+            collected,
+            export,
+            indent: Indent::new(nomad_style),
+            parent_prefixes: vec!["".to_string()],
+            print,
+        };
+
+        if let Some(document) = tree_writer.document() {
+            document.set_style(nomad_style);
+            document.begin(root_node.unwrap_or_else(|| ExportNode::text(&root)));
+        }
+
+        tree_writer.write_line("", &root)?;
+
+        Ok(tree_writer)
+    }
+
+    /// Check whether the tree is exported into a document, in which case items
+    /// should be described with `begin_node()` and `add_node()`.
+    pub fn is_documented(&self) -> bool {
+        self.export
+            .as_ref()
+            .is_some_and(|export| export.document.is_some())
+    }
+
+    /// Add an item to the current directory, then make it the current directory.
+    pub fn begin_child(&mut self, text: String, is_last: bool) -> Result<(), NomadError> {
+        self.begin_node(text, None, is_last)
+    }
+
+    /// Add an item to the current directory, then make it the current directory.
+    /// `node` describes the item in the export's document if it is provided.
+    pub fn begin_node(
+        &mut self,
+        text: String,
+        node: Option<ExportNode>,
+        is_last: bool,
+    ) -> Result<(), NomadError> {
+        if let Some(document) = self.document() {
+            document.begin(node.unwrap_or_else(|| ExportNode::text(&text)));
+        }
+
+        let child_prefix = self.add_item(&text, is_last)?;
+        self.parent_prefixes.push(child_prefix);
+
+        Ok(())
+    }
+
+    /// Add an item without children to the current directory.
+    pub fn add_empty_child(&mut self, text: String, is_last: bool) -> Result<(), NomadError> {
+        self.add_node(text, None, is_last)
+    }
+
+    /// Add an item without children to the current directory. `node` describes
+    /// the item in the export's document if it is provided.
+    pub fn add_node(
+        &mut self,
+        text: String,
+        node: Option<ExportNode>,
+        is_last: bool,
+    ) -> Result<(), NomadError> {
+        if let Some(document) = self.document() {
+            document.add(node.unwrap_or_else(|| ExportNode::text(&text)));
+        }
+
+        self.add_detail(text, is_last)
+    }
+
+    /// Add a line below the current item that is left out of the export's
+    /// document, such as a file's `tokei` counts.
+    pub fn add_detail(&mut self, text: String, is_last: bool) -> Result<(), NomadError> {
+        self.add_item(&text, is_last)?;
+
+        Ok(())
+    }
+
+    /// Close the current directory and move back up to its parent.
+    pub fn end_child(&mut self) -> Result<(), NomadError> {
+        if self.parent_prefixes.len() > 1 {
+            if let Some(document) = self.document() {
+                document.end();
+            }
+
+            self.parent_prefixes.pop();
+        }
+
+        Ok(())
+    }
+
+    /// Close directories until only `depth` directories below the root of the
+    /// tree are open.
+    pub fn end_children(&mut self, depth: usize) -> Result<(), NomadError> {
+        while self.parent_prefixes.len() > depth + 1 {
+            self.end_child()?;
+        }
+
+        Ok(())
+    }
+
+    /// Close all open directories and finish the export. Returns the lines of the
+    /// tree if it was collected and the export file if the tree was exported.
+    pub fn finish(mut self) -> Result<(Option<Vec<String>>, Option<TreeExport>), NomadError> {
+        self.end_children(0)?;
+
+        if let Some(ref mut export) = self.export {
+            export.write_document()?;
+            export.file.flush()?;
+        }
+
+        Ok((self.collected, self.export))
+    }
+
+    /// Get the export's document if the tree is exported into one.
+    fn document(&mut self) -> Option<&mut ExportDocument> {
+        self.export
+            .as_mut()
+            .and_then(|export| export.document.as_mut())
+    }
+
+    /// Write an item in the current directory. Returns the prefix drawn in front
+    /// of the item's children.
+    fn add_item(&mut self, text: &str, is_last: bool) -> Result<String, NomadError> {
+        let parent_prefix = self
+            .parent_prefixes
+            .last()
+            .map_or("", |prefix| prefix.as_str());
+
+        let (prefix, child_prefix) = if is_last {
+            (
+                &self.indent.last_regular_prefix,
+                &self.indent.last_child_prefix,
+            )
+        } else {
+            (&self.indent.regular_prefix, &self.indent.child_prefix)
+        };
+
+        let prefix = format!("{parent_prefix}{prefix}");
+        let child_prefix = format!("{parent_prefix}{child_prefix}");
+
+        self.write_line(&prefix, text)?;
+
+        Ok(child_prefix)
+    }
+
+    /// Write a single line to each output.
+    fn write_line(&mut self, prefix: &str, text: &str) -> Result<(), NomadError> {
+        if self.print {
+            let painted_prefix = match self.branch_style {
+                Some(style) => style.paint(prefix).to_string(),
+                None => prefix.to_string(),
+            };
+
+            writeln!(stdout().lock(), "{painted_prefix}{text}")?;
+        }
+        if let Some(export) = self
+            .export
+            .as_mut()
+            .filter(|export| export.document.is_none())
+        {
+            writeln!(export.file, "{prefix}{text}")?;
+        }
+        if let Some(ref mut collected) = self.collected {
+            collected.push(format!("{prefix}{text}"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Find whether each item is the last item in its directory from the depth of
+/// each item, in the order the items are added to the tree. Items without a
+/// depth are not added to the tree, so they are skipped.
+pub fn find_last_items<I: IntoIterator<Item = Option<usize>>>(depths: I) -> Vec<bool> {
+    let depths = depths.into_iter().collect::<Vec<Option<usize>>>();
+
+    // Whether an item at each depth was found below the current item, going
+    // from the bottom of the tree to the top. Finding an item closes every
+    // directory deeper than it.
+    let mut has_next_sibling: Vec<bool> = Vec::new();
+    let mut last_items = vec![false; depths.len()];
+
+    for (index, depth) in depths.into_iter().enumerate().rev() {
+        if let Some(depth) = depth {
+            has_next_sibling.resize(depth + 1, false);
+
+            last_items[index] = !has_next_sibling[depth];
+            has_next_sibling[depth] = true;
+        }
+    }
+
+    last_items
+}
+
+#[cfg(test)]
+mod test_writer {
+    use super::*;
+
+    #[test]
+    fn test_collect_nested_tree() {
+        let nomad_style = NomadStyle::default();
+        let mut tree =
+            TreeWriter::new(&nomad_style, TreeOutput::Collect, "root".to_string(), None).unwrap();
+
+        tree.begin_child("src".to_string(), false).unwrap();
+        tree.add_empty_child("main.rs".to_string(), false).unwrap();
+        tree.add_empty_child("lib.rs".to_string(), true).unwrap();
+        tree.end_child().unwrap();
+        tree.add_empty_child("README.md".to_string(), true).unwrap();
+
+        let (lines, _) = tree.finish().unwrap();
+
+        assert_eq!(
+            lines,
+            Some(vec![
+                "root".to_string(),
+                "├── src".to_string(),
+                "│   ├── main.rs".to_string(),
+                "│   └── lib.rs".to_string(),
+                "└── README.md".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_finish_closes_open_directories() {
+        let nomad_style = NomadStyle::default();
+        let mut tree =
+            TreeWriter::new(&nomad_style, TreeOutput::Collect, "root".to_string(), None).unwrap();
+
+        tree.begin_child("a".to_string(), true).unwrap();
+        tree.begin_child("b".to_string(), true).unwrap();
+        tree.add_empty_child("c".to_string(), true).unwrap();
+
+        let (lines, _) = tree.finish().unwrap();
+
+        assert_eq!(
+            lines,
+            Some(vec![
+                "root".to_string(),
+                "└── a".to_string(),
+                "    └── b".to_string(),
+                "        └── c".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_write_items_when_added() {
+        let nomad_style = NomadStyle::default();
+        let mut tree =
+            TreeWriter::new(&nomad_style, TreeOutput::Collect, "root".to_string(), None).unwrap();
+        let last_line = |tree: &TreeWriter| {
+            tree.collected
+                .as_ref()
+                .and_then(|lines| lines.last())
+                .cloned()
+        };
+
+        tree.begin_child("src".to_string(), false).unwrap();
+        assert_eq!(last_line(&tree), Some("├── src".to_string()));
+
+        tree.add_empty_child("main.rs".to_string(), true).unwrap();
+        assert_eq!(last_line(&tree), Some("│   └── main.rs".to_string()));
+
+        tree.end_child().unwrap();
+        tree.add_empty_child("README.md".to_string(), true).unwrap();
+        assert_eq!(last_line(&tree), Some("└── README.md".to_string()));
+    }
+
+    #[test]
+    fn test_find_last_items() {
+        // root
+        // ├── src
+        // │   ├── main.rs
+        // │   └── nested
+        // │       └── lib.rs
+        // └── README.md
+        assert_eq!(
+            find_last_items([Some(1), Some(2), Some(2), Some(3), Some(1)]),
+            vec![false, false, true, true, true]
+        );
+
+        // Items that are not added to the tree are not siblings of anything.
+        assert_eq!(
+            find_last_items([Some(1), Some(2), None, Some(1), None]),
+            vec![false, true, false, true, false]
+        );
+    }
+}
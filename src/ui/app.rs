@@ -405,7 +405,37 @@ impl<'a> App<'a> {
         self.popup_mode = PopupMode::Disabled;
         self.ui_mode = UIMode::Normal;
 
-        args.regex.pattern = None;
+        args.regex.patterns.clear();
+
+        Ok(())
+    }
+
+    /// Reload the tree for the current directory after its items changed on disk.
+    ///
+    /// Unlike `refresh`, the selected item and the current modes are kept so that
+    /// the tree may be updated while the user is navigating it.
+    pub fn reload(
+        &mut self,
+        args: &GlobalArgs,
+        nomad_style: &'a NomadStyle,
+    ) -> Result<(), NomadError> {
+        let (tree, items) = get_tree(args, nomad_style, &self.current_directory)?;
+
+        let clamp_selection = |selected: Option<usize>, length: usize| {
+            selected.map(|index| index.min(length.saturating_sub(1)))
+        };
+
+        let selected = clamp_selection(self.directory_tree.state.selected(), tree.len());
+        self.directory_tree = StatefulWidget::new(tree, ListState::default(), WidgetMode::Files);
+        self.directory_tree.state.select(selected);
+
+        if let Some(ref mut directory_items) = self.directory_items {
+            let items = items.unwrap_or_default();
+            let selected = clamp_selection(directory_items.state.selected(), items.len());
+
+            *directory_items = StatefulWidget::new(items, ListState::default(), WidgetMode::Files);
+            directory_items.state.select(selected);
+        }
 
         Ok(())
     }
@@ -417,7 +447,7 @@ impl<'a> App<'a> {
         nomad_style: &'a NomadStyle,
         target_directory: &str,
     ) -> Result<(), NomadError> {
-        args.regex.pattern = self.collected_input.pop();
+        args.regex.patterns = self.collected_input.pop().into_iter().collect();
 
         if let Err(error) = self.refresh(args, nomad_style, target_directory) {
             if let NomadError::NothingFound = error {
@@ -198,7 +198,7 @@ where
                     frame.render_widget(Clear, popup_area);
                     frame.render_widget(nothing_found_view(), popup_area);
 
-                    args.regex.pattern = None;
+                    args.regex.patterns.clear();
                 }
                 PopupMode::Settings => {
                     let settings_area = get_settings_area(chunks[1]);
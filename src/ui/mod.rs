@@ -16,11 +16,16 @@ use self::{
     utils::reset_args,
     widgets::cat_view,
 };
-use crate::{cli::global::GlobalArgs, errors::NomadError, style::models::NomadStyle};
+use crate::{
+    cli::global::GlobalArgs,
+    errors::NomadError,
+    style::models::NomadStyle,
+    traverse::watch::{DirectoryWatcher, POLL_INTERVAL},
+};
 
 use anyhow::Result;
 use crossterm::{
-    event::{read, Event, KeyCode},
+    event::{poll, read, Event, KeyCode},
     execute,
     terminal::{
         disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
@@ -79,10 +84,26 @@ where
 {
     let max_help_scroll = HELP_TEXT.as_bytes().iter().filter(|&&c| c == b'\n').count();
     let mut exit_mode = ExitMode::Clean;
+    let mut watcher = DirectoryWatcher::new(args, &app.current_directory);
 
     loop {
         terminal.draw(|frame| render_ui(&mut app, args, frame))?;
 
+        // Reload the tree if its items changed while no key was pressed. The tree
+        // is not reloaded while a popup is displayed or a file is inspected.
+        if !poll(POLL_INTERVAL)? {
+            if watcher.poll(args, &app.current_directory).is_some()
+                && matches!(app.popup_mode, PopupMode::Disabled)
+                && matches!(app.ui_mode, UIMode::Normal | UIMode::Breadcrumbs)
+            {
+                if let Err(error) = app.reload(args, nomad_style) {
+                    app.popup_mode = PopupMode::Error(error.to_string());
+                }
+            }
+
+            continue;
+        }
+
         // Handle keyboard events.
         if let Event::Key(key) = read()? {
             match app.popup_mode {
@@ -647,7 +668,7 @@ where
                     }
                     _ => {
                         if let UIMode::Normal = app.ui_mode {
-                            args.regex.pattern = None;
+                            args.regex.patterns.clear();
                         }
 
                         app.popup_mode = PopupMode::Disabled;
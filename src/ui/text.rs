@@ -64,6 +64,9 @@ pub const HELP_TEXT: &str = r#"
  or files, open a file in a text editor, filter results by pattern, etc. See the
  Keybindings section for details.
 
+ The tree is reloaded automatically when items within the current directory are
+ created, modified, or deleted.
+
  Breadcrumbs Widget
  ------------------
 
@@ -2,14 +2,13 @@
 
 use std::path::{Component, Path};
 
-use ptree::write_tree_with;
 use tui::{
     style::{Color, Style},
     widgets::{Cell, Row},
 };
 
 use crate::{
-    cli::global::GlobalArgs,
+    cli::global::{ExportFormat, GlobalArgs, SortBy, StatsFormat, TimeField},
     errors::NomadError,
     style::models::NomadStyle,
     traverse::{models::DirItem, modes::NomadMode, utils::build_walker, walk_directory},
@@ -30,10 +29,104 @@ pub fn get_settings<'a>(args: &GlobalArgs) -> Vec<Row<'a>> {
 
     vec![
         assign_boolean_flag(" all labels", args.labels.all_labels),
+        assign_boolean_flag(" apparent size", args.meta.apparent_size),
+        Row::new(vec![
+            Cell::from(" changed within"),
+            Cell::from(
+                if let Some(ref changed_within) = args.modifiers.changed_within {
+                    changed_within.to_string()
+                } else {
+                    "None".to_string()
+                },
+            )
+            .style(
+                Style::default().fg(if args.modifiers.changed_within.is_some() {
+                    Color::Green
+                } else {
+                    Color::Red
+                }),
+            ),
+        ]),
+        assign_boolean_flag(" compact", args.modifiers.compact),
+        Row::new(vec![
+            Cell::from(" contains"),
+            Cell::from(if let Some(ref contains) = args.regex.contains {
+                contains.to_string()
+            } else {
+                "None".to_string()
+            })
+            .style(Style::default().fg(if args.regex.contains.is_some() {
+                Color::Green
+            } else {
+                Color::Red
+            })),
+        ]),
         assign_boolean_flag(" dirs", args.modifiers.dirs),
+        assign_boolean_flag(" dirs first", args.sort.dirs_first),
         assign_boolean_flag(" disrespect", args.modifiers.disrespect),
+        assign_boolean_flag(" du", args.meta.du),
+        assign_boolean_flag(" dupes", args.meta.dupes),
+        Row::new(vec![
+            Cell::from(" exclude"),
+            Cell::from(if args.regex.exclude.is_empty() {
+                "None".to_string()
+            } else {
+                args.regex.exclude.join(", ")
+            })
+            .style(Style::default().fg(if !args.regex.exclude.is_empty() {
+                Color::Green
+            } else {
+                Color::Red
+            })),
+        ]),
+        Row::new(vec![
+            Cell::from(" group"),
+            Cell::from(if let Some(ref group) = args.modifiers.group {
+                group.to_string()
+            } else {
+                "None".to_string()
+            })
+            .style(Style::default().fg(if args.modifiers.group.is_some() {
+                Color::Green
+            } else {
+                Color::Red
+            })),
+        ]),
         assign_boolean_flag(" hidden", args.modifiers.hidden),
+        assign_boolean_flag(" ignore case", args.regex.ignore_case),
+        Row::new(vec![
+            Cell::from(" ignore file"),
+            Cell::from(if args.modifiers.ignore_file.is_empty() {
+                "None".to_string()
+            } else {
+                args.modifiers.ignore_file.join(", ")
+            })
+            .style(
+                Style::default().fg(if !args.modifiers.ignore_file.is_empty() {
+                    Color::Green
+                } else {
+                    Color::Red
+                }),
+            ),
+        ]),
+        Row::new(vec![
+            Cell::from(" ignore glob"),
+            Cell::from(if args.modifiers.ignore_glob.is_empty() {
+                "None".to_string()
+            } else {
+                args.modifiers.ignore_glob.join(", ")
+            })
+            .style(
+                Style::default().fg(if !args.modifiers.ignore_glob.is_empty() {
+                    Color::Green
+                } else {
+                    Color::Red
+                }),
+            ),
+        ]),
         assign_boolean_flag(" label directories", args.labels.label_directories),
+        assign_boolean_flag(" match all", args.regex.match_all),
+        assign_boolean_flag(" match dirs", args.regex.match_dirs),
         Row::new(vec![
             Cell::from(" max depth"),
             Cell::from(if let Some(ref depth) = args.modifiers.max_depth {
@@ -47,6 +140,21 @@ pub fn get_settings<'a>(args: &GlobalArgs) -> Vec<Row<'a>> {
                 Color::Red
             })),
         ]),
+        Row::new(vec![
+            Cell::from(" max entries"),
+            Cell::from(if let Some(ref entries) = args.modifiers.max_entries {
+                entries.to_string()
+            } else {
+                "None".to_string()
+            })
+            .style(
+                Style::default().fg(if args.modifiers.max_entries.is_some() {
+                    Color::Green
+                } else {
+                    Color::Red
+                }),
+            ),
+        ]),
         Row::new(vec![
             Cell::from(" max filesize"),
             Cell::from(if let Some(ref size) = args.modifiers.max_filesize {
@@ -62,24 +170,134 @@ pub fn get_settings<'a>(args: &GlobalArgs) -> Vec<Row<'a>> {
                 }),
             ),
         ]),
+        Row::new(vec![
+            Cell::from(" min filesize"),
+            Cell::from(if let Some(ref size) = args.modifiers.min_filesize {
+                size.to_string()
+            } else {
+                "None".to_string()
+            })
+            .style(
+                Style::default().fg(if args.modifiers.min_filesize.is_some() {
+                    Color::Green
+                } else {
+                    Color::Red
+                }),
+            ),
+        ]),
         assign_boolean_flag(" metadata", args.meta.metadata),
+        assign_boolean_flag(" name only", args.regex.name_only),
+        Row::new(vec![
+            Cell::from(" newer"),
+            Cell::from(if let Some(ref newer) = args.modifiers.newer {
+                newer.to_string()
+            } else {
+                "None".to_string()
+            })
+            .style(Style::default().fg(if args.modifiers.newer.is_some() {
+                Color::Green
+            } else {
+                Color::Red
+            })),
+        ]),
+        assign_boolean_flag(" no follow", args.modifiers.no_follow),
         assign_boolean_flag(" no Git", args.style.no_git),
         assign_boolean_flag(" no icons", args.style.no_icons),
         assign_boolean_flag(" numbered", args.labels.numbers),
+        Row::new(vec![
+            Cell::from(" older than"),
+            Cell::from(if let Some(ref older_than) = args.modifiers.older_than {
+                older_than.to_string()
+            } else {
+                "None".to_string()
+            })
+            .style(Style::default().fg(if args.modifiers.older_than.is_some() {
+                Color::Green
+            } else {
+                Color::Red
+            })),
+        ]),
         Row::new(vec![
             Cell::from(" pattern"),
-            Cell::from(if let Some(ref pattern) = args.regex.pattern {
-                pattern.to_string()
+            Cell::from(if args.regex.patterns.is_empty() {
+                "None".to_string()
             } else {
+                args.regex.patterns.join(", ")
+            })
+            .style(Style::default().fg(if !args.regex.patterns.is_empty() {
+                Color::Green
+            } else {
+                Color::Red
+            })),
+        ]),
+        Row::new(vec![
+            Cell::from(" perm"),
+            Cell::from(if args.modifiers.perm.is_empty() {
                 "None".to_string()
+            } else {
+                args.modifiers
+                    .perm
+                    .iter()
+                    .map(|permission| permission.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
             })
-            .style(Style::default().fg(if args.regex.pattern.is_some() {
+            .style(Style::default().fg(if !args.modifiers.perm.is_empty() {
                 Color::Green
             } else {
                 Color::Red
             })),
         ]),
         assign_boolean_flag(" plain", args.style.plain),
+        assign_boolean_flag(" reverse", args.sort.reverse),
+        assign_boolean_flag(" setuid", args.modifiers.setuid),
+        assign_boolean_flag(" show lines", args.regex.show_lines),
+        Row::new(vec![
+            Cell::from(" sort"),
+            Cell::from(args.sort.sort_by.to_string()).style(Style::default().fg(
+                if args.sort.sort_by != SortBy::Name {
+                    Color::Green
+                } else {
+                    Color::Red
+                },
+            )),
+        ]),
+        Row::new(vec![
+            Cell::from(" time field"),
+            Cell::from(args.modifiers.time_field.to_string()).style(Style::default().fg(
+                if args.modifiers.time_field != TimeField::Modified {
+                    Color::Green
+                } else {
+                    Color::Red
+                },
+            )),
+        ]),
+        Row::new(vec![
+            Cell::from(" top"),
+            Cell::from(if let Some(ref top) = args.meta.top {
+                top.to_string()
+            } else {
+                "None".to_string()
+            })
+            .style(Style::default().fg(if args.meta.top.is_some() {
+                Color::Green
+            } else {
+                Color::Red
+            })),
+        ]),
+        Row::new(vec![
+            Cell::from(" user"),
+            Cell::from(if let Some(ref user) = args.modifiers.user {
+                user.to_string()
+            } else {
+                "None".to_string()
+            })
+            .style(Style::default().fg(if args.modifiers.user.is_some() {
+                Color::Green
+            } else {
+                Color::Red
+            })),
+        ]),
     ]
 }
 
@@ -101,25 +319,16 @@ pub fn get_tree(
     nomad_style: &NomadStyle,
     target_directory: &str,
 ) -> Result<(Vec<String>, Option<Vec<DirItem>>), NomadError> {
-    let (tree, config, directory_items) = walk_directory(
+    let (tree, directory_items) = walk_directory(
         args,
+        None,
         NomadMode::Rootless,
         nomad_style,
         target_directory,
-        &mut build_walker(args, target_directory, None)?,
+        build_walker(args, target_directory, None)?,
     )?;
 
-    // Write the tree to a buffer, then convert it to a `Vec<String>`.
-    let mut tree_buf = Vec::new();
-    write_tree_with(&tree, &mut tree_buf, &config)?;
-
-    Ok((
-        String::from_utf8_lossy(&tree_buf)
-            .split('\n')
-            .map(|line| line.to_string())
-            .collect::<Vec<String>>(),
-        directory_items,
-    ))
+    Ok((tree.unwrap_or_default(), directory_items))
 }
 
 /// Reset all settings to its original value.
@@ -127,30 +336,90 @@ pub fn reset_args(args: &mut GlobalArgs) {
     if args.labels.all_labels {
         args.labels.all_labels = false;
     }
+    if args.meta.apparent_size {
+        args.meta.apparent_size = false;
+    }
+    if args.modifiers.changed_within.is_some() {
+        args.modifiers.changed_within = None;
+    }
+    if args.modifiers.compact {
+        args.modifiers.compact = false;
+    }
+    if args.regex.contains.is_some() {
+        args.regex.contains = None;
+    }
     if args.modifiers.dirs {
         args.modifiers.dirs = false;
     }
+    if args.sort.dirs_first {
+        args.sort.dirs_first = false;
+    }
     if args.modifiers.disrespect {
         args.modifiers.disrespect = false;
     }
+    if args.meta.du {
+        args.meta.du = false;
+    }
+    if args.meta.dupes {
+        args.meta.dupes = false;
+    }
     if args.export.is_some() {
         args.export = None;
     }
+    if args.export_format != ExportFormat::Text {
+        args.export_format = ExportFormat::Text;
+    }
+    if !args.regex.exclude.is_empty() {
+        args.regex.exclude.clear();
+    }
+    if args.modifiers.group.is_some() {
+        args.modifiers.group = None;
+    }
     if args.modifiers.hidden {
         args.modifiers.hidden = false;
     }
+    if args.regex.ignore_case {
+        args.regex.ignore_case = false;
+    }
+    if !args.modifiers.ignore_file.is_empty() {
+        args.modifiers.ignore_file.clear();
+    }
+    if !args.modifiers.ignore_glob.is_empty() {
+        args.modifiers.ignore_glob.clear();
+    }
     if args.labels.label_directories {
         args.labels.label_directories = false;
     }
+    if args.regex.match_all {
+        args.regex.match_all = false;
+    }
+    if args.regex.match_dirs {
+        args.regex.match_dirs = false;
+    }
     if args.modifiers.max_depth.is_some() {
         args.modifiers.max_depth = None;
     }
+    if args.modifiers.max_entries.is_some() {
+        args.modifiers.max_entries = None;
+    }
     if args.modifiers.max_filesize.is_some() {
         args.modifiers.max_filesize = None;
     }
+    if args.modifiers.min_filesize.is_some() {
+        args.modifiers.min_filesize = None;
+    }
     if args.meta.metadata {
         args.meta.metadata = false;
     }
+    if args.regex.name_only {
+        args.regex.name_only = false;
+    }
+    if args.modifiers.newer.is_some() {
+        args.modifiers.newer = None;
+    }
+    if args.modifiers.no_follow {
+        args.modifiers.no_follow = false;
+    }
     if args.style.no_git {
         args.style.no_git = false;
     }
@@ -160,13 +429,43 @@ pub fn reset_args(args: &mut GlobalArgs) {
     if args.labels.numbers {
         args.labels.numbers = false;
     }
-    if args.regex.pattern.is_some() {
-        args.regex.pattern = None;
+    if args.modifiers.older_than.is_some() {
+        args.modifiers.older_than = None;
+    }
+    if !args.regex.patterns.is_empty() {
+        args.regex.patterns.clear();
+    }
+    if !args.modifiers.perm.is_empty() {
+        args.modifiers.perm.clear();
     }
     if args.style.plain {
         args.style.plain = false;
     }
+    if args.sort.reverse {
+        args.sort.reverse = false;
+    }
+    if args.modifiers.setuid {
+        args.modifiers.setuid = false;
+    }
+    if args.regex.show_lines {
+        args.regex.show_lines = false;
+    }
+    if args.sort.sort_by != SortBy::Name {
+        args.sort.sort_by = SortBy::Name;
+    }
+    if args.modifiers.time_field != TimeField::Modified {
+        args.modifiers.time_field = TimeField::Modified;
+    }
     if args.statistics {
         args.statistics = false;
     }
+    if args.stats_format != StatsFormat::Table {
+        args.stats_format = StatsFormat::Table;
+    }
+    if args.meta.top.is_some() {
+        args.meta.top = None;
+    }
+    if args.modifiers.user.is_some() {
+        args.modifiers.user = None;
+    }
 }
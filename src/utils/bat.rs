@@ -1,23 +1,43 @@
 //! Run `bat`.
 
-use crate::errors::NomadError;
+use crate::{
+    archive::{find_archive_member, open_member},
+    errors::NomadError,
+    git::revision::{find_revision_item, read_revision_blob},
+};
 
 use anyhow::Result;
 use bat::{Input, PagingMode, PrettyPrinter, WrappingMode};
 
-use std::path::Path;
+use std::{io::Cursor, path::Path};
 
 /// Create a new `PrettyPrinter`, then run it against the file.
+///
+/// Members of archives are streamed from the archive without extracting them,
+/// and files of a Git revision are read from the repository.
 pub fn run_bat(found_items: Vec<String>) -> Result<(), NomadError> {
+    let inputs = found_items
+        .iter()
+        .map(|path| match find_archive_member(path) {
+            Some((archive, member)) => open_member(&archive, &member)
+                .map(|contents| Input::from_reader(contents).name(path).title(path)),
+            None => match find_revision_item(path) {
+                Some((target_directory, revision, item)) => {
+                    read_revision_blob(&target_directory, &revision, &item).map(|contents| {
+                        Input::from_reader(Cursor::new(contents))
+                            .name(&item)
+                            .title(format!("{item} @ {revision}"))
+                    })
+                }
+                None => Ok(Input::from_file(Path::new(path))),
+            },
+        })
+        .collect::<Result<Vec<Input>, NomadError>>()?;
+
     PrettyPrinter::new()
         .grid(true)
         .header(true)
-        .inputs(
-            found_items
-                .iter()
-                .map(|path| Input::from_file(Path::new(path)))
-                .collect::<Vec<Input>>(),
-        )
+        .inputs(inputs)
         .line_numbers(true)
         .paging_mode(PagingMode::QuitIfOneScreen)
         .true_color(true)
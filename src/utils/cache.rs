@@ -3,6 +3,7 @@
 use std::{
     fs::{create_dir_all, File},
     io::Write,
+    path::PathBuf,
 };
 
 use anyhow::Result;
@@ -11,32 +12,35 @@ use serde_json::Value;
 
 use crate::errors::NomadError;
 
+/// Return the path to nomad's cache directory.
+pub fn get_cache_directory() -> Result<PathBuf, NomadError> {
+    match ProjectDirs::from("", "", "nomad") {
+        Some(project_directory) => Ok(project_directory.cache_dir().to_path_buf()),
+        None => Err(NomadError::ApplicationError),
+    }
+}
+
 /// Return a JSON `File` object in write/overwrite or read-only mode.
 pub fn get_json_file(read_only: bool) -> Result<File, NomadError> {
-    match ProjectDirs::from("", "", "nomad") {
-        Some(project_directory) => {
-            let items_json = project_directory.cache_dir().join("items.json");
-
-            if !items_json.exists() {
-                match &items_json.parent() {
-                    Some(parent) => create_dir_all(parent)?,
-                    None => {
-                        return Err(NomadError::PathError(
-                            "Could not get the path to nomad's application directory!".to_string(),
-                        ))
-                    }
-                }
+    let items_json = get_cache_directory()?.join("items.json");
+
+    if !items_json.exists() {
+        match &items_json.parent() {
+            Some(parent) => create_dir_all(parent)?,
+            None => {
+                return Err(NomadError::PathError(
+                    "Could not get the path to nomad's application directory!".to_string(),
+                ))
             }
-
-            let file = match read_only {
-                true => File::open(items_json)?,
-                false => File::create(items_json)?,
-            };
-
-            Ok(file)
         }
-        None => Err(NomadError::ApplicationError),
     }
+
+    let file = match read_only {
+        true => File::open(items_json)?,
+        false => File::create(items_json)?,
+    };
+
+    Ok(file)
 }
 
 /// Write a JSON string to `items.json`.
@@ -1,14 +1,28 @@
 //! Export a directory's tree to a file instead of saving.
 
-use crate::errors::NomadError;
+use super::html::{render_page, HtmlTheme};
+use crate::{
+    cli::global::{ExportFormat, GlobalArgs},
+    errors::NomadError,
+    loc::LocCounts,
+    style::models::NomadStyle,
+};
 
 use ansi_term::*;
 use anyhow::Result;
 use chrono::Local;
-use ptree::{item::StringItem, write_tree_with, PrintConfig};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{json, Value};
 
 use std::{env, fs::File, io::Write};
 
+lazy_static! {
+    /// Matches the ANSI escape sequences used to paint text.
+    static ref ANSI_ESCAPE: Regex = Regex::new(r"\x1b\[[0-9;]*m").expect("Invalid ANSI regex!");
+}
+
 /// Get the absolute path for the file name.
 fn get_absolute_path(file_name: &str) -> Result<String, NomadError> {
     Ok(env::current_dir()?
@@ -20,28 +34,293 @@ fn get_absolute_path(file_name: &str) -> Result<String, NomadError> {
 
 /// Variants for export modes.
 pub enum ExportMode<'a> {
+    /// `nomad` was run for an archive.
+    Archive(&'a str),
+    /// `nomad` verified a checksum manifest.
+    Checksum(&'a str),
+    /// `nomad` was run in compare mode.
+    Compare(&'a str, &'a str),
     /// `nomad` was run in filetype mode.
     Filetype(&'a Vec<String>, &'a Vec<String>),
     /// `nomad` was run in normal mode.
     Normal,
+    /// `nomad` was run for a Git revision.
+    Revision(&'a str),
+    /// `nomad` was run in snapshot mode.
+    Snapshot(&'a str),
     /// `nomad` was run in Git branch mode.
     GitBranch,
     /// `nomad` was run in Git status mode.
     GitStatus,
 }
 
-/// Export the tree to a file. Writes to a custom filename if specified, otherwise
-/// the filename corresponds to the tree mode (normal, filetype, or Git status)
-/// and the current timestamp.
-pub fn export_tree(
-    config: PrintConfig,
+impl ExportMode<'_> {
+    /// Describe the mode in the header of a JSON document.
+    fn to_json(&self) -> Value {
+        match self {
+            ExportMode::Archive(archive) => json!({ "mode": "archive", "archive": archive }),
+            ExportMode::Checksum(manifest) => json!({ "mode": "checksum", "manifest": manifest }),
+            ExportMode::Compare(left, right) => {
+                json!({ "mode": "compare", "left": left, "right": right })
+            }
+            ExportMode::Filetype(filetypes, globs) => {
+                json!({ "mode": "filetype", "filetypes": filetypes, "globs": globs })
+            }
+            ExportMode::Normal => json!({ "mode": "normal" }),
+            ExportMode::Revision(revision) => json!({ "mode": "revision", "revision": revision }),
+            ExportMode::Snapshot(name) => json!({ "mode": "snapshot", "snapshot": name }),
+            ExportMode::GitBranch => json!({ "mode": "git_branch" }),
+            ExportMode::GitStatus => json!({ "mode": "git_status" }),
+        }
+    }
+}
+
+/// Variants for the kinds of nodes in an exported document.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeType {
+    /// A Git branch.
+    Branch,
+    /// A directory, or a group of Git branches sharing a parent name.
+    Directory,
+    /// A file.
+    File,
+    /// A symlink that does not point to a directory.
+    Symlink,
+    /// Any other line in the tree, such as a summary of items that were not displayed.
+    Text,
+}
+
+/// Contains the metadata of an item in an exported document.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct ExportMetadata {
+    /// The name of the group that owns the item.
+    pub group: Option<String>,
+    /// The permissions of the item, ie. `-rw-r--r--`.
+    pub mode: Option<String>,
+    /// The last modified time of the item as a UNIX timestamp.
+    pub modified: Option<i64>,
+    /// The size of the item in bytes.
+    pub size: Option<u64>,
+    /// The name of the user that owns the item.
+    pub user: Option<String>,
+}
+
+/// Contains an item in an exported document.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ExportNode {
+    /// The items within this item. Only directories have children.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<ExportNode>>,
+    /// The name of the item's Git status, ie. `modified` or `staged_added`.
+    pub git_status: Option<&'static str>,
+    /// The icon displayed next to the item. Only used in HTML exports.
+    #[serde(skip)]
+    pub icon: Option<String>,
+    /// The directory label or file number used by other commands.
+    pub label: Option<String>,
+    /// The item's metadata if `--metadata` was used.
+    pub metadata: Option<ExportMetadata>,
+    /// The name of the item.
+    pub name: String,
+    /// The path to the item relative to the root of the tree.
+    pub path: String,
+    /// The lines of code in the file if `--tokei` was used.
+    pub tokei: Option<LocCounts>,
+    /// The kind of item.
+    #[serde(rename = "type")]
+    pub node_type: NodeType,
+}
+
+impl ExportNode {
+    /// Create a node without any labels or metadata.
+    pub fn new(name: &str, node_type: NodeType, path: &str) -> Self {
+        Self {
+            children: (node_type == NodeType::Directory).then(Vec::new),
+            git_status: None,
+            icon: None,
+            label: None,
+            metadata: None,
+            name: name.to_string(),
+            node_type,
+            path: path.to_string(),
+            tokei: None,
+        }
+    }
+
+    /// Create a node for a line of the tree that does not describe an item.
+    /// Colors are removed from the text.
+    pub fn text(text: &str) -> Self {
+        Self::new(&ANSI_ESCAPE.replace_all(text, ""), NodeType::Text, "")
+    }
+}
+
+/// Builds a nested document while the tree is written.
+#[derive(Debug)]
+pub struct ExportDocument {
+    /// Indicates whether colors are included in an HTML page.
+    colors: bool,
+    /// The format the document is written in.
+    format: ExportFormat,
+    /// The fields describing the mode the tree was written in.
+    header: Value,
+    /// Indicates whether icons are included in an HTML page.
+    icons: bool,
+    /// The root of the tree followed by each directory that is currently open.
+    nodes: Vec<ExportNode>,
+    /// The colors and Git markers used to render an HTML page.
+    theme: Option<HtmlTheme>,
+}
+
+impl ExportDocument {
+    /// Start a document for the export mode.
+    fn new(args: &GlobalArgs, export_mode: &ExportMode) -> Self {
+        Self {
+            colors: !(args.style.no_colors || args.style.plain),
+            format: args.export_format,
+            header: export_mode.to_json(),
+            icons: !(args.style.no_icons || args.style.plain),
+            nodes: Vec::new(),
+            theme: None,
+        }
+    }
+
+    /// Use the colors and Git markers in `NomadStyle` if the document is
+    /// written as an HTML page.
+    pub fn set_style(&mut self, nomad_style: &NomadStyle) {
+        if self.format == ExportFormat::Html {
+            self.theme = Some(HtmlTheme::new(self.colors, self.icons, nomad_style));
+        }
+    }
+
+    /// Add a node to the current directory, then make it the current directory.
+    pub fn begin(&mut self, node: ExportNode) {
+        self.nodes.push(node);
+    }
+
+    /// Add a node without children to the current directory.
+    pub fn add(&mut self, node: ExportNode) {
+        self.begin(node);
+        self.end();
+    }
+
+    /// Close the current directory and move back up to its parent. The root of
+    /// the tree is never closed.
+    pub fn end(&mut self) {
+        if self.nodes.len() > 1 {
+            if let Some(node) = self.nodes.pop() {
+                if let Some(parent) = self.nodes.last_mut() {
+                    parent.children.get_or_insert_with(Vec::new).push(node);
+                }
+            }
+        }
+    }
+
+    /// Close every directory and write the document to the export file.
+    fn write(mut self, file: &mut File) -> Result<(), NomadError> {
+        while self.nodes.len() > 1 {
+            self.end();
+        }
+
+        let root = match self.nodes.pop() {
+            Some(root) => root,
+            None => return Ok(()),
+        };
+
+        if self.format == ExportFormat::Html {
+            let theme = self
+                .theme
+                .unwrap_or_else(|| HtmlTheme::new(self.colors, self.icons, &NomadStyle::default()));
+            write!(file, "{}", render_page(&self.header, &root, &theme))?;
+        } else {
+            if let Some(header) = self.header.as_object_mut() {
+                header.insert("tree".to_string(), serde_json::to_value(root)?);
+            }
+
+            serde_json::to_writer_pretty(&mut *file, &self.header)?;
+            writeln!(file)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Contains the file the tree is streamed into while it is displayed.
+pub struct TreeExport {
+    /// The document the tree is built into if it is exported as JSON or HTML.
+    /// The document is written once the tree is finished.
+    pub document: Option<ExportDocument>,
+    /// The export file.
+    pub file: File,
+    /// The absolute path to the export file.
+    pub file_path: String,
+}
+
+impl TreeExport {
+    /// Write the document to the export file if the tree is exported as JSON or HTML.
+    pub fn write_document(&mut self) -> Result<(), NomadError> {
+        match self.document.take() {
+            Some(document) => document.write(&mut self.file),
+            None => Ok(()),
+        }
+    }
+
+    /// Display the path the tree was exported to.
+    pub fn display_success(&self) {
+        let success_message = Colour::Green
+            .bold()
+            .paint(format!("Tree was exported to {}\n", self.file_path));
+        println!("{success_message}");
+    }
+}
+
+/// Create the export file if the `--export` flag was provided.
+pub fn get_export(
+    args: &GlobalArgs,
+    export_mode: ExportMode,
+) -> Result<Option<TreeExport>, NomadError> {
+    match args.export {
+        Some(ref filename) => Ok(Some(create_export_file(args, export_mode, filename)?)),
+        None => Ok(None),
+    }
+}
+
+/// Create the file the tree is exported to and write its header. Writes to a
+/// custom filename if specified, otherwise the filename corresponds to the tree
+/// mode (normal, filetype, or Git status) and the current timestamp.
+///
+/// The header is written into the document instead if the tree is exported as
+/// JSON or HTML.
+fn create_export_file(
+    args: &GlobalArgs,
     export_mode: ExportMode,
     filename: &Option<String>,
-    tree: StringItem,
-) -> Result<(), NomadError> {
+) -> Result<TreeExport, NomadError> {
+    let document = match args.export_format {
+        ExportFormat::Html | ExportFormat::Json => Some(ExportDocument::new(args, &export_mode)),
+        ExportFormat::Text => None,
+    };
+
     let mut file_header = "nomad".to_string();
 
     let mut default_filename = match export_mode {
+        ExportMode::Archive(archive) => {
+            file_header.push_str(&format!("\n\nMode: archive\nArchive: {archive}\n\n"));
+
+            "archive".to_string()
+        }
+        ExportMode::Checksum(manifest) => {
+            file_header.push_str(&format!("\n\nMode: checksum\nManifest: {manifest}\n\n"));
+
+            "checksum".to_string()
+        }
+        ExportMode::Compare(left, right) => {
+            file_header.push_str(&format!(
+                "\n\nMode: compare\nLeft: {left}\nRight: {right}\n\n"
+            ));
+
+            "compare".to_string()
+        }
         ExportMode::Filetype(filetypes, globs) => {
             let mut filetype_info = "\n\n".to_string();
             if !filetypes.is_empty() {
@@ -63,6 +342,16 @@ pub fn export_tree(
 
             "nomad".to_string()
         }
+        ExportMode::Revision(revision) => {
+            file_header.push_str(&format!("\n\nMode: Git revision\nRevision: {revision}\n\n"));
+
+            "revision".to_string()
+        }
+        ExportMode::Snapshot(name) => {
+            file_header.push_str(&format!("\n\nMode: snapshot\nSnapshot: {name}\n\n"));
+
+            "snapshot".to_string()
+        }
         ExportMode::GitBranch => {
             file_header.push_str("\n\nMode: Git branch\n\n");
 
@@ -79,29 +368,80 @@ pub fn export_tree(
         filename.to_string()
     } else {
         let timestamp = Local::now().format("%F_%H-%M-%S").to_string();
-        default_filename.push_str(&format!("_{}.txt", timestamp));
+        let extension = match args.export_format {
+            ExportFormat::Html => "html",
+            ExportFormat::Json => "json",
+            ExportFormat::Text => "txt",
+        };
+        default_filename.push_str(&format!("_{}.{extension}", timestamp));
 
         default_filename
     };
 
     let file_path = get_absolute_path(&export_filename)?;
     let mut file = File::create(&file_path)?;
-    write!(file, "{}", file_header)?;
-
-    write_tree_with(&tree, file, &config).map_or_else(
-        |error| {
-            Err(NomadError::PTreeError {
-                context: format!("Unable to export directory tree to {file_path}"),
-                source: error,
-            })
-        },
-        |_| {
-            let success_message = Colour::Green
-                .bold()
-                .paint(format!("Tree was exported to {file_path}\n"));
-            println!("{success_message}");
-
-            Ok(())
-        },
-    )
+    if document.is_none() {
+        write!(file, "{}", file_header)?;
+    }
+
+    Ok(TreeExport {
+        document,
+        file,
+        file_path,
+    })
+}
+
+#[cfg(test)]
+mod test_export {
+    use super::*;
+
+    use crate::utils::test_utils::create_fixture;
+
+    use structopt::StructOpt;
+
+    use std::fs;
+
+    #[test]
+    fn test_export_document() {
+        let mut document = ExportDocument::new(
+            &GlobalArgs::from_iter(["nd", "--export-format", "json"]),
+            &ExportMode::Revision("HEAD"),
+        );
+
+        document.begin(ExportNode::new("nomad", NodeType::Directory, ""));
+        document.begin(ExportNode {
+            label: Some("a".to_string()),
+            ..ExportNode::new("src", NodeType::Directory, "src")
+        });
+        document.add(ExportNode {
+            git_status: Some("modified"),
+            label: Some("0".to_string()),
+            ..ExportNode::new("main.rs", NodeType::File, "src/main.rs")
+        });
+        document.add(ExportNode::text("\x1b[1m… 3 more items\x1b[0m"));
+        document.end();
+        document.add(ExportNode::new("README.md", NodeType::File, "README.md"));
+
+        let fixture = create_fixture(&[]);
+        let file_path = fixture.path().join("export.json");
+        document
+            .write(&mut File::create(&file_path).unwrap())
+            .unwrap();
+
+        let exported: Value =
+            serde_json::from_str(&fs::read_to_string(&file_path).unwrap()).unwrap();
+
+        assert_eq!(exported["mode"], "revision");
+        assert_eq!(exported["revision"], "HEAD");
+
+        let root = &exported["tree"];
+        assert_eq!(root["name"], "nomad");
+        assert_eq!(root["children"][0]["label"], "a");
+        assert_eq!(root["children"][0]["children"][0]["git_status"], "modified");
+        assert_eq!(root["children"][0]["children"][0]["type"], "file");
+        assert!(root["children"][0]["children"][0].get("children").is_none());
+        assert_eq!(root["children"][0]["children"][1]["name"], "… 3 more items");
+        assert_eq!(root["children"][0]["children"][1]["type"], "text");
+        assert_eq!(root["children"][1]["path"], "README.md");
+    }
 }
@@ -0,0 +1,497 @@
+//! Render an exported document as a self-contained HTML page.
+
+use super::{
+    export::{ExportMetadata, ExportNode, NodeType},
+    meta::{convert_bytes, convert_time},
+};
+use crate::style::models::NomadStyle;
+
+use ansi_term::{Colour, Style};
+use serde_json::Value;
+
+use std::{collections::HashMap, fmt::Write};
+
+/// The icon displayed next to a directory.
+const DIRECTORY_ICON: &str = "\u{f115}"; // 
+/// The icon displayed next to an empty directory.
+const EMPTY_DIRECTORY_ICON: &str = "\u{f114}"; // 
+
+/// The styles that do not depend on `NomadStyle`.
+const BASE_CSS: &str = r#"
+body { background: #1d1f21; color: #d0d0d0; font-family: "Hack Nerd Font", "FiraCode Nerd Font", "JetBrainsMono Nerd Font", ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; font-size: 14px; margin: 2em; }
+header { margin-bottom: 1em; }
+h1 { font-size: 1.3em; margin: 0 0 0.3em; }
+dl.details { color: #969896; display: grid; gap: 0.1em 1em; grid-template-columns: max-content auto; margin: 0 0 1em; }
+dl.details dt { font-weight: bold; }
+dl.details dd { margin: 0; }
+.controls { align-items: center; display: flex; flex-wrap: wrap; gap: 0.6em; }
+.controls input[type="search"] { background: #282a2e; border: 1px solid #4d4d4c; border-radius: 4px; color: inherit; font: inherit; padding: 0.3em 0.6em; width: 24em; }
+.controls button { background: #282a2e; border: 1px solid #4d4d4c; border-radius: 4px; color: inherit; cursor: pointer; font: inherit; padding: 0.3em 0.6em; }
+ul.tree, ul.tree ul { list-style: none; margin: 0; padding-left: 1.6em; }
+ul.tree { padding-left: 0; }
+ul.tree ul { border-left: 1px solid #4d4d4c; }
+ul.tree li { white-space: pre; }
+ul.tree summary { cursor: pointer; }
+ul.tree li.file, ul.tree li.symlink, ul.tree li.branch, ul.tree li.text { padding-left: 1.1em; }
+.row { display: inline-flex; gap: 0.5em; }
+.metadata { color: #969896; display: inline-flex; gap: 0.8em; }
+body.hide-metadata .metadata { display: none; }
+li.text .name { color: #969896; font-style: italic; }
+"#;
+
+/// The script that filters items, expands or collapses every directory, and
+/// toggles the metadata columns.
+const SCRIPT: &str = r#"
+const items = Array.from(document.querySelectorAll("ul.tree li")).reverse();
+const filter = document.getElementById("filter");
+filter.addEventListener("input", () => {
+  const query = filter.value.trim().toLowerCase();
+  for (const item of items) {
+    const visible = query === ""
+      || item.dataset.search.includes(query)
+      || item.querySelector(":scope > details > ul > li:not([hidden])") !== null;
+    item.hidden = !visible;
+    const details = item.querySelector(":scope > details");
+    if (details && visible && query !== "") {
+      details.open = true;
+    }
+  }
+});
+const setOpen = (open) => document.querySelectorAll("ul.tree details").forEach((details) => details.open = open);
+document.getElementById("expand").addEventListener("click", () => setOpen(true));
+document.getElementById("collapse").addEventListener("click", () => setOpen(false));
+const showMetadata = document.getElementById("show-metadata");
+if (showMetadata) {
+  showMetadata.addEventListener("change", () => document.body.classList.toggle("hide-metadata", !showMetadata.checked));
+}
+"#;
+
+/// Contains the colors, Git markers, and display settings used to render a page.
+#[derive(Debug)]
+pub struct HtmlTheme {
+    /// The styles translated from `NomadStyle`.
+    css: String,
+    /// Indicates whether icons are displayed.
+    icons: bool,
+    /// Maps each Git status name to its marker.
+    markers: HashMap<&'static str, String>,
+}
+
+impl HtmlTheme {
+    /// Translate the colors and Git markers in `NomadStyle` into a theme.
+    /// Colors are left out if `colors` is disabled.
+    pub fn new(colors: bool, icons: bool, nomad_style: &NomadStyle) -> Self {
+        let git = &nomad_style.git;
+        let statuses = [
+            ("conflicted", &git.conflicted_marker, git.conflicted_color),
+            ("deleted", &git.deleted_marker, git.deleted_color),
+            ("modified", &git.modified_marker, git.modified_color),
+            ("renamed", &git.renamed_marker, git.renamed_color),
+            (
+                "staged_added",
+                &git.staged_added_marker,
+                git.staged_added_color,
+            ),
+            (
+                "staged_deleted",
+                &git.staged_deleted_marker,
+                git.staged_deleted_color,
+            ),
+            (
+                "staged_modified",
+                &git.staged_modified_marker,
+                git.staged_modified_color,
+            ),
+            (
+                "staged_renamed",
+                &git.staged_renamed_marker,
+                git.staged_renamed_color,
+            ),
+            (
+                "staged_typechanged",
+                &git.staged_typechanged_marker,
+                git.staged_typechanged_color,
+            ),
+            (
+                "typechanged",
+                &git.typechanged_marker,
+                git.typechanged_color,
+            ),
+            ("untracked", &git.untracked_marker, git.untracked_color),
+        ];
+
+        let mut css = BASE_CSS.to_string();
+        if colors {
+            let tree = &nomad_style.tree;
+            // Item labels are less specific than directory labels, so they are written first.
+            let mut rules = vec![
+                (".label".to_string(), tree.label_colors.item_labels),
+                (
+                    "li.directory > details > summary .label".to_string(),
+                    tree.label_colors.directory_labels,
+                ),
+                (
+                    "li.directory > details > summary .name".to_string(),
+                    tree.item_colors.directory_color,
+                ),
+                (
+                    "li.directory.empty > details > summary .name".to_string(),
+                    tree.item_colors.empty_directory_color,
+                ),
+                (".metadata .group".to_string(), Colour::Fixed(193).normal()),
+                (
+                    ".metadata .modified".to_string(),
+                    Colour::Fixed(35).normal(),
+                ),
+                (".metadata .size".to_string(), Colour::Fixed(172).normal()),
+                (".metadata .user".to_string(), Colour::Fixed(194).normal()),
+            ];
+
+            rules.extend(
+                statuses
+                    .iter()
+                    .map(|(name, _, style)| (format!(".git-{name}"), *style)),
+            );
+
+            for (selector, style) in rules {
+                let _ = writeln!(css, "{selector} {{ {} }}", style_to_css(&style));
+            }
+        }
+
+        Self {
+            css,
+            icons,
+            markers: statuses
+                .iter()
+                .map(|(name, marker, _)| (*name, marker.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Render the page for a tree. `header` describes the mode the tree was
+/// displayed in.
+pub fn render_page(header: &Value, root: &ExportNode, theme: &HtmlTheme) -> String {
+    let has_metadata = has_metadata(root);
+
+    let mut page = String::new();
+    let _ = write!(
+        page,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{} · nomad</title>\n<style>{}</style>\n</head>\n<body>\n<header>\n<h1>{}</h1>\n",
+        escape(&root.name),
+        theme.css,
+        escape(&root.name)
+    );
+
+    if let Some(details) = header.as_object() {
+        page.push_str("<dl class=\"details\">\n");
+        for (key, value) in details {
+            let value = match value {
+                Value::Array(values) => values
+                    .iter()
+                    .map(|value| value.as_str().unwrap_or("?"))
+                    .collect::<Vec<&str>>()
+                    .join(", "),
+                Value::String(value) => value.replace('_', " "),
+                value => value.to_string(),
+            };
+
+            let _ = writeln!(
+                page,
+                "<dt>{}</dt><dd>{}</dd>",
+                escape(&capitalize(key)),
+                escape(&value)
+            );
+        }
+        page.push_str("</dl>\n");
+    }
+
+    page.push_str(
+        "<div class=\"controls\">\n\
+         <input id=\"filter\" type=\"search\" placeholder=\"Filter items\" autofocus>\n\
+         <button id=\"expand\" type=\"button\">Expand all</button>\n\
+         <button id=\"collapse\" type=\"button\">Collapse all</button>\n",
+    );
+    if has_metadata {
+        page.push_str(
+            "<label><input id=\"show-metadata\" type=\"checkbox\" checked> Metadata</label>\n",
+        );
+    }
+    page.push_str("</div>\n</header>\n<ul class=\"tree\">\n");
+
+    render_node(&mut page, root, theme);
+
+    let _ = write!(page, "</ul>\n<script>{SCRIPT}</script>\n</body>\n</html>\n");
+
+    page
+}
+
+/// Render an item and its children as a list item.
+fn render_node(page: &mut String, node: &ExportNode, theme: &HtmlTheme) {
+    let mut classes = vec![node_class(node.node_type)];
+    if node
+        .children
+        .as_ref()
+        .is_some_and(|children| children.is_empty())
+    {
+        classes.push("empty");
+    }
+
+    let search = if node.path.is_empty() {
+        &node.name
+    } else {
+        &node.path
+    };
+    let _ = write!(
+        page,
+        "<li class=\"{}\" data-search=\"{}\">",
+        classes.join(" "),
+        escape(&search.to_lowercase())
+    );
+
+    let mut row = String::from("<span class=\"row\">");
+    if let Some(marker) = node
+        .git_status
+        .and_then(|status| Some((status, theme.markers.get(status)?)))
+    {
+        let _ = write!(
+            row,
+            "<span class=\"marker git-{}\" title=\"{}\">{}</span>",
+            marker.0,
+            marker.0.replace('_', " "),
+            escape(marker.1)
+        );
+    }
+    if theme.icons {
+        if let Some(icon) = node_icon(node) {
+            let _ = write!(row, "<span class=\"icon\">{icon}</span>");
+        }
+    }
+    if let Some(ref label) = node.label {
+        let _ = write!(row, "<span class=\"label\">[{}]</span>", escape(label));
+    }
+    let _ = write!(row, "<span class=\"name\">{}</span>", escape(&node.name));
+    if let Some(ref metadata) = node.metadata {
+        render_metadata(&mut row, metadata);
+    }
+    row.push_str("</span>");
+
+    match node.children {
+        Some(ref children) => {
+            let _ = write!(page, "<details open><summary>{row}</summary>");
+            if !children.is_empty() {
+                page.push_str("<ul>\n");
+                for child in children {
+                    render_node(page, child, theme);
+                }
+                page.push_str("</ul>");
+            }
+            page.push_str("</details></li>\n");
+        }
+        None => {
+            let _ = writeln!(page, "{row}</li>");
+        }
+    }
+}
+
+/// Render the metadata columns of an item.
+fn render_metadata(row: &mut String, metadata: &ExportMetadata) {
+    let columns = [
+        ("mode", metadata.mode.clone()),
+        ("user", metadata.user.clone()),
+        ("group", metadata.group.clone()),
+        (
+            "size",
+            metadata
+                .size
+                .and_then(|size| i64::try_from(size).ok())
+                .map(|size| convert_bytes(size).trim().to_string()),
+        ),
+        ("modified", metadata.modified.map(convert_time)),
+    ];
+
+    row.push_str("<span class=\"metadata\">");
+    for (class, value) in columns {
+        if let Some(value) = value {
+            let _ = write!(row, "<span class=\"{class}\">{}</span>", escape(&value));
+        }
+    }
+    row.push_str("</span>");
+}
+
+/// Check whether any item in the tree has metadata.
+fn has_metadata(node: &ExportNode) -> bool {
+    node.metadata.is_some()
+        || node
+            .children
+            .as_ref()
+            .is_some_and(|children| children.iter().any(has_metadata))
+}
+
+/// Get the icon displayed next to an item. Directories without an icon, such
+/// as the root of the tree, use the directory icons.
+fn node_icon(node: &ExportNode) -> Option<&str> {
+    match (node.icon.as_deref(), node.node_type) {
+        (Some(icon), _) => Some(icon),
+        (None, NodeType::Directory) if node.children.as_ref().is_some_and(Vec::is_empty) => {
+            Some(EMPTY_DIRECTORY_ICON)
+        }
+        (None, NodeType::Directory) => Some(DIRECTORY_ICON),
+        (None, _) => None,
+    }
+}
+
+/// Get the class of a list item.
+fn node_class(node_type: NodeType) -> &'static str {
+    match node_type {
+        NodeType::Branch => "branch",
+        NodeType::Directory => "directory",
+        NodeType::File => "file",
+        NodeType::Symlink => "symlink",
+        NodeType::Text => "text",
+    }
+}
+
+/// Capitalize the first letter of a word.
+fn capitalize(word: &str) -> String {
+    let mut characters = word.chars();
+    match characters.next() {
+        Some(first) => first.to_uppercase().chain(characters).collect(),
+        None => String::new(),
+    }
+}
+
+/// Escape the characters that have special meanings in HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Translate an `ansi_term::Style` into CSS declarations.
+fn style_to_css(style: &Style) -> String {
+    let mut declarations = Vec::new();
+
+    if let Some(foreground) = style.foreground {
+        declarations.push(format!("color: {};", colour_to_css(foreground)));
+    }
+    if let Some(background) = style.background {
+        declarations.push(format!("background: {};", colour_to_css(background)));
+    }
+    if style.is_bold {
+        declarations.push("font-weight: bold;".to_string());
+    }
+    if style.is_dimmed {
+        declarations.push("opacity: 0.7;".to_string());
+    }
+    if style.is_italic {
+        declarations.push("font-style: italic;".to_string());
+    }
+    if style.is_underline {
+        declarations.push("text-decoration: underline;".to_string());
+    } else if style.is_strikethrough {
+        declarations.push("text-decoration: line-through;".to_string());
+    }
+
+    declarations.join(" ")
+}
+
+/// Translate an `ansi_term::Colour` into a CSS color using the xterm palette.
+fn colour_to_css(colour: Colour) -> String {
+    let (r, g, b) = match colour {
+        Colour::Black => fixed_to_rgb(0),
+        Colour::Red => fixed_to_rgb(1),
+        Colour::Green => fixed_to_rgb(2),
+        Colour::Yellow => fixed_to_rgb(3),
+        Colour::Blue => fixed_to_rgb(4),
+        Colour::Purple => fixed_to_rgb(5),
+        Colour::Cyan => fixed_to_rgb(6),
+        Colour::White => fixed_to_rgb(7),
+        Colour::Fixed(code) => fixed_to_rgb(code),
+        Colour::RGB(r, g, b) => (r, g, b),
+    };
+
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Get the RGB values of a color in the xterm 256 color palette.
+fn fixed_to_rgb(code: u8) -> (u8, u8, u8) {
+    const BASE: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (59, 120, 255),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+
+    match code {
+        0..=15 => BASE[code as usize],
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let index = code - 16;
+
+            (level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        _ => {
+            let gray = 8 + (code - 232) * 10;
+
+            (gray, gray, gray)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_html {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn test_colours() {
+        assert_eq!(colour_to_css(Colour::Fixed(172)), "#d78700");
+        assert_eq!(colour_to_css(Colour::Fixed(244)), "#808080");
+        assert_eq!(colour_to_css(Colour::RGB(1, 2, 3)), "#010203");
+        assert_eq!(
+            style_to_css(&Colour::Blue.bold()),
+            "color: #3b78ff; font-weight: bold;"
+        );
+    }
+
+    #[test]
+    fn test_render_page() {
+        let theme = HtmlTheme::new(true, false, &NomadStyle::default());
+        let root = ExportNode {
+            children: Some(vec![ExportNode {
+                git_status: Some("modified"),
+                label: Some("0".to_string()),
+                ..ExportNode::new("<main>.rs", NodeType::File, "src/<main>.rs")
+            }]),
+            ..ExportNode::new("nomad", NodeType::Directory, "")
+        };
+
+        let page = render_page(&json!({ "mode": "normal" }), &root, &theme);
+
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<dt>Mode</dt><dd>normal</dd>"));
+        assert!(page.contains("<span class=\"marker git-modified\" title=\"modified\">M</span>"));
+        assert!(page.contains("<span class=\"name\">&lt;main&gt;.rs</span>"));
+        assert!(page.contains("data-search=\"src/&lt;main&gt;.rs\""));
+        assert!(page.contains(".git-modified {"));
+        assert!(!page.contains("id=\"show-metadata\""));
+    }
+}
@@ -1,6 +1,8 @@
 //! Retrieving metadata for files.
 
-use crate::cli::global::GlobalArgs;
+use crate::{
+    cli::global::GlobalArgs, traverse::models::ItemMetadata, utils::export::ExportMetadata,
+};
 
 use ansi_term::Colour;
 use chrono::{Local, NaiveDateTime};
@@ -31,10 +33,10 @@ pub fn convert_time(timestamp: i64) -> String {
 ///
 /// Petabyte is the largest unit of data that may be converted. Otherwise, file
 /// sizes will be displayed in bytes.
-fn convert_bytes(bytes: i64) -> String {
+pub fn convert_bytes(bytes: i64) -> String {
     let (convert_by, label): (i64, &str) = match bytes {
         1000..=999999 => (1000, "KB"),
-        1000000..=9999999 => (1000000, "MB"),
+        1000000..=999999999 => (1000000, "MB"),
         1000000000..=999999999999 => (1000000000, "GB"),
         1000000000000..=999999999999999 => (1000000000000, "TB"),
         1000000000000000..=999999999999999999 => (1000000000000000, "PB"),
@@ -222,3 +224,62 @@ pub fn get_metadata(args: &GlobalArgs, item: &Path) -> String {
             .to_string()
     }
 }
+
+/// Format the metadata of an item that is not on disk, such as a member of an
+/// archive. Owners are not displayed since they may not exist on this system.
+pub fn format_item_metadata(args: &GlobalArgs, metadata: &ItemMetadata) -> String {
+    let plain_mode = metadata
+        .mode
+        .map_or_else(|| format!("{:<10}", "-"), to_string);
+    let plain_size = metadata
+        .size
+        .and_then(|size| i64::try_from(size).ok())
+        .map_or_else(|| format!("{:>6}", "-"), convert_bytes);
+    let plain_last_modified = metadata
+        .modified
+        .map_or_else(|| format!("{:<24}", "-"), convert_time);
+
+    if args.style.plain || args.style.no_colors {
+        format!("{plain_mode} {plain_size} {plain_last_modified}")
+    } else {
+        format!(
+            "{} {} {}",
+            colorize_permission_bits(plain_mode),
+            Colour::Fixed(172).paint(plain_size),
+            Colour::Fixed(035).paint(plain_last_modified)
+        )
+    }
+}
+
+/// Get the metadata of an item for an exported document. The metadata of an
+/// item that is not on disk is used as is, without owners.
+pub fn get_export_metadata(item: &Path, metadata: Option<&ItemMetadata>) -> ExportMetadata {
+    if let Some(metadata) = metadata {
+        return ExportMetadata {
+            mode: metadata.mode.map(to_string),
+            modified: metadata.modified,
+            size: metadata.size,
+            ..Default::default()
+        };
+    }
+
+    match item.metadata() {
+        #[cfg(target_family = "unix")]
+        Ok(metadata) => ExportMetadata {
+            group: get_group_by_gid(metadata.gid())
+                .and_then(|group| group.name().to_str().map(|name| name.to_string())),
+            mode: Some(to_string(metadata.permissions().mode())),
+            modified: Some(metadata.mtime()),
+            size: Some(metadata.size()),
+            user: get_user_by_uid(metadata.uid())
+                .and_then(|user| user.name().to_str().map(|name| name.to_string())),
+        },
+        #[cfg(target_family = "windows")]
+        Ok(metadata) => ExportMetadata {
+            modified: i64::try_from(metadata.last_write_time()).ok(),
+            size: Some(metadata.file_size()),
+            ..Default::default()
+        },
+        Err(_) => ExportMetadata::default(),
+    }
+}
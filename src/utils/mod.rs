@@ -3,6 +3,7 @@
 pub mod bat;
 pub mod cache;
 pub mod export;
+pub mod html;
 pub mod icons;
 pub mod meta;
 pub mod open;
@@ -10,3 +11,5 @@ pub mod paint;
 pub mod paths;
 pub mod search;
 pub mod table;
+#[cfg(test)]
+pub mod test_utils;
@@ -44,16 +44,15 @@ pub fn get_filename(item: &Path) -> String {
         .to_string()
 }
 
-/// Get the symlinked item.
+/// Get the symlinked item. Displays the symlink's target as-is if it does not exist.
 pub fn get_symlink(item: &Path) -> String {
-    let points_to = read_link(item).map_or("?".to_string(), |pathbuf_path| {
-        pathbuf_path
-            .canonicalize()
-            .unwrap_or_else(|_| PathBuf::from("?"))
-            .into_os_string()
-            .into_string()
-            .map_or("?".to_string(), |path_string| path_string)
-    });
+    let points_to = item
+        .canonicalize()
+        .or_else(|_| read_link(item))
+        .unwrap_or_else(|_| PathBuf::from("?"))
+        .into_os_string()
+        .into_string()
+        .map_or("?".to_string(), |path_string| path_string);
 
     format!("⇒ {points_to}")
 }
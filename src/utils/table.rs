@@ -64,6 +64,27 @@ impl TableView for TabledItems<String> {
     }
 }
 
+impl TableView for TabledItems<Vec<String>> {
+    /// Display a table for rows of `String`s. Each row should contain a cell
+    /// for each label.
+    fn display_table(self) {
+        let mut table = Table::new();
+
+        table.max_column_width = self.table_width;
+        table.style = TableStyle::rounded();
+
+        table.add_row(Row::new(self.labels.iter().map(|label| {
+            TableCell::new(Colour::White.bold().paint(label).to_string())
+        })));
+
+        for row in self.items {
+            table.add_row(Row::new(row.into_iter().map(TableCell::new)));
+        }
+
+        println!("\n{}", table.render());
+    }
+}
+
 impl TableView for TabledItems<(&str, &String, &String)> {
     /// Display a table for items that need to be contained in a `(&str, &String, &String)`.
     fn display_table(self) {
@@ -0,0 +1,31 @@
+//! Fixtures shared by the tests.
+
+use tempfile::TempDir;
+
+use std::fs;
+
+/// Create a unique temporary directory containing the items. Each item is
+/// given as its path relative to the directory and its contents. Paths ending
+/// with `/` are created as empty directories, and parent directories are
+/// created as needed.
+///
+/// The directory is removed once the returned `TempDir` is dropped, so it does
+/// not outlive a failing test.
+pub fn create_fixture(items: &[(&str, &str)]) -> TempDir {
+    let fixture = TempDir::with_prefix("nomad_test_").expect("Could not create a fixture!");
+
+    for (path, contents) in items {
+        let item = fixture.path().join(path);
+
+        if path.ends_with('/') {
+            fs::create_dir_all(&item).expect("Could not create a fixture directory!");
+        } else {
+            if let Some(parent) = item.parent() {
+                fs::create_dir_all(parent).expect("Could not create a fixture directory!");
+            }
+            fs::write(&item, contents).expect("Could not create a fixture file!");
+        }
+    }
+
+    fixture
+}